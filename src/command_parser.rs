@@ -0,0 +1,945 @@
+//! Grammar of the commands typed in the input line.
+//!
+//! Input is split into tokens first. Words are separated by whitespace, and
+//! `"double"` or `'single'` quotes keep spaces inside a token; in double quotes
+//! `\"` and `\\` stand for a quote and a backslash. The first word selects the
+//! command, case-insensitively; input that doesn't start with a command is a
+//! new task and is kept exactly as typed.
+//!
+//! Commands that act on tasks take one or more targets. Selectors and quoted
+//! text are targets of their own, and a run of unquoted words is a single
+//! content query, so `complete 1 2 3.1` names three tasks and `complete Buy
+//! groceries` one. Selectors are
+//!
+//! - task indices: `3`, `1.2`
+//! - ranges of siblings: `1-5`, `1.2-1.4` (or `1.2-4`)
+//! - subtrees, every task below a task: `2.*`
+//! - searches, every task containing a text: `/milk`
+//! - tags, every task with a tag: `#work`
+//! - the tasks selected in the outliner: `@`
+//!
+//! and can be listed with commas: `1,3,4.2`.
+
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::commands::Command;
+use crate::completion;
+use crate::display_container::TaskIndex;
+use crate::recurrence::Recurrence;
+use crate::sorting::SortMode;
+use crate::taskstore::TaskContainer;
+
+/// Tasks a command acts on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A task by its index, like `1.2`
+    Index(TaskIndex),
+    /// Siblings from one index to another, like `1-5` or `1.2-1.4`
+    Range(TaskIndex, TaskIndex),
+    /// Every task below a task, like `2.*`
+    Subtree(TaskIndex),
+    /// Every task whose content contains a text, like `/milk`
+    Search(String),
+    /// Every task with a tag, like `#work`
+    Tag(String),
+    /// The tasks selected in the outliner, `@`
+    Selection,
+    /// A task matched by its content
+    Content(String),
+}
+
+impl Target {
+    /// Returns true if the target may name more than one task
+    pub const fn is_set(&self) -> bool {
+        !matches!(self, Self::Index(_) | Self::Content(_))
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{index}"),
+            Self::Range(start, end) => write!(f, "{start}-{end}"),
+            Self::Subtree(index) => write!(f, "{index}.*"),
+            Self::Search(text) => write!(f, "/{text}"),
+            Self::Tag(tag) => write!(f, "#{tag}"),
+            Self::Selection => write!(f, "@"),
+            Self::Content(content) => write!(f, "{content}"),
+        }
+    }
+}
+
+/// A word of the input line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    /// The word with quotes and escapes removed
+    text: String,
+    /// Whether the word was quoted
+    quoted: bool,
+    /// 1-based column where the word starts
+    column: usize,
+}
+
+/// What is wrong with a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A quote is never closed
+    UnterminatedQuote,
+    /// A closing quote is directly followed by more text
+    MissingSpaceAfterQuote,
+    /// A command that acts on tasks names none
+    MissingTask { command: &'static str },
+    /// A quoted target is empty
+    EmptyTask,
+    /// Unquoted words directly after a selector, like `2 apples`
+    UnquotedContent,
+    /// A command that acts on one task names several, or a set
+    TooManyTasks { command: &'static str },
+    /// A range whose ends aren't siblings, or that runs backwards
+    InvalidRange { range: String },
+    /// `tag` or `untag` without a tag, or with one that isn't a single word
+    InvalidTag { command: &'static str },
+    /// `due` without a date like `2026-10-31` or `none`
+    InvalidDate,
+    /// `expand to` with a depth of 0
+    InvalidDepth,
+    /// `repeat` without a rule like `mon,thu` or `none`
+    InvalidRecurrence,
+    /// A sort order that doesn't exist
+    UnknownSortMode { name: String, suggestion: Option<&'static str> },
+    /// `show` or `move` without a container
+    MissingContainer { command: &'static str },
+    /// A container name that doesn't exist or can't be used here
+    UnknownContainer {
+        name: String,
+        expected: &'static [&'static str],
+        suggestion: Option<&'static str>,
+    },
+    /// `subtask` without the text of the new subtask
+    MissingSubtaskText,
+    /// `session` without `new` or `resume`
+    MissingSubcommand { command: &'static str, expected: &'static str },
+    /// `session` followed by something else than `new` or `resume`
+    UnknownSubcommand { command: &'static str, found: String, expected: &'static str },
+    /// Arguments after a command that takes none
+    UnexpectedArgument { command: &'static str, argument: String },
+}
+
+/// A command that couldn't be parsed, and where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// 1-based column the error was found at
+    pub column: usize,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnterminatedQuote => write!(f, "quote is never closed"),
+            Self::MissingSpaceAfterQuote => write!(f, "expected a space after the closing quote"),
+            Self::MissingTask { command } => write!(f, "'{command}' expects a task"),
+            Self::EmptyTask => write!(f, "empty task"),
+            Self::UnquotedContent => write!(f, "quote task text that follows task numbers, like '1 \"Buy 2 apples\"'"),
+            Self::TooManyTasks { command } => write!(f, "'{command}' expects a single task"),
+            Self::InvalidRange { range } => write!(
+                f,
+                "range '{range}' must go forward between siblings, like '1-5' or '1.2-1.4'"
+            ),
+            Self::InvalidTag { command } => write!(f, "'{command}' expects a tag, like '{command} work 1-3'"),
+            Self::InvalidDate => write!(f, "'due' expects a date like '2026-10-31', or 'none'"),
+            Self::InvalidRecurrence => write!(f, "'repeat' expects a rule like 'day', 'mon,thu', '15th' or '3d', or 'none'"),
+            Self::InvalidDepth => write!(f, "'expand to' expects a number of levels, like 'expand to 2'"),
+            Self::UnknownSortMode { name, suggestion } => {
+                write!(f, "unknown sort order '{name}'")?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", did you mean '{suggestion}'?"),
+                    None => write!(f, ", expected one of: {}", SortMode::NAMES.join(", ")),
+                }
+            }
+            Self::MissingContainer { command } => write!(f, "'{command}' expects a container"),
+            Self::UnknownContainer { name, expected, suggestion } => {
+                write!(f, "unknown container '{name}'")?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", did you mean '{suggestion}'?"),
+                    None => write!(f, ", expected one of: {}", expected.join(", ")),
+                }
+            }
+            Self::MissingSubtaskText => write!(
+                f,
+                "'subtask' expects a task and the subtask text, e.g. 'subtask 1 Call back'"
+            ),
+            Self::MissingSubcommand { command, expected } => {
+                write!(f, "'{command}' expects {expected}")
+            }
+            Self::UnknownSubcommand { command, found, expected } => {
+                write!(f, "unknown '{command}' command '{found}', expected {expected}")
+            }
+            Self::UnexpectedArgument { command, argument } => {
+                write!(f, "'{command}' takes no arguments, got '{argument}'")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (column {})", self.kind, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Containers `show` switches to
+const SHOW_CONTAINERS: &[&str] = &["taskpad", "backburner", "shelved", "archived"];
+
+/// Containers tasks can be moved to; archiving is done with `complete`
+const MOVE_CONTAINERS: &[&str] = &["taskpad", "backburner", "shelved"];
+
+/// Returns true for task indices like `3`, `1.2` or `1.2.`
+pub fn is_task_index(text: &str) -> bool {
+    let text = text.strip_suffix('.').unwrap_or(text);
+    !text.is_empty()
+        && text
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Parses a line of input into a command.
+///
+/// Input that doesn't start with a command becomes `Command::Create` with the
+/// input unchanged.
+pub fn parse(input: &str) -> Result<Command, ParseError> {
+    let Some(keyword) = input.split_whitespace().next() else {
+        return Ok(Command::Create(input.to_string()));
+    };
+    let keyword = keyword.to_lowercase();
+    if !is_keyword(&keyword) {
+        return Ok(Command::Create(input.to_string()));
+    }
+
+    let tokens = tokenize(input)?;
+    let end = input.chars().count() + 1;
+    let args = &tokens[1..];
+
+    match keyword.as_str() {
+        "complete" | "c" => Ok(Command::Complete(targets("complete", args, end)?)),
+        "delete" | "rm" => Ok(Command::Delete(targets("delete", args, end)?)),
+        "tag" => {
+            let tag = tag("tag", args, end)?;
+            Ok(Command::Tag(tag, targets("tag", &args[1..], end)?))
+        }
+        "untag" => {
+            let tag = tag("untag", args, end)?;
+            Ok(Command::Untag(tag, targets("untag", &args[1..], end)?))
+        }
+        "move" | "mv" => parse_move(args, end),
+        "due" => {
+            let due = due_date(args, end)?;
+            Ok(Command::Due(due, targets("due", &args[1..], end)?))
+        }
+        "repeat" => {
+            let recurrence = recurrence(args, end)?;
+            Ok(Command::Repeat(recurrence, targets("repeat", &args[1..], end)?))
+        }
+        "sort" => {
+            let mode = sort_mode(args, end)?;
+            no_arguments("sort", &args[1..])?;
+            Ok(Command::Sort(mode))
+        }
+        "indent" => Ok(Command::Indent(targets("indent", args, end)?)),
+        "outdent" => Ok(Command::Outdent(targets("outdent", args, end)?)),
+        "focus" => Ok(Command::Focus(single_target("focus", args, end)?)),
+        "zoom" => match args {
+            [word] if is_word(word, "out") => Ok(Command::ZoomOut),
+            _ => Ok(Command::Zoom(single_target("zoom", args, end)?)),
+        },
+        "private" => Ok(Command::TogglePrivate(targets("private", args, end)?)),
+        "expand" => match args {
+            [word] if is_word(word, "all") => Ok(Command::ExpandAll),
+            [word, levels] if is_word(word, "to") => {
+                match levels.text.parse() {
+                    Ok(0) | Err(_) => Err(ParseError { kind: ParseErrorKind::InvalidDepth, column: levels.column }),
+                    Ok(depth) => Ok(Command::ExpandToDepth(depth)),
+                }
+            }
+            _ => Ok(Command::Expand(targets("expand", args, end)?)),
+        },
+        "collapse" => match args {
+            [word] if is_word(word, "all") => Ok(Command::CollapseAll),
+            _ => Ok(Command::Collapse(targets("collapse", args, end)?)),
+        },
+        "toggle" => {
+            let words: Vec<String> = args.iter().map(|t| t.text.to_lowercase()).collect();
+            if words == ["app", "mode"] {
+                Ok(Command::ToggleAppMode)
+            } else {
+                Ok(Command::Toggle(targets("toggle", args, end)?))
+            }
+        }
+        "show" => {
+            let container = container("show", args, end, SHOW_CONTAINERS)?;
+            no_arguments("show", &args[1..])?;
+            Ok(Command::Show(container))
+        }
+        "subtask" => parse_subtask(args, end),
+        "session" => parse_session(args, end),
+        "sessions" => no_arguments("sessions", args).map(|()| Command::ListSessions),
+        "login" => no_arguments("login", args).map(|()| Command::Login),
+        "logout" => no_arguments("logout", args).map(|()| Command::Logout),
+        _ => unreachable!("keyword list and grammar disagree on '{keyword}'"),
+    }
+}
+
+/// Returns true if a token is the unquoted word `word`, ignoring case
+fn is_word(token: &Token, word: &str) -> bool {
+    !token.quoted && token.text.eq_ignore_ascii_case(word)
+}
+
+/// Returns true if a lowercase word starts a command
+fn is_keyword(word: &str) -> bool {
+    matches!(
+        word,
+        "complete"
+            | "c"
+            | "delete"
+            | "rm"
+            | "move"
+            | "mv"
+            | "indent"
+            | "outdent"
+            | "focus"
+            | "zoom"
+            | "private"
+            | "toggle"
+            | "expand"
+            | "collapse"
+            | "tag"
+            | "untag"
+            | "due"
+            | "repeat"
+            | "sort"
+            | "show"
+            | "subtask"
+            | "session"
+            | "sessions"
+            | "login"
+            | "logout"
+    )
+}
+
+/// Splits input into words, honouring quotes
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().enumerate().peekable();
+
+    while let Some(&(index, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let column = index + 1;
+
+        if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            let mut closed = false;
+            while let Some((_, c2)) = chars.next() {
+                match c2 {
+                    '\\' if c == '"' => {
+                        // Only quotes and backslashes are escaped, anything else stays as typed
+                        match chars.peek() {
+                            Some(&(_, next @ ('"' | '\\'))) => {
+                                text.push(next);
+                                chars.next();
+                            }
+                            _ => text.push('\\'),
+                        }
+                    }
+                    _ if c2 == c => {
+                        closed = true;
+                        break;
+                    }
+                    _ => text.push(c2),
+                }
+            }
+            if !closed {
+                return Err(ParseError { kind: ParseErrorKind::UnterminatedQuote, column });
+            }
+            if let Some(&(next_index, next)) = chars.peek() {
+                if !next.is_whitespace() {
+                    return Err(ParseError {
+                        kind: ParseErrorKind::MissingSpaceAfterQuote,
+                        column: next_index + 1,
+                    });
+                }
+            }
+            tokens.push(Token { text, quoted: true, column });
+        } else {
+            // Quotes inside a word, like in "don't", are part of it
+            let mut text = String::new();
+            while let Some(&(_, c2)) = chars.peek() {
+                if c2.is_whitespace() {
+                    break;
+                }
+                text.push(c2);
+                chars.next();
+            }
+            tokens.push(Token { text, quoted: false, column });
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses a single selector like `1.2`, `1-5`, `2.*`, `/milk`, `#work` or `@`.
+///
+/// Returns `None` for anything else, which then counts as content.
+fn selector(text: &str, column: usize) -> Option<Result<Target, ParseError>> {
+    if text == "@" {
+        return Some(Ok(Target::Selection));
+    }
+    if let Some(search) = text.strip_prefix('/') {
+        return (!search.is_empty()).then(|| Ok(Target::Search(search.to_string())));
+    }
+    if let Some(tag) = text.strip_prefix('#') {
+        return (!tag.is_empty()).then(|| Ok(Target::Tag(tag.to_lowercase())));
+    }
+    if let Some(parent) = text.strip_suffix(".*") {
+        return task_index(parent).map(|index| Ok(Target::Subtree(index)));
+    }
+    if let Some((start, end)) = text.split_once('-') {
+        if !is_task_index(start) || !is_task_index(end) {
+            return None;
+        }
+        let invalid = || ParseError { kind: ParseErrorKind::InvalidRange { range: text.to_string() }, column };
+        let (Some(start), Some(mut end)) = (task_index(start), task_index(end)) else {
+            return Some(Err(invalid()));
+        };
+        // `1.2-4` is short for `1.2-1.4`
+        if end.path.len() == 1 && start.path.len() > 1 {
+            let mut path = start.path[..start.path.len() - 1].to_vec();
+            path.push(end.path[0]);
+            end = TaskIndex { path };
+        }
+        let siblings = start.path.len() == end.path.len()
+            && start.path[..start.path.len() - 1] == end.path[..end.path.len() - 1];
+        if !siblings || start.path.last() > end.path.last() {
+            return Some(Err(invalid()));
+        }
+        return Some(Ok(Target::Range(start, end)));
+    }
+    task_index(text).map(|index| Ok(Target::Index(index)))
+}
+
+/// Parses a task index, if `text` is one
+fn task_index(text: &str) -> Option<TaskIndex> {
+    if is_task_index(text) {
+        TaskIndex::from_str(text).ok()
+    } else {
+        None
+    }
+}
+
+/// Parses a word made of comma-separated selectors, like `1,3-4,2.*`.
+///
+/// Returns `None` if any part isn't a selector, so `apples,` stays content.
+fn selectors(token: &Token) -> Option<Result<Vec<Target>, ParseError>> {
+    let mut targets = Vec::new();
+    let mut offset = 0;
+    for part in token.text.split(',') {
+        let column = token.column + offset;
+        offset += part.chars().count() + 1;
+        if part.is_empty() {
+            continue;
+        }
+        match selector(part, column)? {
+            Ok(target) => targets.push(target),
+            Err(error) => return Some(Err(error)),
+        }
+    }
+    (!targets.is_empty()).then_some(Ok(targets))
+}
+
+/// Groups arguments into targets.
+///
+/// Selectors and quoted words are targets of their own. Unquoted words are
+/// joined into one content query up to the next quoted word, so numbers inside
+/// content ("Buy 2 apples") stay part of it. Unquoted words right after a
+/// selector are an error, since `2 apples` could be task 2 or one content query.
+fn group_targets(args: &[Token]) -> Result<Vec<Target>, ParseError> {
+    let mut targets = Vec::new();
+    let mut content: Vec<&str> = Vec::new();
+    let mut after_selector = false;
+
+    for token in args {
+        if token.quoted {
+            after_selector = false;
+            if !content.is_empty() {
+                targets.push(Target::Content(content.join(" ")));
+                content.clear();
+            }
+            if token.text.trim().is_empty() {
+                return Err(ParseError { kind: ParseErrorKind::EmptyTask, column: token.column });
+            }
+            targets.push(Target::Content(token.text.clone()));
+            continue;
+        }
+        if content.is_empty() {
+            if let Some(selected) = selectors(token) {
+                targets.extend(selected?);
+                after_selector = true;
+                continue;
+            }
+            if after_selector {
+                return Err(ParseError { kind: ParseErrorKind::UnquotedContent, column: token.column });
+            }
+        }
+        content.push(&token.text);
+    }
+    if !content.is_empty() {
+        targets.push(Target::Content(content.join(" ")));
+    }
+
+    Ok(targets)
+}
+
+/// The targets of a command acting on tasks; at least one is required
+fn targets(command: &'static str, args: &[Token], end: usize) -> Result<Vec<Target>, ParseError> {
+    let targets = group_targets(args)?;
+    if targets.is_empty() {
+        return Err(ParseError { kind: ParseErrorKind::MissingTask { command }, column: end });
+    }
+    Ok(targets)
+}
+
+/// The target of a command acting on exactly one task
+fn single_target(command: &'static str, args: &[Token], end: usize) -> Result<String, ParseError> {
+    let targets = targets(command, args, end)?;
+    match targets.as_slice() {
+        [target] if !target.is_set() => Ok(target.to_string()),
+        _ => Err(ParseError {
+            kind: ParseErrorKind::TooManyTasks { command },
+            column: args[0].column,
+        }),
+    }
+}
+
+/// The tag named by the first argument, lowercase and without `#`
+fn tag(command: &'static str, args: &[Token], end: usize) -> Result<String, ParseError> {
+    let Some(token) = args.first() else {
+        return Err(ParseError { kind: ParseErrorKind::InvalidTag { command }, column: end });
+    };
+    let tag = token.text.strip_prefix('#').unwrap_or(&token.text).to_lowercase();
+    if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err(ParseError { kind: ParseErrorKind::InvalidTag { command }, column: token.column });
+    }
+    Ok(tag)
+}
+
+/// The due date named by the first argument, `None` for `none`
+fn due_date(args: &[Token], end: usize) -> Result<Option<NaiveDate>, ParseError> {
+    let Some(token) = args.first() else {
+        return Err(ParseError { kind: ParseErrorKind::InvalidDate, column: end });
+    };
+    if token.text.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(&token.text, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| ParseError { kind: ParseErrorKind::InvalidDate, column: token.column })
+}
+
+/// The recurrence rule named by the first argument, `None` for `none`
+fn recurrence(args: &[Token], end: usize) -> Result<Option<Recurrence>, ParseError> {
+    let Some(token) = args.first() else {
+        return Err(ParseError { kind: ParseErrorKind::InvalidRecurrence, column: end });
+    };
+    if token.text.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    Recurrence::parse(&token.text)
+        .map(Some)
+        .ok_or(ParseError { kind: ParseErrorKind::InvalidRecurrence, column: token.column })
+}
+
+/// The sort mode named by the first argument
+fn sort_mode(args: &[Token], end: usize) -> Result<SortMode, ParseError> {
+    let Some(token) = args.first() else {
+        return Err(ParseError {
+            kind: ParseErrorKind::MissingSubcommand {
+                command: "sort",
+                expected: "a sort order: manual, created, due, status or name",
+            },
+            column: end,
+        });
+    };
+    SortMode::from_name(&token.text).ok_or_else(|| ParseError {
+        kind: ParseErrorKind::UnknownSortMode {
+            name: token.text.clone(),
+            suggestion: completion::closest(&token.text, SortMode::NAMES),
+        },
+        column: token.column,
+    })
+}
+
+/// The container named by the first argument
+fn container(
+    command: &'static str,
+    args: &[Token],
+    end: usize,
+    expected: &'static [&'static str],
+) -> Result<TaskContainer, ParseError> {
+    let Some(token) = args.first() else {
+        return Err(ParseError { kind: ParseErrorKind::MissingContainer { command }, column: end });
+    };
+    let name = token.text.to_lowercase();
+    match TaskContainer::from_name(&name) {
+        Some(container) if expected.contains(&name.as_str()) => Ok(container),
+        _ => Err(ParseError {
+            kind: ParseErrorKind::UnknownContainer {
+                name: token.text.clone(),
+                expected,
+                suggestion: completion::closest(&name, expected),
+            },
+            column: token.column,
+        }),
+    }
+}
+
+/// Fails if there are arguments left
+fn no_arguments(command: &'static str, args: &[Token]) -> Result<(), ParseError> {
+    match args.first() {
+        Some(token) => Err(ParseError {
+            kind: ParseErrorKind::UnexpectedArgument { command, argument: token.text.clone() },
+            column: token.column,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// `move [to] <container> <targets>` or `move <targets> under <task>`
+fn parse_move(args: &[Token], end: usize) -> Result<Command, ParseError> {
+    let is_word = |token: &Token, word: &str| !token.quoted && token.text.eq_ignore_ascii_case(word);
+    let names_container = args
+        .iter()
+        .find(|token| !is_word(token, "to"))
+        .is_some_and(|token| TaskContainer::from_name(&token.text).is_some());
+    if let Some(under) = args.iter().position(|token| is_word(token, "under")).filter(|_| !names_container) {
+        let tasks = targets("move", &args[..under], args[under].column)?;
+        let parent = single_target("move", &args[under + 1..], end)?;
+        return Ok(Command::MoveUnder(tasks, parent));
+    }
+
+    let args = match args.first() {
+        Some(token) if is_word(token, "to") => &args[1..],
+        _ => args,
+    };
+    let container = container("move", args, end, MOVE_CONTAINERS)?;
+    let targets = targets("move", &args[1..], end)?;
+    Ok(match container {
+        TaskContainer::Backburner => Command::MoveToBackburner(targets),
+        TaskContainer::Shelved => Command::MoveToShelved(targets),
+        _ => Command::MoveToTaskpad(targets),
+    })
+}
+
+/// `subtask <task> <text>` or `subtask <task content> | <text>`
+fn parse_subtask(args: &[Token], end: usize) -> Result<Command, ParseError> {
+    let missing_text = ParseError { kind: ParseErrorKind::MissingSubtaskText, column: end };
+    let join = |tokens: &[Token]| tokens.iter().map(|t| t.text.as_str()).collect::<Vec<_>>().join(" ");
+
+    if let Some(bar) = args.iter().position(|t| !t.quoted && t.text == "|") {
+        let parent = join(&args[..bar]);
+        if parent.is_empty() {
+            return Err(ParseError {
+                kind: ParseErrorKind::MissingTask { command: "subtask" },
+                column: args[bar].column,
+            });
+        }
+        let text = join(&args[bar + 1..]);
+        if text.is_empty() {
+            return Err(missing_text);
+        }
+        return Ok(Command::AddSubtask(parent, text));
+    }
+
+    let Some(parent) = args.first() else {
+        return Err(ParseError { kind: ParseErrorKind::MissingTask { command: "subtask" }, column: end });
+    };
+    let text = join(&args[1..]);
+    if text.is_empty() {
+        return Err(missing_text);
+    }
+    Ok(Command::AddSubtask(parent.text.clone(), text))
+}
+
+/// `session new` or `session resume <session>`
+fn parse_session(args: &[Token], end: usize) -> Result<Command, ParseError> {
+    const EXPECTED: &str = "'new' or 'resume'";
+    let Some(subcommand) = args.first() else {
+        return Err(ParseError {
+            kind: ParseErrorKind::MissingSubcommand { command: "session", expected: EXPECTED },
+            column: end,
+        });
+    };
+    match subcommand.text.to_lowercase().as_str() {
+        "new" => no_arguments("session new", &args[1..]).map(|()| Command::NewSession),
+        "resume" => match args.get(1) {
+            Some(session) => {
+                no_arguments("session resume", &args[2..])?;
+                Ok(Command::ResumeSession(session.text.clone()))
+            }
+            None => Err(ParseError {
+                kind: ParseErrorKind::MissingSubcommand {
+                    command: "session resume",
+                    expected: "a session number or ID",
+                },
+                column: end,
+            }),
+        },
+        _ => Err(ParseError {
+            kind: ParseErrorKind::UnknownSubcommand {
+                command: "session",
+                found: subcommand.text.clone(),
+                expected: EXPECTED,
+            },
+            column: subcommand.column,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn index(text: &str) -> Target {
+        Target::Index(TaskIndex::from_str(text).unwrap())
+    }
+
+    fn content(text: &str) -> Target {
+        Target::Content(text.to_string())
+    }
+
+    fn error(input: &str) -> ParseError {
+        parse(input).expect_err("input should not parse")
+    }
+
+    #[test]
+    fn test_targets_and_quoting() {
+        assert_eq!(parse("complete 1 2 3.1").unwrap(), Command::Complete(vec![index("1"), index("2"), index("3.1")]));
+        assert_eq!(parse("c Buy groceries  ").unwrap(), Command::Complete(vec![content("Buy groceries")]));
+        assert_eq!(
+            parse(r#"rm 2 "Call dentist" 'Write report'"#).unwrap(),
+            Command::Delete(vec![index("2"), content("Call dentist"), content("Write report")])
+        );
+        assert_eq!(parse("delete Buy 2 apples").unwrap(), Command::Delete(vec![content("Buy 2 apples")]));
+        assert_eq!(parse("delete 1 'Buy 2 apples'").unwrap(), Command::Delete(vec![index("1"), content("Buy 2 apples")]));
+        assert_eq!(
+            parse("delete 'Call dentist' 2 3").unwrap(),
+            Command::Delete(vec![content("Call dentist"), index("2"), index("3")])
+        );
+        // `2 apples` could be task 2 or a task containing "2 apples"
+        assert_eq!(error("delete 2 apples"), ParseError { kind: ParseErrorKind::UnquotedContent, column: 10 });
+        assert_eq!(error("delete 1 'Buy' 2 apples").column, 18);
+        assert_eq!(
+            parse(r#"focus "say \"hi\" to \\ Bob""#).unwrap(),
+            Command::Focus(r#"say "hi" to \ Bob"#.to_string())
+        );
+        assert_eq!(parse("focus don't panic").unwrap(), Command::Focus("don't panic".to_string()));
+    }
+
+    #[test]
+    fn test_keywords_and_aliases() {
+        assert_eq!(parse("MV backburner 2").unwrap(), Command::MoveToBackburner(vec![index("2")]));
+        assert_eq!(parse("Move To Shelved 1.1").unwrap(), Command::MoveToShelved(vec![index("1.1")]));
+        assert_eq!(parse("Show ARCHIVED").unwrap(), Command::Show(TaskContainer::Archived));
+        assert_eq!(parse("Toggle App Mode").unwrap(), Command::ToggleAppMode);
+        assert_eq!(parse("toggle 3").unwrap(), Command::Toggle(vec![index("3")]));
+        assert_eq!(
+            parse("subtask Buy groceries | Milk and eggs").unwrap(),
+            Command::AddSubtask("Buy groceries".to_string(), "Milk and eggs".to_string())
+        );
+        assert_eq!(parse("Session Resume 2").unwrap(), Command::ResumeSession("2".to_string()));
+        assert_eq!(parse("  Buy milk ").unwrap(), Command::Create("  Buy milk ".to_string()));
+        assert_eq!(parse("").unwrap(), Command::Create(String::new()));
+    }
+
+    #[test]
+    fn test_selectors() {
+        let range = |start: &str, end: &str| {
+            Target::Range(TaskIndex::from_str(start).unwrap(), TaskIndex::from_str(end).unwrap())
+        };
+        assert_eq!(
+            parse("complete 1-5 2.* 1,3,4.2").unwrap(),
+            Command::Complete(vec![
+                range("1", "5"),
+                Target::Subtree(TaskIndex::from_str("2").unwrap()),
+                index("1"),
+                index("3"),
+                index("4.2"),
+            ])
+        );
+        assert_eq!(
+            parse("rm 1.2-4,/Milk #Work").unwrap(),
+            Command::Delete(vec![range("1.2", "1.4"), Target::Search("Milk".to_string()), Target::Tag("work".to_string())])
+        );
+        assert_eq!(
+            parse("tag #Errands 1-2 'Buy apples, pears'").unwrap(),
+            Command::Tag("errands".to_string(), vec![range("1", "2"), content("Buy apples, pears")])
+        );
+        assert_eq!(parse("untag work #work").unwrap(), Command::Untag("work".to_string(), vec![Target::Tag("work".to_string())]));
+        assert_eq!(parse("mv shelved @").unwrap(), Command::MoveToShelved(vec![Target::Selection]));
+        assert_eq!(parse("complete @,3").unwrap(), Command::Complete(vec![Target::Selection, index("3")]));
+
+        assert_eq!(error("complete 5-1").kind, ParseErrorKind::InvalidRange { range: "5-1".to_string() });
+        assert_eq!(error("complete 3,1.2-2.4").column, 12);
+        assert_eq!(error("focus 1-3").kind, ParseErrorKind::TooManyTasks { command: "focus" });
+        assert_eq!(error("tag").kind, ParseErrorKind::InvalidTag { command: "tag" });
+        assert_eq!(error("tag work").kind, ParseErrorKind::MissingTask { command: "tag" });
+    }
+
+    #[test]
+    fn test_hierarchy_commands() {
+        assert_eq!(
+            parse("move 3.2 under 5").unwrap(),
+            Command::MoveUnder(vec![index("3.2")], "5".to_string())
+        );
+        assert_eq!(
+            parse("mv 1-2 \"Buy milk\" UNDER Plan trip").unwrap(),
+            Command::MoveUnder(
+                vec![Target::Range(TaskIndex::from_str("1").unwrap(), TaskIndex::from_str("2").unwrap()), content("Buy milk")],
+                "Plan trip".to_string()
+            )
+        );
+        // A container comes first, so "under" is part of the task
+        assert_eq!(
+            parse("move to shelved Look under bed").unwrap(),
+            Command::MoveToShelved(vec![content("Look under bed")])
+        );
+        assert_eq!(parse("indent 2 3").unwrap(), Command::Indent(vec![index("2"), index("3")]));
+        assert_eq!(parse("outdent @").unwrap(), Command::Outdent(vec![Target::Selection]));
+
+        assert_eq!(error("move under 5").kind, ParseErrorKind::MissingTask { command: "move" });
+        assert_eq!(error("move under 5").column, 6);
+        assert_eq!(error("move 3 under 4 5").kind, ParseErrorKind::TooManyTasks { command: "move" });
+        assert_eq!(error("move 3 under").kind, ParseErrorKind::MissingTask { command: "move" });
+
+        assert_eq!(parse("zoom 3.2").unwrap(), Command::Zoom("3.2".to_string()));
+        assert_eq!(parse("Zoom OUT").unwrap(), Command::ZoomOut);
+        assert_eq!(parse("zoom \"out\"").unwrap(), Command::Zoom("out".to_string()));
+        assert_eq!(error("zoom").kind, ParseErrorKind::MissingTask { command: "zoom" });
+        assert_eq!(error("zoom 1 2").kind, ParseErrorKind::TooManyTasks { command: "zoom" });
+
+        assert_eq!(parse("expand 2 3.1").unwrap(), Command::Expand(vec![index("2"), index("3.1")]));
+        assert_eq!(parse("expand all").unwrap(), Command::ExpandAll);
+        assert_eq!(parse("expand 'all'").unwrap(), Command::Expand(vec![content("all")]));
+        assert_eq!(parse("expand to 3").unwrap(), Command::ExpandToDepth(3));
+        assert_eq!(parse("COLLAPSE All").unwrap(), Command::CollapseAll);
+        assert_eq!(parse("collapse @").unwrap(), Command::Collapse(vec![Target::Selection]));
+        assert_eq!(error("expand to 0").kind, ParseErrorKind::InvalidDepth);
+        assert_eq!(error("expand to 0").column, 11);
+        assert_eq!(error("collapse").kind, ParseErrorKind::MissingTask { command: "collapse" });
+    }
+
+    #[test]
+    fn test_due_and_sort() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 31);
+        assert_eq!(parse("due 2026-10-31 1-2").unwrap(), Command::Due(date, vec![Target::Range(
+            TaskIndex::from_str("1").unwrap(),
+            TaskIndex::from_str("2").unwrap()
+        )]));
+        assert_eq!(parse("Due NONE Buy milk").unwrap(), Command::Due(None, vec![content("Buy milk")]));
+        assert_eq!(parse("sort Name").unwrap(), Command::Sort(SortMode::Alphabetical));
+
+        assert_eq!(error("due 31.10.2026 1").to_string(), "'due' expects a date like '2026-10-31', or 'none' (column 5)");
+        assert_eq!(error("due 2026-02-30 1").kind, ParseErrorKind::InvalidDate);
+        assert_eq!(error("due none").kind, ParseErrorKind::MissingTask { command: "due" });
+
+        assert_eq!(
+            parse("repeat mon,thu 3").unwrap(),
+            Command::Repeat(Some(Recurrence::Weekly(vec![chrono::Weekday::Mon, chrono::Weekday::Thu])), vec![index("3")])
+        );
+        assert_eq!(parse("repeat none @").unwrap(), Command::Repeat(None, vec![Target::Selection]));
+        assert_eq!(error("repeat often 3").kind, ParseErrorKind::InvalidRecurrence);
+        assert_eq!(error("repeat often 3").column, 8);
+        assert_eq!(error("repeat 3d").kind, ParseErrorKind::MissingTask { command: "repeat" });
+        assert_eq!(error("sort stauts").to_string(), "unknown sort order 'stauts', did you mean 'status'? (column 6)");
+        assert_eq!(error("sort").to_string(), "'sort' expects a sort order: manual, created, due, status or name (column 5)");
+        assert_eq!(error("sort due 1").kind, ParseErrorKind::UnexpectedArgument { command: "sort", argument: "1".to_string() });
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(error("complete \"Buy milk").to_string(), "quote is never closed (column 10)");
+        assert_eq!(error("complete \"a\"b").kind, ParseErrorKind::MissingSpaceAfterQuote);
+        assert_eq!(error("complete").to_string(), "'complete' expects a task (column 9)");
+        assert_eq!(error("focus 1 2").kind, ParseErrorKind::TooManyTasks { command: "focus" });
+        assert_eq!(
+            error("show archivd").to_string(),
+            "unknown container 'archivd', did you mean 'archived'? (column 6)"
+        );
+        assert_eq!(
+            error("move to archived 1").to_string(),
+            "unknown container 'archived', expected one of: taskpad, backburner, shelved (column 9)"
+        );
+        assert_eq!(error("subtask 1").kind, ParseErrorKind::MissingSubtaskText);
+        assert_eq!(error("session").column, 8);
+        assert_eq!(error("logout now").to_string(), "'logout' takes no arguments, got 'now' (column 8)");
+        assert_eq!(error("c ''").kind, ParseErrorKind::EmptyTask);
+    }
+
+    fn index_text() -> impl Strategy<Value = String> {
+        prop::collection::vec(1..100u32, 1..4)
+            .prop_map(|parts| parts.iter().map(u32::to_string).collect::<Vec<_>>().join("."))
+    }
+
+    fn quote(text: &str) -> String {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(input in "\\PC*") {
+            let _ = parse(&input);
+        }
+
+        #[test]
+        fn keywords_ignore_case(
+            keyword in prop::sample::select(vec!["complete", "delete", "focus", "private", "toggle", "show", "rm"]),
+            rest in "[a-z0-9 .\"']{0,20}",
+            upper in prop::collection::vec(any::<bool>(), 8),
+        ) {
+            let mixed: String = keyword
+                .chars()
+                .zip(upper.iter().cycle())
+                .map(|(c, &up)| if up { c.to_ascii_uppercase() } else { c })
+                .collect();
+            prop_assert_eq!(parse(&format!("{mixed} {rest}")), parse(&format!("{keyword} {rest}")));
+        }
+
+        #[test]
+        fn every_index_is_a_target(indices in prop::collection::vec(index_text(), 1..6), commas in any::<bool>()) {
+            let input = format!("complete {}", indices.join(if commas { "," } else { " " }));
+            let targets = indices.iter().map(|i| index(i)).collect();
+            prop_assert_eq!(parse(&input).unwrap(), Command::Complete(targets));
+        }
+
+        #[test]
+        fn quoted_targets_round_trip(texts in prop::collection::vec("[a-z\"\\\\ ]{0,5}[a-z][a-z\"\\\\ ]{0,5}", 1..4)) {
+            let input = format!("delete {}", texts.iter().map(|t| quote(t)).collect::<Vec<_>>().join(" "));
+            let targets = texts.iter().map(|t| content(t)).collect();
+            prop_assert_eq!(parse(&input).unwrap(), Command::Delete(targets));
+        }
+
+        #[test]
+        fn other_input_becomes_a_task(word in "[A-Za-z]{1,12}", rest in "\\PC{0,30}") {
+            prop_assume!(!is_keyword(&word.to_lowercase()));
+            let input = format!("{word} {rest}");
+            prop_assert_eq!(parse(&input).unwrap(), Command::Create(input.clone()));
+        }
+    }
+}
@@ -1,100 +1,134 @@
 //! Command processing for cyberorganism. Translates user
 //! input into task management operations.
 
-use chrono::Utc;
+use chrono::{Local, NaiveDate, Utc};
+use log::warn;
 
 use crate::App;
-use crate::debug::log_debug;
+use crate::command_parser::{self, ParseError, ParseErrorKind, Target};
+use crate::completion;
+use crate::display_container::TaskIndex;
+use crate::credentials::{
+    Credentials, CredentialsError, Secret, delete_credentials, load_credentials, save_credentials,
+};
+use crate::genius_platform::GeniusItem;
+use crate::genius_platform::pinboard::save_pins;
+use crate::genius_platform::sessions::save_sessions;
+use crate::input_history::save_history;
+use crate::links::{self, LinkTarget};
+use crate::recurrence::Recurrence;
+use crate::sorting::{SortMode, save_sort_modes};
+use crate::taskstore::operations::{self, Placement};
 use crate::taskstore::{
-    Task, TaskContainer, TaskStatus, find_task_by_content, find_task_by_id, save_tasks,
+    Task, TaskContainer, TaskSource, TaskStatus, find_task_by_content, find_task_by_content_where, find_task_by_id,
+    save_tasks,
 };
 
 /// Represents the application interaction mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum AppMode {
     /// Default mode for PKM task management
+    #[default]
     Pkm,
     /// Mode for interacting with Genius Feed
     Feed,
 }
 
-impl Default for AppMode {
-    fn default() -> Self {
-        Self::Pkm
+/// Step of the `login` flow answered by the next line of input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CredentialPrompt {
+    /// Asking for the Genius API key
+    ApiKey,
+    /// Asking for the organization ID that goes with the key
+    OrganizationId { api_key: Secret },
+    /// Asking for a passphrase to encrypt the new credentials with
+    NewPassphrase { credentials: Credentials },
+    /// Asking for the passphrase of the saved credentials
+    Unlock,
+}
+
+impl CredentialPrompt {
+    /// Text shown while waiting for the answer
+    pub const fn message(&self) -> &'static str {
+        match self {
+            Self::ApiKey => "Enter your Genius API key (Escape to cancel)",
+            Self::OrganizationId { .. } => "Enter your Genius organization ID",
+            Self::NewPassphrase { .. } => "Choose a passphrase to encrypt the API key",
+            Self::Unlock => "Enter your passphrase to unlock the Genius API key",
+        }
+    }
+
+    /// Returns true if the answer must be masked in the input field
+    pub const fn is_secret(&self) -> bool {
+        !matches!(self, Self::OrganizationId { .. })
     }
 }
 
 /// Commands that can be executed by the user
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Command {
     Create(String),
-    Complete(String),
+    Complete(Vec<Target>),
     CompleteById(u32),
-    Delete(String),
-    MoveToTaskpad(String),
-    MoveToBackburner(String),
-    MoveToShelved(String),
+    Delete(Vec<Target>),
+    MoveToTaskpad(Vec<Target>),
+    MoveToBackburner(Vec<Target>),
+    MoveToShelved(Vec<Target>),
+    MoveUnder(Vec<Target>, String), // (tasks, new parent query)
+    Indent(Vec<Target>),        // Make tasks subtasks of the task above
+    Outdent(Vec<Target>),       // Move subtasks up to their parent's level
+    MoveUp(Vec<Target>),        // Move tasks above their previous sibling
+    MoveDown(Vec<Target>),      // Move tasks below their next sibling
+    Place(Vec<Target>, Placement), // Drop tasks next to or under a task
     Edit(u32, String),          // (task_id, new_content)
+    EditNotes(u32, String),     // (task_id, new notes)
     Focus(String),              // Focus on a task by index or content
     Show(TaskContainer),        // Switch active container
+    Zoom(String),               // Show only the subtasks of a task
+    ZoomOut,                    // Zoom out to the parent of the zoomed task
     AddSubtask(String, String), // (parent_query, subtask_content)
-    Toggle(String),             // Toggle expansion state of a task
+    Toggle(Vec<Target>),        // Toggle expansion state of tasks
+    Expand(Vec<Target>),        // Expand tasks and everything below them
+    Collapse(Vec<Target>),      // Collapse tasks and everything below them
+    ExpandAll,                  // Expand every task of the view
+    CollapseAll,                // Collapse every task of the view
+    ExpandToDepth(usize),       // Show a number of levels of tasks
+    TogglePrivate(Vec<Target>), // Mark tasks as private or public
+    Tag(String, Vec<Target>),   // Add a tag to tasks
+    Due(Option<NaiveDate>, Vec<Target>), // Set or clear the due date of tasks
+    Repeat(Option<Recurrence>, Vec<Target>), // Set or clear the recurrence of tasks
+    Sort(SortMode),             // Sort the active container
+    Untag(String, Vec<Target>), // Remove a tag from tasks
     ToggleAppMode,              // Toggle application mode
+    Login,                      // Unlock or store Genius API credentials
+    Logout,                     // Forget the Genius API credentials
+    NewSession,                 // Start a new Genius feed session
+    ResumeSession(String),      // Switch back to a Genius feed session
+    ListSessions,               // List the Genius feed sessions
+    Invalid(ParseError),        // Input that starts like a command but doesn't parse
 }
 
 /// Parses the input string into a Command
-#[allow(clippy::option_if_let_else)]
+///
+/// See `command_parser` for the grammar. Input that starts with a command
+/// keyword but doesn't parse becomes `Command::Invalid`.
 pub fn parse_command(input: String) -> Command {
-    if let Some(task_query) = input.strip_prefix("complete ") {
-        Command::Complete(task_query.to_string())
-    } else if let Some(task_query) = input.strip_prefix("delete ") {
-        Command::Delete(task_query.to_string())
-    } else if let Some(task_query) = input.strip_prefix("move to taskpad ") {
-        Command::MoveToTaskpad(task_query.to_string())
-    } else if let Some(task_query) = input.strip_prefix("move to backburner ") {
-        Command::MoveToBackburner(task_query.to_string())
-    } else if let Some(task_query) = input.strip_prefix("move to shelved ") {
-        Command::MoveToShelved(task_query.to_string())
-    } else if let Some(task_query) = input.strip_prefix("focus ") {
-        Command::Focus(task_query.to_string())
-    } else if let Some(task_query) = input.strip_prefix("toggle ") {
-        Command::Toggle(task_query.to_string())
-    } else if let Some(container) = input.strip_prefix("show ") {
-        match container {
-            "taskpad" => Command::Show(TaskContainer::Taskpad),
-            "backburner" => Command::Show(TaskContainer::Backburner),
-            "shelved" => Command::Show(TaskContainer::Shelved),
-            "archived" => Command::Show(TaskContainer::Archived),
-            _ => Command::Create(input), // Invalid container, treat as task creation
-        }
-    } else if let Some(task_query) = input.strip_prefix("subtask ") {
-        // Format: "subtask <parent_query> <content>"
-        let parts: Vec<&str> = task_query.splitn(2, ' ').collect();
-        if parts.len() == 2 {
-            return Command::AddSubtask(parts[0].to_string(), parts[1].to_string());
-        }
-        Command::Create(input) // Invalid format, treat as task creation
-    } else if input.to_lowercase() == "toggle app mode" {
-        Command::ToggleAppMode
-    } else {
-        Command::Create(input)
-    }
+    command_parser::parse(&input).unwrap_or_else(Command::Invalid)
 }
 
 /// Result of executing a command
 enum CommandResult {
-    TaskCompleted { content: String },
+    TaskCompleted { content: String, parents: Vec<String>, next_due: Option<NaiveDate> },
     TaskAlreadyArchived(String),
     NoMatchingTask,
 }
 
 /// Finds a task by display index (including dot notation) or content match
 fn find_task(app: &App, query: &str) -> Option<usize> {
-    use regex::Regex;
     let query = query.trim();
 
-    // Check for dot notation pattern (e.g., "1.2.3" or "1.2.")
-    let dot_pattern = Regex::new(r"^\d+(\.\d+)*\.?$").unwrap();
-    if dot_pattern.is_match(query) {
+    // Check for dot notation (e.g., "3", "1.2.3" or "1.2.")
+    if command_parser::is_task_index(query) {
         // Remove trailing dot if present
         let clean_query = query.trim_end_matches('.');
         if let Some(task_id) = app
@@ -104,16 +138,13 @@ fn find_task(app: &App, query: &str) -> Option<usize> {
             return find_task_by_id(&app.tasks, task_id);
         }
     }
-    // Check for simple index (backwards compatibility)
-    else if query.chars().all(|c| c.is_ascii_digit()) {
-        if let Ok(index) = query.parse::<usize>() {
-            // Convert single number to dot notation
-            if let Some(task_id) = app
-                .display_container_state
-                .get_task_id_by_path(&index.to_string(), &app.tasks)
-            {
-                return find_task_by_id(&app.tasks, task_id);
-            }
+
+    // While zoomed, tasks below the zoomed task come first
+    let state = &app.display_container_state;
+    if state.zoom.is_some() {
+        let in_view = find_task_by_content_where(&app.tasks, query, |task| state.in_view(task, &app.tasks));
+        if in_view.is_some() {
+            return in_view;
         }
     }
 
@@ -125,155 +156,537 @@ fn find_task(app: &App, query: &str) -> Option<usize> {
     )
 }
 
-/// Completes a task by content match or task ID
-fn complete_task(app: &mut App, query: &str, task_id: Option<u32>) -> CommandResult {
-    // If task_id is provided, complete that task directly
-    if let Some(id) = task_id {
-        if let Some(task) = app.tasks.iter_mut().find(|t| t.id == id) {
-            if matches!(task.container(), TaskContainer::Archived) {
-                return CommandResult::TaskAlreadyArchived(task.content.clone());
+/// IDs of the tasks a target names, in display order
+fn resolve_target(app: &App, target: &Target) -> Vec<u32> {
+    let state = &app.display_container_state;
+    let by_path = |path: Vec<usize>| state.get_task_id_by_path(&TaskIndex { path }.to_string(), &app.tasks);
+    let in_view = |task: &&Task| state.in_view(task, &app.tasks);
+
+    match target {
+        Target::Index(index) => find_task(app, &index.to_string())
+            .map(|i| app.tasks[i].id)
+            .into_iter()
+            .collect(),
+        Target::Range(start, end) => {
+            let (parent, first) = start.path.split_at(start.path.len() - 1);
+            let last = end.path[end.path.len() - 1];
+            (first[0]..=last)
+                .filter_map(|position| by_path([parent, &[position]].concat()))
+                .collect()
+        }
+        Target::Subtree(index) => by_path(index.path.clone())
+            .map(|task_id| descendants(&app.tasks, task_id))
+            .unwrap_or_default(),
+        Target::Search(text) => {
+            let text = text.to_lowercase();
+            app.tasks
+                .iter()
+                .filter(in_view)
+                .filter(|t| {
+                    t.content.to_lowercase().contains(&text)
+                        || t.notes.as_ref().is_some_and(|notes| notes.to_lowercase().contains(&text))
+                })
+                .map(|t| t.id)
+                .collect()
+        }
+        Target::Tag(tag) => app.tasks.iter().filter(in_view).filter(|t| t.has_tag(tag)).map(|t| t.id).collect(),
+        Target::Selection => state.selected_task_ids(),
+        Target::Content(content) => find_task(app, content)
+            .map(|i| app.tasks[i].id)
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// IDs of every task below a task, depth first
+fn descendants(tasks: &[Task], task_id: u32) -> Vec<u32> {
+    let mut ids = Vec::new();
+    if let Some(index) = find_task_by_id(tasks, task_id) {
+        for &child_id in &tasks[index].child_ids {
+            ids.push(child_id);
+            ids.extend(descendants(tasks, child_id));
+        }
+    }
+    ids
+}
+
+/// Runs `action` on each task named by `targets`, then saves once.
+///
+/// All targets are resolved before the first action runs, so indices refer
+/// to the tasks as displayed when the command was entered. `action` returns
+/// the message for the activity log, `Ok` if it changed the task. A command
+/// on a single task logs that message; one on several logs a single summary
+/// like "Completed 2 of 3 tasks".
+fn for_each_target(
+    app: &mut App,
+    targets: &[Target],
+    verb: &str,
+    action: impl FnMut(&mut App, usize) -> Result<String, String>,
+) {
+    let (task_ids, unmatched) = resolve_targets(app, targets);
+    for_each_task(app, targets, &task_ids, &unmatched, verb, action);
+}
+
+/// Like `for_each_target`, for commands that move tasks within the hierarchy.
+///
+/// Subtasks of named tasks move along with them, so they are left out. With
+/// `reverse`, the tasks are handled from the last one up, which keeps their
+/// order when each is placed right after the same task.
+fn for_each_branch(
+    app: &mut App,
+    targets: &[Target],
+    verb: &str,
+    reverse: bool,
+    action: impl FnMut(&mut App, usize) -> Result<String, String>,
+) {
+    let (task_ids, unmatched) = resolve_targets(app, targets);
+    let mut roots: Vec<u32> = task_ids
+        .iter()
+        .copied()
+        .filter(|&id| {
+            !task_ids
+                .iter()
+                .any(|&other| other != id && operations::is_ancestor(&app.tasks, other, id))
+        })
+        .collect();
+    if reverse {
+        roots.reverse();
+    }
+    for_each_task(app, targets, &roots, &unmatched, verb, action);
+}
+
+/// IDs of the tasks named by `targets` without duplicates, and the targets
+/// that matched nothing
+fn resolve_targets(app: &App, targets: &[Target]) -> (Vec<u32>, Vec<String>) {
+    let mut task_ids: Vec<u32> = Vec::new();
+    let mut unmatched = Vec::new();
+    for target in targets {
+        let resolved = resolve_target(app, target);
+        if resolved.is_empty() {
+            unmatched.push(target.to_string());
+        }
+        for task_id in resolved {
+            if !task_ids.contains(&task_id) {
+                task_ids.push(task_id);
+            }
+        }
+    }
+    (task_ids, unmatched)
+}
+
+/// Runs `action` on resolved tasks, saves once and logs the outcome
+fn for_each_task(
+    app: &mut App,
+    targets: &[Target],
+    task_ids: &[u32],
+    unmatched: &[String],
+    verb: &str,
+    mut action: impl FnMut(&mut App, usize) -> Result<String, String>,
+) {
+    let mut changed = 0;
+    let mut last_message = None;
+    for task_id in task_ids {
+        // Earlier actions may have removed the task
+        if let Some(index) = find_task_by_id(&app.tasks, *task_id) {
+            let result = action(app, index);
+            changed += usize::from(result.is_ok());
+            last_message = Some(result.unwrap_or_else(|message| message));
+        }
+    }
+
+    if changed > 0 {
+        if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
+            warn!("Failed to save tasks: {e}");
+        }
+    }
+
+    let single = targets.len() == 1 && !targets[0].is_set();
+    let message = match last_message {
+        Some(message) if single => message,
+        None if single => "No matching task found".to_string(),
+        _ => {
+            let total = task_ids.len();
+            let noun = if total == 1 { "task" } else { "tasks" };
+            let mut summary = if changed == total {
+                format!("{verb} {total} {noun}")
+            } else {
+                format!("{verb} {changed} of {total} {noun}")
+            };
+            if !unmatched.is_empty() {
+                summary.push_str(&format!(", no match for: {}", unmatched.join(", ")));
             }
-            let content = task.content.clone();
-            task.complete();
-            app.display_container_state.update_display_order(&app.tasks);
-            return CommandResult::TaskCompleted { content };
+            summary
         }
+    };
+    app.log_activity(message);
+}
+
+/// Completes a task by ID
+///
+/// With `auto_complete_parents`, parents whose subtasks are now all done are
+/// completed as well.
+fn complete_task(app: &mut App, task_id: u32) -> CommandResult {
+    let Some(index) = find_task_by_id(&app.tasks, task_id) else {
         return CommandResult::NoMatchingTask;
+    };
+    let task = &app.tasks[index];
+    if matches!(task.container(), TaskContainer::Archived) {
+        return CommandResult::TaskAlreadyArchived(task.content.clone());
+    }
+    let content = task.content.clone();
+    let mut parent_id = task.parent_id;
+    let next_due = complete_occurrence(app, index);
+
+    let mut parents = Vec::new();
+    while let Some(id) = parent_id.filter(|_| app.auto_complete_parents) {
+        let Some(index) = find_task_by_id(&app.tasks, id) else {
+            break;
+        };
+        let parent = &app.tasks[index];
+        let all_done = parent.child_ids.iter().all(|child_id| {
+            app.tasks.iter().find(|t| t.id == *child_id).is_none_or(|t| t.status == TaskStatus::Done)
+        });
+        if parent.status == TaskStatus::Done || !all_done {
+            break;
+        }
+        parents.push(parent.content.clone());
+        parent_id = parent.parent_id;
+        complete_occurrence(app, index);
     }
 
-    // Otherwise, search for task by content
-    if let Some(idx) = find_task(app, query) {
-        let task = &mut app.tasks[idx];
-        if matches!(task.container(), TaskContainer::Archived) {
-            return CommandResult::TaskAlreadyArchived(task.content.clone());
+    app.display_container_state.update_display_order(&app.tasks);
+    CommandResult::TaskCompleted { content, parents, next_due }
+}
+
+/// Completes the task at `index`; a recurring task comes back right where it
+/// was. Returns the due date of the next occurrence, if there is one.
+///
+/// A completed occurrence of a recurring subtask leaves its parent for the
+/// archive, so that its parent only lists the open occurrence.
+fn complete_occurrence(app: &mut App, index: usize) -> Option<NaiveDate> {
+    let next = app.tasks[index].next_occurrence(app.next_id, today());
+    let task = &mut app.tasks[index];
+    task.complete();
+    let task_id = task.id;
+    let next = next?;
+    task.parent_id = None;
+    app.next_id += 1;
+    if let Some(parent) = next.parent_id.and_then(|id| app.tasks.iter_mut().find(|t| t.id == id)) {
+        match parent.child_ids.iter().position(|&id| id == task_id) {
+            Some(position) => parent.child_ids[position] = next.id,
+            None => parent.child_ids.push(next.id),
         }
-        let content = task.content.clone();
-        task.complete();
-        app.display_container_state.update_display_order(&app.tasks);
-        CommandResult::TaskCompleted { content }
-    } else {
-        CommandResult::NoMatchingTask
     }
+    let next_due = next.due;
+    operations::add_task(&mut app.tasks, next);
+    next_due
 }
 
 /// Execute a create command
+///
+/// While zoomed, the new task becomes the last subtask of the zoomed task.
 pub fn execute_create_command(app: &mut App, content: &str) -> u32 {
-    let task = Task::new(app.next_id, content.to_string());
+    if let Some(task_id) = app.display_container_state.zoom
+        .and_then(|root_id| execute_add_subtask_by_id(app, root_id, content))
+    {
+        return task_id;
+    }
+    let mut task = Task::new(app.next_id, content.to_string());
+    take_inline_recurrence(&mut task);
     let task_id = task.id;
     app.next_id += 1;
     app.add_task(task);
     app.log_activity("Task added".to_string());
     if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
-        log_debug(&format!("Failed to save tasks: {e}"));
+        warn!("Failed to save tasks: {e}");
     }
     task_id
 }
 
 /// Execute a complete command
-fn execute_complete_command(app: &mut App, query: &str) {
-    match complete_task(app, query, None) {
-        CommandResult::TaskCompleted { content } => {
-            app.log_activity(format!("Completed task: {content}"));
-            if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
-                app.log_activity(format!("Error saving tasks: {e}"));
+fn execute_complete_command(app: &mut App, targets: &[Target]) {
+    for_each_target(app, targets, "Completed", |app, index| {
+        let task_id = app.tasks[index].id;
+        completion_message(complete_task(app, task_id))
+    });
+}
+
+/// Activity message for the result of completing a task, `Ok` if it was completed
+fn completion_message(result: CommandResult) -> Result<String, String> {
+    match result {
+        CommandResult::TaskCompleted { content, parents, next_due } => {
+            let mut message = format!("Completed task: {content}");
+            if let Some(next_due) = next_due {
+                message.push_str(&format!(", next due {next_due}"));
             }
+            if !parents.is_empty() {
+                let noun = if parents.len() == 1 { "parent" } else { "parents" };
+                message.push_str(&format!(", and its {noun}: {}", parents.join(", ")));
+            }
+            Ok(message)
         }
-        CommandResult::TaskAlreadyArchived(content) => {
-            app.log_activity(format!("Task '{content}' is already archived"));
-        }
-        CommandResult::NoMatchingTask => {
-            app.log_activity("No matching task found".to_string());
-        }
+        CommandResult::TaskAlreadyArchived(content) => Err(format!("Task '{content}' is already archived")),
+        CommandResult::NoMatchingTask => Err("No matching task found".to_string()),
     }
 }
 
 /// Execute a complete by ID command
 pub fn execute_complete_by_id_command(app: &mut App, task_id: u32) {
-    match complete_task(app, "", Some(task_id)) {
-        CommandResult::TaskCompleted { content } => {
-            app.log_activity(format!("Completed task: {content}"));
+    match completion_message(complete_task(app, task_id)) {
+        Ok(message) => {
+            app.log_activity(message);
             if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
                 app.log_activity(format!("Error saving tasks: {e}"));
             }
         }
-        CommandResult::TaskAlreadyArchived(content) => {
-            app.log_activity(format!("Task '{content}' is already archived"));
-        }
-        CommandResult::NoMatchingTask => {
-            app.log_activity("No matching task found".to_string());
-        }
+        Err(message) => app.log_activity(message),
     }
 }
 
 /// Execute a delete command
-fn execute_delete_command(app: &mut App, query: &str) {
-    if let Some(index) = find_task(app, query) {
-        let task = &app.tasks[index];
-        let content = task.content.clone();
-        let task_id = task.id;
+fn execute_delete_command(app: &mut App, targets: &[Target]) {
+    for_each_target(app, targets, "Deleted", delete_task);
+}
 
-        // If this task has a parent, remove it from the parent's child_ids
-        if let Some(parent_id) = task.parent_id {
-            if let Some(parent_index) = app.tasks.iter().position(|t| t.id == parent_id) {
-                app.remove_child_from_parent(parent_index, task_id);
-            }
+/// Deletes the task at `index`
+fn delete_task(app: &mut App, index: usize) -> Result<String, String> {
+    let task = &app.tasks[index];
+    let content = task.content.clone();
+    let task_id = task.id;
+
+    // If this task has a parent, remove it from the parent's child_ids
+    if let Some(parent_id) = task.parent_id {
+        if let Some(parent_index) = app.tasks.iter().position(|t| t.id == parent_id) {
+            app.remove_child_from_parent(parent_index, task_id);
         }
+    }
 
+    // Removing the child from its parent may have shifted the task
+    if let Some(index) = find_task_by_id(&app.tasks, task_id) {
         app.remove_task(index);
-        app.log_activity(format!("Deleted task: {content}"));
-        if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
-            log_debug(&format!("Failed to save tasks: {e}"));
-        }
-    } else {
-        app.log_activity("No matching task found".to_string());
     }
+    Ok(format!("Deleted task: {content}"))
 }
 
 /// Execute a move command
-fn execute_move_command(app: &mut App, query: &str, target_container: TaskContainer) {
-    if let Some(index) = find_task(app, query) {
+fn execute_move_command(app: &mut App, targets: &[Target], target_container: TaskContainer) {
+    let container_name = target_container.display_name();
+    let verb = format!("Moved to {container_name}");
+    for_each_target(app, targets, &verb, |app, index| {
         let task = &mut app.tasks[index];
         if task.container == target_container {
-            app.log_activity(format!(
-                "Task already in {}",
-                target_container.display_name()
-            ));
+            Err(format!("Task already in {container_name}"))
         } else {
-            let content = task.content.clone();
-            let container_name = target_container.display_name();
             task.container = target_container;
-
-            // Save tasks after moving one
-            if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
-                log_debug(&format!("Failed to save tasks: {e}"));
-            }
-
-            app.log_activity(format!("Moved task to {container_name}: {content}"));
+            Ok(format!("Moved task to {container_name}: {}", task.content))
         }
-    } else {
-        app.log_activity("No matching task found".to_string());
-    }
+    });
 }
 
 /// Execute a move to taskpad command
-fn execute_move_to_taskpad_command(app: &mut App, query: &str) {
-    execute_move_command(app, query, TaskContainer::Taskpad);
+fn execute_move_to_taskpad_command(app: &mut App, targets: &[Target]) {
+    execute_move_command(app, targets, TaskContainer::Taskpad);
 }
 
 /// Execute a move to backburner command
-fn execute_move_to_backburner_command(app: &mut App, query: &str) {
-    execute_move_command(app, query, TaskContainer::Backburner);
+fn execute_move_to_backburner_command(app: &mut App, targets: &[Target]) {
+    execute_move_command(app, targets, TaskContainer::Backburner);
 }
 
 /// Execute a move to shelved command
-fn execute_move_to_shelved_command(app: &mut App, query: &str) {
-    execute_move_command(app, query, TaskContainer::Shelved);
+fn execute_move_to_shelved_command(app: &mut App, targets: &[Target]) {
+    execute_move_command(app, targets, TaskContainer::Shelved);
+}
+
+/// The sibling displayed right before (or with `forward`, right after) a task
+fn adjacent_sibling(app: &App, task_id: u32, forward: bool) -> Option<u32> {
+    let task = &app.tasks[find_task_by_id(&app.tasks, task_id)?];
+    let siblings = match task.parent_id {
+        Some(_) => operations::sibling_ids(&app.tasks, task_id),
+        None => app.display_container_state.top_level_tasks(&app.tasks).iter().map(|t| t.id).collect(),
+    };
+    let position = siblings.iter().position(|&id| id == task_id)?;
+    let adjacent = if forward { position + 1 } else { position.checked_sub(1)? };
+    siblings.get(adjacent).copied()
+}
+
+/// Execute a move under command, making tasks the last subtasks of another task
+fn execute_move_under_command(app: &mut App, targets: &[Target], parent_query: &str) {
+    let Some(parent_index) = find_task(app, parent_query) else {
+        app.log_activity(format!("No matching task found for: {parent_query}"));
+        return;
+    };
+    let parent_id = app.tasks[parent_index].id;
+    let parent_content = app.tasks[parent_index].content.clone();
+    let verb = format!("Moved under '{parent_content}'");
+    for_each_branch(app, targets, &verb, false, |app, index| {
+        let task = &app.tasks[index];
+        let (task_id, content) = (task.id, task.content.clone());
+        if task.parent_id == Some(parent_id) {
+            return Err(format!("Task already under '{parent_content}': {content}"));
+        }
+        operations::move_task(&mut app.tasks, task_id, Placement::LastChildOf(parent_id))?;
+        app.display_container_state.folded_tasks.remove(&parent_id);
+        Ok(format!("Moved task under '{parent_content}': {content}"))
+    });
+}
+
+/// Execute an indent command, making tasks the last subtasks of the sibling above them
+fn execute_indent_command(app: &mut App, targets: &[Target]) {
+    for_each_branch(app, targets, "Indented", false, |app, index| {
+        let (task_id, content) = (app.tasks[index].id, app.tasks[index].content.clone());
+        let Some(parent_id) = adjacent_sibling(app, task_id, false) else {
+            return Err(format!("No task above to indent under: {content}"));
+        };
+        operations::move_task(&mut app.tasks, task_id, Placement::LastChildOf(parent_id))?;
+        app.display_container_state.folded_tasks.remove(&parent_id);
+        Ok(format!("Indented task: {content}"))
+    });
+}
+
+/// Execute an outdent command, moving subtasks right after their parent
+fn execute_outdent_command(app: &mut App, targets: &[Target]) {
+    for_each_branch(app, targets, "Outdented", true, |app, index| {
+        let task = &app.tasks[index];
+        let (task_id, content) = (task.id, task.content.clone());
+        let Some(parent_id) = task.parent_id.filter(|&id| app.display_container_state.zoom != Some(id)) else {
+            return Err(format!("Task is already at the top level: {content}"));
+        };
+        operations::move_task(&mut app.tasks, task_id, Placement::After(parent_id))?;
+        Ok(format!("Outdented task: {content}"))
+    });
+}
+
+/// Execute a move up or down command, swapping tasks with their siblings
+///
+/// A task that can't move, because it is first or last, also holds back
+/// the tasks moved after it, so a block of tasks keeps its shape.
+fn execute_reorder_command(app: &mut App, targets: &[Target], down: bool) {
+    let (verb, end) = if down { ("Moved down", "last") } else { ("Moved up", "first") };
+    let mode = app.display_container_state.sort_mode();
+    let mut stuck = Vec::new();
+    for_each_branch(app, targets, verb, down, |app, index| {
+        let (task_id, content) = (app.tasks[index].id, app.tasks[index].content.clone());
+        if mode != SortMode::Manual && app.tasks[index].parent_id.is_none() {
+            return Err(format!("Tasks are sorted by {mode}, use 'sort manual' to arrange them: {content}"));
+        }
+        match adjacent_sibling(app, task_id, down) {
+            Some(sibling_id) if !stuck.contains(&sibling_id) => {
+                let placement = if down { Placement::After(sibling_id) } else { Placement::Before(sibling_id) };
+                operations::move_task(&mut app.tasks, task_id, placement)?;
+                Ok(format!("{verb} task: {content}"))
+            }
+            _ => {
+                stuck.push(task_id);
+                Err(format!("Task is already {end}: {content}"))
+            }
+        }
+    });
+}
+
+/// Execute a place command, putting tasks where they were dropped in the task list
+fn execute_place_command(app: &mut App, targets: &[Target], placement: Placement) {
+    let (Placement::LastChildOf(anchor_id) | Placement::Before(anchor_id) | Placement::After(anchor_id)) = placement;
+    let mode = app.display_container_state.sort_mode();
+    let to_top_level = match placement {
+        Placement::LastChildOf(_) => false,
+        Placement::Before(_) | Placement::After(_) => find_task_by_id(&app.tasks, anchor_id)
+            .is_some_and(|index| app.tasks[index].parent_id.is_none()),
+    };
+    let reverse = matches!(placement, Placement::After(_));
+    for_each_branch(app, targets, "Moved", reverse, |app, index| {
+        let task = &app.tasks[index];
+        let (task_id, content) = (task.id, task.content.clone());
+        if task_id == anchor_id {
+            return Err(format!("Task is already there: {content}"));
+        }
+        if mode != SortMode::Manual && to_top_level && task.parent_id.is_none() {
+            return Err(format!("Tasks are sorted by {mode}, use 'sort manual' to arrange them: {content}"));
+        }
+        operations::move_task(&mut app.tasks, task_id, placement)?;
+        if let Placement::LastChildOf(parent_id) = placement {
+            app.display_container_state.folded_tasks.remove(&parent_id);
+        }
+        Ok(format!("Moved task: {content}"))
+    });
+}
+
+/// Set or clear the due date of tasks
+fn execute_due_command(app: &mut App, due: Option<NaiveDate>, targets: &[Target]) {
+    let verb = due.map_or_else(|| "Cleared due date of".to_string(), |due| format!("Set due {due} on"));
+    for_each_target(app, targets, &verb, |app, index| {
+        let task = &mut app.tasks[index];
+        if task.due == due {
+            return Err(match due {
+                Some(due) => format!("Task already due {due}: {}", task.content),
+                None => format!("Task has no due date: {}", task.content),
+            });
+        }
+        task.due = due;
+        Ok(match due {
+            Some(due) => format!("Task due {due}: {}", task.content),
+            None => format!("Cleared due date: {}", task.content),
+        })
+    });
+}
+
+/// Execute a repeat command, which sets or clears the recurrence of tasks
+fn execute_repeat_command(app: &mut App, recurrence: Option<&Recurrence>, targets: &[Target]) {
+    let verb = recurrence.map_or_else(|| "Cleared recurrence of".to_string(), |recurrence| format!("Set recurrence ({recurrence}) on"));
+    for_each_target(app, targets, &verb, |app, index| {
+        let task = &mut app.tasks[index];
+        if task.recurrence.as_ref() == recurrence {
+            return Err(match recurrence {
+                Some(recurrence) => format!("Task already repeats {recurrence}: {}", task.content),
+                None => format!("Task doesn't repeat: {}", task.content),
+            });
+        }
+        match recurrence {
+            Some(recurrence) => set_recurrence(task, recurrence.clone()),
+            None => task.recurrence = None,
+        }
+        Ok(match recurrence {
+            Some(recurrence) => format!("Task repeats {recurrence}: {}", task.content),
+            None => format!("Task no longer repeats: {}", task.content),
+        })
+    });
+}
+
+/// Makes a task recur, giving it the first due date of the rule if it has none
+fn set_recurrence(task: &mut Task, recurrence: Recurrence) {
+    task.due = task.due.or_else(|| recurrence.first_due(today()));
+    task.recurrence = Some(recurrence);
+}
+
+/// Moves an inline rule like `every:mon` from a task's content to its recurrence
+fn take_inline_recurrence(task: &mut Task) {
+    if let (content, Some(recurrence)) = Recurrence::extract(&task.content) {
+        task.content = content;
+        set_recurrence(task, recurrence);
+    }
+}
+
+/// The local date, which recurrence rules follow
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Execute a sort command, which sorts the active container and remembers it
+fn execute_sort_command(app: &mut App, mode: SortMode) {
+    let container = app.display_container_state.active_container;
+    app.display_container_state.sort_modes.set(container, mode);
+    let container_name = container.display_name();
+    if let Err(e) = save_sort_modes(&app.display_container_state.sort_modes, &app.sorting_file()) {
+        warn!("Failed to save sort modes: {e}");
+    }
+    app.log_activity(format!("Sorting {container_name} by {mode}"));
 }
 
 /// Execute show container command
 fn execute_show_command(app: &mut App, container: TaskContainer) {
     let display_name = container.display_name().to_string();
     app.display_container_state.active_container = container;
+    app.display_container_state.zoom = None;
     app.display_container_state.update_display_order(&app.tasks);
     app.activity_log
         .add_message(format!("Showing {display_name} tasks"));
@@ -313,18 +726,128 @@ fn execute_focus_command(app: &mut App, query: &str) {
     }
 }
 
+/// Focuses a task by ID from anywhere in the store.
+///
+/// Switches to the container of the task's top-level ancestor and unfolds
+/// every ancestor so the task is visible. Returns true if the task was focused.
+pub fn execute_focus_by_id_command(app: &mut App, task_id: u32) -> bool {
+    let Some(index) = find_task_by_id(&app.tasks, task_id) else {
+        app.log_activity("No matching task found".to_string());
+        return false;
+    };
+
+    // Tasks outside the zoomed task end the zoom
+    if !app.display_container_state.in_view(&app.tasks[index], &app.tasks) {
+        app.display_container_state.zoom = None;
+    }
+
+    // Walk up to the top-level ancestor, unfolding each parent on the way
+    let mut root_index = index;
+    while let Some(parent_id) = app.tasks[root_index].parent_id {
+        let Some(parent_index) = find_task_by_id(&app.tasks, parent_id) else {
+            break;
+        };
+        app.display_container_state.folded_tasks.remove(&parent_id);
+        root_index = parent_index;
+    }
+
+    app.display_container_state.active_container = app.tasks[root_index].container;
+    app.display_container_state.update_display_order(&app.tasks);
+
+    let content = app.tasks[index].content.clone();
+    if app.display_container_state.focus_task_and_update_input(Some(task_id), &app.tasks) {
+        app.log_activity(format!("Focused on task: {content}"));
+        true
+    } else {
+        app.log_activity("No matching task found".to_string());
+        false
+    }
+}
+
+/// Executes the zoom command
+fn execute_zoom_command(app: &mut App, query: &str) {
+    match find_task(app, query) {
+        Some(index) => {
+            let task_id = app.tasks[index].id;
+            execute_zoom_by_id_command(app, Some(task_id));
+        }
+        None => app.log_activity("No matching task found".to_string()),
+    }
+}
+
+/// Zooms out to the parent of the zoomed task, or to the whole container
+pub fn execute_zoom_out_command(app: &mut App) {
+    let Some(root_id) = app.display_container_state.zoom else {
+        app.log_activity("Not zoomed in".to_string());
+        return;
+    };
+    let parent_id = find_task_by_id(&app.tasks, root_id).and_then(|index| app.tasks[index].parent_id);
+    execute_zoom_by_id_command(app, parent_id);
+}
+
+/// Zooms into a task by ID, or out to the whole container with `None`.
+///
+/// Switches to the container of the task's top-level ancestor. Focuses the
+/// task that was zoomed into before if it is shown, and the first task
+/// otherwise.
+pub fn execute_zoom_by_id_command(app: &mut App, task_id: Option<u32>) {
+    let previous = app.display_container_state.zoom;
+    match task_id.and_then(|id| find_task_by_id(&app.tasks, id)) {
+        Some(index) => {
+            let content = app.tasks[index].content.clone();
+            let ancestry = operations::ancestry(&app.tasks, app.tasks[index].id);
+            if let Some(root) = ancestry.last() {
+                app.display_container_state.active_container = root.container;
+            }
+            app.display_container_state.zoom = task_id;
+            app.log_activity(format!("Zoomed into: {content}"));
+        }
+        None if task_id.is_some() => {
+            app.log_activity("No matching task found".to_string());
+            return;
+        }
+        None => {
+            app.display_container_state.zoom = None;
+            let container = app.display_container_state.active_container.display_name().to_string();
+            app.log_activity(format!("Showing all {container} tasks"));
+        }
+    }
+
+    let state = &mut app.display_container_state;
+    state.update_display_order(&app.tasks);
+    let focus = previous
+        .filter(|&id| state.get_display_index(id).is_some())
+        .or_else(|| state.display_to_id.first().copied());
+    state.focus_task_and_update_input(focus, &app.tasks);
+}
+
 /// Execute edit command
+///
+/// Links to the task by its old title are renamed along with it.
 fn execute_edit_command(app: &mut App, task_id: u32, content: String) {
-    if let Some(_task) = app.tasks.iter_mut().find(|t| t.id == task_id) {
+    let (content, recurrence) = Recurrence::extract(&content);
+    if let Some(task) = app.tasks.iter_mut().find(|t| t.id == task_id) {
+        let old_content = task.content.clone();
+        let linked_by_title = old_content.trim().to_lowercase() != content.trim().to_lowercase()
+            && LinkTarget::Title(old_content.trim().to_string()).resolve(&app.tasks) == Some(task_id);
         app.update_task(
             app.tasks.iter().position(|t| t.id == task_id).unwrap(),
             |task| {
-                task.update_content(content);
+                task.update_content(content.clone());
+                if let Some(recurrence) = recurrence {
+                    set_recurrence(task, recurrence);
+                }
             },
         );
-        app.activity_log.add_message("Task updated".to_string());
+        let renamed = if linked_by_title { links::rename_links(&mut app.tasks, &old_content, &content) } else { 0 };
+        if renamed > 0 {
+            let noun = if renamed == 1 { "task" } else { "tasks" };
+            app.log_activity(format!("Task updated, renamed links in {renamed} {noun}"));
+        } else {
+            app.activity_log.add_message("Task updated".to_string());
+        }
         if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
-            log_debug(&format!("Failed to save tasks: {e}"));
+            warn!("Failed to save tasks: {e}");
         }
     } else {
         app.activity_log
@@ -332,28 +855,48 @@ fn execute_edit_command(app: &mut App, task_id: u32, content: String) {
     }
 }
 
+/// Replace the notes of a task; blank notes are removed
+fn execute_edit_notes_command(app: &mut App, task_id: u32, notes: &str) {
+    let Some(index) = find_task_by_id(&app.tasks, task_id) else {
+        app.log_activity(format!("No task found with ID {task_id}"));
+        return;
+    };
+    let notes = notes.trim_end();
+    let task = &mut app.tasks[index];
+    let message = if notes.trim().is_empty() {
+        task.notes = None;
+        format!("Removed notes: {}", task.content)
+    } else {
+        task.notes = Some(notes.to_string());
+        format!("Saved notes: {}", task.content)
+    };
+    if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
+        warn!("Failed to save tasks: {e}");
+    }
+    app.log_activity(message);
+}
+
 /// Execute add subtask command
 ///
-/// Can be called with either:
-/// - A query string to find the parent task
-/// - A direct parent task ID
-pub fn execute_add_subtask(app: &mut App, query_or_id: &str, content: &str) -> Option<u32> {
-    // Check if query_or_id is a task ID (u32)
-    let parent_idx = if let Ok(parent_id) = query_or_id.parse::<u32>() {
-        // If it's a valid u32, find the task by ID
-        find_task_by_id(&app.tasks, parent_id)
-    } else {
-        // Otherwise use the regular find_task function
-        find_task(app, query_or_id)
+/// The parent is named like in other commands, by its display index (`3`,
+/// `1.2`) or its content.
+pub fn execute_add_subtask(app: &mut App, query: &str, content: &str) -> Option<u32> {
+    let Some(parent_idx) = find_task(app, query) else {
+        app.log_activity(format!("No task found matching '{query}'"));
+        return None;
     };
+    let parent_id = app.tasks[parent_idx].id;
+    execute_add_subtask_by_id(app, parent_id, content)
+}
 
+/// Adds a subtask to the task with the given ID
+pub fn execute_add_subtask_by_id(app: &mut App, parent_id: u32, content: &str) -> Option<u32> {
     // Create the subtask if parent was found
-    if let Some(parent_idx) = parent_idx {
-        let parent_id = app.tasks[parent_idx].id;
+    if let Some(parent_idx) = find_task_by_id(&app.tasks, parent_id) {
         let parent_content = app.tasks[parent_idx].content.clone();
 
         // Create a new subtask
-        let subtask = Task {
+        let mut subtask = Task {
             id: app.next_id,
             content: content.to_string(),
             created_at: Utc::now(),
@@ -361,7 +904,15 @@ pub fn execute_add_subtask(app: &mut App, query_or_id: &str, content: &str) -> O
             status: TaskStatus::Todo,
             parent_id: Some(parent_id),
             child_ids: Vec::new(),
+            notes: None,
+            source: None,
+            private: false,
+            tags: Vec::new(),
+            order: String::new(),
+            due: None,
+            recurrence: None,
         };
+        take_inline_recurrence(&mut subtask);
         let subtask_id = subtask.id;
         app.next_id += 1;
 
@@ -377,27 +928,443 @@ pub fn execute_add_subtask(app: &mut App, query_or_id: &str, content: &str) -> O
         };
         app.log_activity(msg);
 
-        // Save updated task list
-        if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
-            log_debug(&format!("Failed to save tasks: {e}"));
+        // Save updated task list
+        if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
+            warn!("Failed to save tasks: {e}");
+        }
+
+        Some(subtask_id)
+    } else {
+        app.log_activity(format!("No task found with ID {parent_id}"));
+        None
+    }
+}
+
+/// Where a captured feed item should end up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureTarget {
+    /// A new top-level task
+    Task,
+    /// A new subtask of the task with the given ID
+    Subtask(u32),
+    /// A note appended to the task with the given ID
+    Note(u32),
+}
+
+/// Captures a Genius feed item into the task store.
+///
+/// New tasks take the first line of the item as their content and keep the
+/// rest as notes. The item ID, provider and query are recorded on the task so
+/// it links back to the card. Returns the ID of the task that was created or
+/// changed.
+pub fn execute_capture_item_command(
+    app: &mut App,
+    item: &GeniusItem,
+    provider: &str,
+    query: &str,
+    target: CaptureTarget,
+) -> Option<u32> {
+    let description = item.description.trim();
+    let mut lines = description.lines();
+    let title = lines.next().unwrap_or_default().trim().to_string();
+    let body = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+
+    let source = TaskSource {
+        provider: provider.to_string(),
+        item_id: item.id.clone(),
+        query: query.to_string(),
+        captured_at: Utc::now(),
+        metadata: item.metadata.clone(),
+    };
+
+    let task_id = match target {
+        CaptureTarget::Task | CaptureTarget::Subtask(_) => {
+            let task_id = if let CaptureTarget::Subtask(parent_id) = target {
+                execute_add_subtask_by_id(app, parent_id, &title)?
+            } else {
+                execute_create_command(app, &title)
+            };
+            let index = find_task_by_id(&app.tasks, task_id)?;
+            app.update_task(index, |task| {
+                if !body.is_empty() {
+                    task.notes = Some(body);
+                }
+                task.source = Some(source);
+            });
+            app.log_activity(format!("Captured suggestion as task: {title}"));
+            task_id
+        }
+        CaptureTarget::Note(task_id) => {
+            let Some(index) = find_task_by_id(&app.tasks, task_id) else {
+                app.log_activity("No matching task found".to_string());
+                return None;
+            };
+            let note = format!("{description}\n(from {provider} item {})", item.id);
+            app.update_task(index, |task| task.append_note(&note));
+            app.log_activity(format!(
+                "Added suggestion as note to: {}",
+                app.tasks[index].content
+            ));
+            task_id
+        }
+    };
+
+    if let Err(e) = save_tasks(&app.tasks, &app.tasks_file) {
+        warn!("Failed to save tasks: {e}");
+    }
+
+    Some(task_id)
+}
+
+/// Pin a Genius item to the workspace pinboard, or unpin it if already pinned.
+///
+/// Returns true if the item is pinned afterwards.
+pub fn execute_toggle_pin_command(app: &mut App, item: &GeniusItem, query: &str) -> bool {
+    let pinned = app.pinboard.toggle(item, query);
+    let title = item.description.lines().next().unwrap_or_default().trim();
+    if pinned {
+        app.log_activity(format!("Pinned item: {title}"));
+    } else {
+        app.log_activity(format!("Unpinned item: {title}"));
+    }
+
+    if let Err(e) = save_pins(&app.pinboard, &app.pins_file()) {
+        warn!("Failed to save pins: {e}");
+    }
+
+    pinned
+}
+
+/// Execute toggle expansion command
+fn execute_toggle_command(app: &mut App, targets: &[Target]) {
+    for_each_target(app, targets, "Toggled", |app, index| {
+        let task_id = app.tasks[index].id;
+        app.display_container_state
+            .toggle_task_expansion(task_id, &app.tasks);
+        Ok(format!("Toggled task: {}", app.tasks[index].content))
+    });
+}
+
+/// Expand or collapse tasks with all their subtasks
+fn execute_fold_command(app: &mut App, targets: &[Target], expanded: bool) {
+    let verb = if expanded { "Expanded" } else { "Collapsed" };
+    for_each_target(app, targets, verb, |app, index| {
+        let task_id = app.tasks[index].id;
+        app.display_container_state
+            .set_subtree_expanded(task_id, expanded, &app.tasks);
+        Ok(format!("{verb} task: {}", app.tasks[index].content))
+    });
+}
+
+/// Expand or collapse every task of the view
+fn execute_fold_all_command(app: &mut App, expanded: bool) {
+    if expanded {
+        app.display_container_state.expand_all(&app.tasks);
+        app.log_activity("Expanded all tasks".to_string());
+    } else {
+        app.display_container_state.collapse_all(&app.tasks);
+        app.log_activity("Collapsed all tasks".to_string());
+    }
+}
+
+/// Show a number of levels of tasks in the view
+fn execute_expand_to_depth_command(app: &mut App, depth: usize) {
+    app.display_container_state.expand_to_depth(depth, &app.tasks);
+    let noun = if depth == 1 { "level" } else { "levels" };
+    app.log_activity(format!("Showing {depth} {noun} of tasks"));
+}
+
+/// Mark tasks as private, or public again if they already are
+///
+/// Private tasks and their subtasks are never sent to external providers.
+fn execute_toggle_private_command(app: &mut App, targets: &[Target]) {
+    for_each_target(app, targets, "Toggled privacy of", |app, index| {
+        let task = &mut app.tasks[index];
+        task.private = !task.private;
+        Ok(if task.private {
+            format!("Marked task as private: {}", task.content)
+        } else {
+            format!("Task is no longer private: {}", task.content)
+        })
+    });
+}
+
+/// Add a tag to tasks
+fn execute_tag_command(app: &mut App, tag: &str, targets: &[Target]) {
+    for_each_target(app, targets, &format!("Tagged #{tag} on"), |app, index| {
+        let task = &mut app.tasks[index];
+        if task.add_tag(tag) {
+            Ok(format!("Tagged task #{tag}: {}", task.content))
+        } else {
+            Err(format!("Task already tagged #{tag}: {}", task.content))
+        }
+    });
+}
+
+/// Remove a tag from tasks
+fn execute_untag_command(app: &mut App, tag: &str, targets: &[Target]) {
+    for_each_target(app, targets, &format!("Removed #{tag} from"), |app, index| {
+        let task = &mut app.tasks[index];
+        if task.remove_tag(tag) {
+            Ok(format!("Removed #{tag} from task: {}", task.content))
+        } else {
+            Err(format!("Task isn't tagged #{tag}: {}", task.content))
+        }
+    });
+}
+
+/// Starts the `login` flow: unlocks saved credentials, or asks for new ones
+pub fn execute_login_command(app: &mut App) {
+    let prompt = if std::path::Path::new(&app.credentials_file).exists() {
+        CredentialPrompt::Unlock
+    } else {
+        CredentialPrompt::ApiKey
+    };
+    app.log_activity(prompt.message().to_string());
+    app.credential_prompt = Some(prompt);
+}
+
+/// Answers the current step of the `login` flow with a line of input
+pub fn execute_credential_input(app: &mut App, input: &str) {
+    let Some(prompt) = app.credential_prompt.take() else {
+        return;
+    };
+    let input = Secret::new(input.trim());
+
+    let next = match prompt {
+        _ if input.is_blank() => {
+            app.log_activity("Login cancelled".to_string());
+            None
+        }
+        CredentialPrompt::ApiKey => Some(CredentialPrompt::OrganizationId { api_key: input }),
+        CredentialPrompt::OrganizationId { api_key } => Some(CredentialPrompt::NewPassphrase {
+            credentials: Credentials {
+                api_key,
+                organization_id: input.expose().to_string(),
+            },
+        }),
+        CredentialPrompt::NewPassphrase { credentials } => {
+            match save_credentials(&app.credentials_file, &credentials, &input) {
+                Ok(()) => {
+                    crate::genius_platform::get_api_bridge().configure_with_credentials(&credentials);
+                    app.log_activity("Logged in, API key saved encrypted".to_string());
+                }
+                Err(e) => app.log_activity(format!("Failed to save credentials: {e}")),
+            }
+            None
+        }
+        CredentialPrompt::Unlock => match load_credentials(&app.credentials_file, &input) {
+            Ok(credentials) => {
+                crate::genius_platform::get_api_bridge().configure_with_credentials(&credentials);
+                app.log_activity("Logged in".to_string());
+                None
+            }
+            Err(CredentialsError::WrongPassphrase) => {
+                app.log_activity("Wrong passphrase, try again (Escape to cancel)".to_string());
+                Some(CredentialPrompt::Unlock)
+            }
+            Err(e) => {
+                app.log_activity(format!("Failed to unlock credentials: {e}"));
+                None
+            }
+        },
+    };
+
+    if let Some(prompt) = &next {
+        if !matches!(prompt, CredentialPrompt::Unlock) {
+            app.log_activity(prompt.message().to_string());
+        }
+    }
+    app.credential_prompt = next;
+}
+
+/// Cancels the `login` flow
+pub fn cancel_credential_prompt(app: &mut App) {
+    if app.credential_prompt.take().is_some() {
+        app.log_activity("Login cancelled".to_string());
+    }
+}
+
+/// Forgets the Genius API credentials and deletes the encrypted file
+pub fn execute_logout_command(app: &mut App) {
+    app.credential_prompt = None;
+    crate::genius_platform::get_api_bridge().clear_credentials();
+    match delete_credentials(&app.credentials_file) {
+        Ok(true) => app.log_activity("Logged out, saved API key deleted".to_string()),
+        Ok(false) => app.log_activity("Logged out".to_string()),
+        Err(e) => app.log_activity(format!("Failed to delete credentials: {e}")),
+    }
+}
+
+/// Saves the Genius sessions of the workspace
+fn save_app_sessions(app: &App) {
+    if let Err(e) = save_sessions(&app.sessions, &app.sessions_file()) {
+        warn!("Failed to save Genius sessions: {e}");
+    }
+}
+
+/// Starts a new Genius feed session
+pub fn execute_new_session_command(app: &mut App) {
+    let session = app.sessions.start_new();
+    let (id, short_id) = (session.id.clone(), session.short_id().to_string());
+    crate::genius_platform::get_api_bridge().set_session(&id);
+    save_app_sessions(app);
+    app.log_activity(format!("Started Genius session {short_id}"));
+}
+
+/// Switches back to a Genius feed session and repeats its last query
+pub fn execute_resume_session_command(app: &mut App, query: &str) {
+    let Some(session) = app.sessions.resume(query) else {
+        app.log_activity("No matching session found".to_string());
+        return;
+    };
+    let id = session.id.clone();
+    let short_id = session.short_id().to_string();
+    let last_query = session.last_query().map(str::to_string);
+    save_app_sessions(app);
+
+    let mut api_bridge = crate::genius_platform::get_api_bridge();
+    api_bridge.set_session(&id);
+    match last_query {
+        Some(last_query) => {
+            let _ = api_bridge.query_with_input(app, &last_query);
+            app.log_activity(format!("Resumed Genius session {short_id} at '{last_query}'"));
+        }
+        None => app.log_activity(format!("Resumed Genius session {short_id}")),
+    }
+}
+
+/// Lists the Genius feed sessions with their latest query
+pub fn execute_list_sessions_command(app: &mut App) {
+    let active_id = app.sessions.active().map(|session| session.id.clone());
+    let sessions: Vec<String> = app
+        .sessions
+        .sessions()
+        .iter()
+        .enumerate()
+        .map(|(index, session)| {
+            let marker = if active_id.as_deref() == Some(session.id.as_str()) { "*" } else { "" };
+            let last_query = session
+                .last_query()
+                .map_or_else(String::new, |query| format!(", last '{query}'"));
+            format!(
+                "{}. {}{} ({}, {} queries{})",
+                index + 1,
+                session.short_id(),
+                marker,
+                session.started_at.format("%Y-%m-%d %H:%M"),
+                session.queries.len(),
+                last_query
+            )
+        })
+        .collect();
+
+    if sessions.is_empty() {
+        app.log_activity("No Genius sessions yet".to_string());
+    } else {
+        app.log_activity(format!("Genius sessions: {}", sessions.join(" | ")));
+    }
+}
+
+/// Adds a query to the history of the active Genius session
+pub fn record_session_query(app: &mut App, query: &str) {
+    app.sessions.record_query(query);
+    save_app_sessions(app);
+}
+
+/// Remembers a query of the Genius Feed once it is committed, with Enter or
+/// by leaving Feed mode.
+///
+/// Queries are sent while they are typed, so only the input that was
+/// actually sent is recorded, and only in Feed mode.
+pub fn commit_feed_query(app: &mut App, input: &str, sent_query: &str) {
+    let query = input.trim();
+    if app.app_mode != AppMode::Feed || query.is_empty() || query != sent_query.trim() {
+        return;
+    }
+    record_session_query(app, query);
+    record_input(app, AppMode::Feed, query);
+}
+
+/// Whether a valid command reads like the text of a new task.
+///
+/// Tasks usually start with a capital letter, so a capitalized keyword that
+/// names tasks only by unquoted content, like "Focus group prep", may not be
+/// meant as a command.
+fn reads_like_task(input: &str, command: &Command) -> bool {
+    let content_only = |targets: &[Target]| targets.iter().all(|target| matches!(target, Target::Content(_)));
+    let capitalized = input.trim_start().starts_with(|c: char| c.is_uppercase());
+    capitalized && !input.contains(['"', '\'']) && match command {
+        Command::Focus(query) | Command::Zoom(query) | Command::AddSubtask(query, _) => {
+            !command_parser::is_task_index(query)
         }
+        Command::Complete(targets)
+        | Command::Delete(targets)
+        | Command::MoveToTaskpad(targets)
+        | Command::MoveToBackburner(targets)
+        | Command::MoveToShelved(targets)
+        | Command::MoveUnder(targets, _)
+        | Command::Indent(targets)
+        | Command::Outdent(targets)
+        | Command::MoveUp(targets)
+        | Command::MoveDown(targets)
+        | Command::Place(targets, _)
+        | Command::Toggle(targets)
+        | Command::Expand(targets)
+        | Command::Collapse(targets)
+        | Command::TogglePrivate(targets)
+        | Command::Tag(_, targets)
+        | Command::Untag(_, targets)
+        | Command::Due(_, targets)
+        | Command::Repeat(_, targets) => content_only(targets),
+        _ => false,
+    }
+}
 
-        Some(subtask_id)
-    } else {
-        app.log_activity(format!("No task found matching '{query_or_id}'"));
-        None
+/// Parses a line entered on the input line in PKM mode.
+///
+/// Input that doesn't parse, that looks like a mistyped command, or that
+/// reads like a task (see `reads_like_task`) only becomes a new task when
+/// Enter is pressed a second time with the same input; the first time the activity log says what is wrong and `None` is
+/// returned. While tasks are selected, a command that names no task acts on
+/// the selection.
+pub fn command_for_enter(app: &mut App, input: &str) -> Option<Command> {
+    let pending = app.pending_create.take();
+    let mut command = parse_command(input.to_string());
+    if let Command::Invalid(ParseError { kind: ParseErrorKind::MissingTask { .. }, column }) = command {
+        if !app.display_container_state.selected_tasks.is_empty() {
+            // Name the selection where the tasks were expected
+            let at = input.char_indices().nth(column - 1).map_or(input.len(), |(i, _)| i);
+            command = parse_command(format!("{} @ {}", input[..at].trim_end(), &input[at..]));
+        }
+    }
+    let hint = match &command {
+        Command::Create(_) => completion::near_miss(input),
+        Command::Invalid(error) => Some(format!("Invalid command: {error}.")),
+        _ if reads_like_task(input, &command) => {
+            let keyword = input.split_whitespace().next().unwrap_or_default().to_lowercase();
+            Some(format!("Type '{keyword}' in lowercase to run it as a command."))
+        }
+        _ => return Some(command),
+    };
+    match hint {
+        Some(hint) if pending.as_deref() != Some(input) => {
+            app.log_activity(format!("{hint} Press Enter again to create it as a task."));
+            app.pending_create = Some(input.to_string());
+            None
+        }
+        _ => Some(Command::Create(input.to_string())),
     }
 }
 
-/// Execute toggle expansion command
-fn execute_toggle_command(app: &mut App, query: &str) {
-    if let Some(index) = find_task(app, query) {
-        let task_id = app.tasks[index].id;
-        app.display_container_state
-            .toggle_task_expansion(task_id, &app.tasks);
-        app.log_activity(format!("Toggled task: {}", app.tasks[index].content));
-    } else {
-        app.log_activity("No matching task found".to_string());
+/// Remembers a line entered in the input field
+///
+/// Feed queries are recorded when they are committed, see `commit_feed_query`.
+pub fn record_input(app: &mut App, mode: AppMode, entry: &str) {
+    let limit = crate::config::get_config().history.max_entries;
+    app.input_history.push(mode, entry, limit);
+    if let Err(e) = save_history(&app.input_history, &app.history_file()) {
+        warn!("Failed to save input history: {e}");
     }
 }
 
@@ -425,28 +1392,52 @@ pub fn toggle_app_mode(app: &mut App, current_mode: AppMode) -> AppMode {
 pub fn execute_command(app: &mut App, command: Option<Command>) -> Option<u32> {
     let result = match command {
         Some(Command::Create(content)) => Some(execute_create_command(app, &content)),
-        Some(Command::Complete(query)) => {
-            execute_complete_command(app, &query);
+        Some(Command::Complete(targets)) => {
+            execute_complete_command(app, &targets);
             None
         }
         Some(Command::CompleteById(id)) => {
             execute_complete_by_id_command(app, id);
             None
         }
-        Some(Command::Delete(query)) => {
-            execute_delete_command(app, &query);
+        Some(Command::Delete(targets)) => {
+            execute_delete_command(app, &targets);
             None
         }
-        Some(Command::MoveToTaskpad(query)) => {
-            execute_move_to_taskpad_command(app, &query);
+        Some(Command::MoveToTaskpad(targets)) => {
+            execute_move_to_taskpad_command(app, &targets);
             None
         }
-        Some(Command::MoveToBackburner(query)) => {
-            execute_move_to_backburner_command(app, &query);
+        Some(Command::MoveToBackburner(targets)) => {
+            execute_move_to_backburner_command(app, &targets);
             None
         }
-        Some(Command::MoveToShelved(query)) => {
-            execute_move_to_shelved_command(app, &query);
+        Some(Command::MoveToShelved(targets)) => {
+            execute_move_to_shelved_command(app, &targets);
+            None
+        }
+        Some(Command::MoveUnder(targets, parent)) => {
+            execute_move_under_command(app, &targets, &parent);
+            None
+        }
+        Some(Command::Indent(targets)) => {
+            execute_indent_command(app, &targets);
+            None
+        }
+        Some(Command::Outdent(targets)) => {
+            execute_outdent_command(app, &targets);
+            None
+        }
+        Some(Command::MoveUp(targets)) => {
+            execute_reorder_command(app, &targets, false);
+            None
+        }
+        Some(Command::MoveDown(targets)) => {
+            execute_reorder_command(app, &targets, true);
+            None
+        }
+        Some(Command::Place(targets, placement)) => {
+            execute_place_command(app, &targets, placement);
             None
         }
         Some(Command::Focus(query)) => {
@@ -457,22 +1448,102 @@ pub fn execute_command(app: &mut App, command: Option<Command>) -> Option<u32> {
             execute_show_command(app, container);
             None
         }
+        Some(Command::Zoom(query)) => {
+            execute_zoom_command(app, &query);
+            None
+        }
+        Some(Command::ZoomOut) => {
+            execute_zoom_out_command(app);
+            None
+        }
         Some(Command::Edit(task_id, content)) => {
             execute_edit_command(app, task_id, content);
             None
         }
+        Some(Command::EditNotes(task_id, notes)) => {
+            execute_edit_notes_command(app, task_id, &notes);
+            None
+        }
         Some(Command::AddSubtask(query, content)) => {
             execute_add_subtask(app, &query, &content);
             None
         }
-        Some(Command::Toggle(query)) => {
-            execute_toggle_command(app, &query);
+        Some(Command::Toggle(targets)) => {
+            execute_toggle_command(app, &targets);
+            None
+        }
+        Some(Command::Expand(targets)) => {
+            execute_fold_command(app, &targets, true);
+            None
+        }
+        Some(Command::Collapse(targets)) => {
+            execute_fold_command(app, &targets, false);
+            None
+        }
+        Some(Command::ExpandAll) => {
+            execute_fold_all_command(app, true);
+            None
+        }
+        Some(Command::CollapseAll) => {
+            execute_fold_all_command(app, false);
+            None
+        }
+        Some(Command::ExpandToDepth(depth)) => {
+            execute_expand_to_depth_command(app, depth);
+            None
+        }
+        Some(Command::TogglePrivate(targets)) => {
+            execute_toggle_private_command(app, &targets);
+            None
+        }
+        Some(Command::Tag(tag, targets)) => {
+            execute_tag_command(app, &tag, &targets);
+            None
+        }
+        Some(Command::Due(due, targets)) => {
+            execute_due_command(app, due, &targets);
+            None
+        }
+        Some(Command::Repeat(recurrence, targets)) => {
+            execute_repeat_command(app, recurrence.as_ref(), &targets);
+            None
+        }
+        Some(Command::Sort(mode)) => {
+            execute_sort_command(app, mode);
+            None
+        }
+        Some(Command::Untag(tag, targets)) => {
+            execute_untag_command(app, &tag, &targets);
             None
         }
         Some(Command::ToggleAppMode) => {
             app.app_mode = toggle_app_mode(app, app.app_mode);
             None
         }
+        Some(Command::Login) => {
+            execute_login_command(app);
+            None
+        }
+        Some(Command::Logout) => {
+            execute_logout_command(app);
+            None
+        }
+        Some(Command::NewSession) => {
+            execute_new_session_command(app);
+            None
+        }
+        Some(Command::ResumeSession(query)) => {
+            execute_resume_session_command(app, &query);
+            None
+        }
+        Some(Command::ListSessions) => {
+            execute_list_sessions_command(app);
+            None
+        }
+        Some(Command::Invalid(error)) => {
+            app.log_activity(format!("Invalid command: {error}"));
+            None
+        }
         None => {
             app.activity_log.add_message("Invalid command".to_string());
             None
@@ -489,15 +1560,26 @@ pub fn execute_command(app: &mut App, command: Option<Command>) -> Option<u32> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::tempdir;
+    use crate::taskstore::{TaskBuilder, load_tasks};
+    use tempfile::{TempDir, tempdir};
+
+    /// The targets `text` stands for when typed after a command
+    fn targets(text: &str) -> Vec<Target> {
+        match parse_command(format!("delete {text}")) {
+            Command::Delete(targets) => targets,
+            command => panic!("'{text}' are no targets: {command:?}"),
+        }
+    }
 
     /// Test utilities and conventions:
     /// - When checking activity log messages, always use `activity_log.latest_message()`
     ///   instead of trying to access the log entries directly. The ActivityLog struct
     ///   provides this method specifically for getting the most recent message.
     /// - Container names in messages are always lowercase (e.g., "taskpad" not "Taskpad")
-
-    fn setup_test_app() -> App {
+    ///
+    /// An app with three tasks, saving to a directory that lives as long as
+    /// the returned `TempDir`
+    fn setup_test_app() -> (App, TempDir) {
         let temp_dir = tempdir().expect("Failed to create temp directory");
         let tasks_file = temp_dir
             .path()
@@ -506,13 +1588,21 @@ mod tests {
             .unwrap()
             .to_string();
 
-        let mut app = App::default();
-        app.tasks_file = tasks_file;
+        let mut app = App {
+            tasks_file,
+            ..App::default()
+        };
         app.add_task(Task::new(1, "Buy groceries".to_string()));
         app.add_task(Task::new(2, "Call dentist".to_string()));
         app.add_task(Task::new(3, "Write report".to_string()));
         app.next_id = 4;
-        app
+        (app, temp_dir)
+    }
+
+    /// Runs a command line and returns the message it logged
+    fn run(app: &mut App, input: &str) -> String {
+        execute_command(app, Some(parse_command(input.to_string())));
+        app.activity_log.latest_message().unwrap().to_string()
     }
 
     #[test]
@@ -523,23 +1613,23 @@ mod tests {
 
         // Test complete command
         let cmd = parse_command("complete Test task".to_string());
-        assert!(matches!(cmd, Command::Complete(content) if content == "Test task"));
+        assert!(matches!(cmd, Command::Complete(content) if content == targets("Test task")));
 
         // Test delete command
         let cmd = parse_command("delete Test task".to_string());
-        assert!(matches!(cmd, Command::Delete(content) if content == "Test task"));
+        assert!(matches!(cmd, Command::Delete(content) if content == targets("Test task")));
 
         // Test move to taskpad command
         let cmd = parse_command("move to taskpad Test task".to_string());
-        assert!(matches!(cmd, Command::MoveToTaskpad(content) if content == "Test task"));
+        assert!(matches!(cmd, Command::MoveToTaskpad(content) if content == targets("Test task")));
 
         // Test move to backburner command
         let cmd = parse_command("move to backburner Test task".to_string());
-        assert!(matches!(cmd, Command::MoveToBackburner(content) if content == "Test task"));
+        assert!(matches!(cmd, Command::MoveToBackburner(content) if content == targets("Test task")));
 
         // Test move to shelved command
         let cmd = parse_command("move to shelved Test task".to_string());
-        assert!(matches!(cmd, Command::MoveToShelved(content) if content == "Test task"));
+        assert!(matches!(cmd, Command::MoveToShelved(content) if content == targets("Test task")));
 
         // Test focus command
         let cmd = parse_command("focus Test task".to_string());
@@ -549,13 +1639,17 @@ mod tests {
         let cmd = parse_command("show taskpad".to_string());
         assert!(matches!(cmd, Command::Show(container) if container == TaskContainer::Taskpad));
 
-        // Test with trailing spaces in task content
+        // Trailing spaces are not part of the task content
         let cmd = parse_command("complete Test task  ".to_string());
-        assert!(matches!(cmd, Command::Complete(content) if content == "Test task  "));
+        assert!(matches!(cmd, Command::Complete(content) if content == targets("Test task")));
 
         // Test toggle command
         let cmd = parse_command("toggle Test task".to_string());
-        assert!(matches!(cmd, Command::Toggle(content) if content == "Test task"));
+        assert!(matches!(cmd, Command::Toggle(content) if content == targets("Test task")));
+
+        // Test private command
+        let cmd = parse_command("private 1.2".to_string());
+        assert!(matches!(cmd, Command::TogglePrivate(query) if query == targets("1.2")));
 
         // Test subtask command
         let cmd = parse_command("subtask 1 subtask content".to_string());
@@ -565,7 +1659,7 @@ mod tests {
 
     #[test]
     fn test_find_task_by_partial_content() {
-        let app = setup_test_app();
+        let (app, _temp_dir) = setup_test_app();
 
         // Should match exact content
         let index = find_task(&app, "Buy groceries");
@@ -578,7 +1672,7 @@ mod tests {
 
     #[test]
     fn test_find_task_by_exact_content() {
-        let app = setup_test_app();
+        let (app, _temp_dir) = setup_test_app();
 
         // Find by exact content
         let index = find_task(&app, "Buy groceries");
@@ -588,7 +1682,7 @@ mod tests {
 
     #[test]
     fn test_find_task_by_display_index() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         app.display_container_state.update_display_order(&app.tasks);
 
         // Find by display index
@@ -598,13 +1692,13 @@ mod tests {
 
     #[test]
     fn test_find_nonexistent_task() {
-        let app = setup_test_app();
+        let (app, _temp_dir) = setup_test_app();
         assert!(find_task(&app, "nonexistent task").is_none());
     }
 
     #[test]
     fn test_find_deleted_task() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let initial_count = app.tasks.len();
 
         // First find and delete a task
@@ -618,22 +1712,41 @@ mod tests {
 
     #[test]
     fn test_complete_task_success() {
-        let mut app = setup_test_app();
-        let result = complete_task(&mut app, "Buy groceries", None);
+        let (mut app, _temp_dir) = setup_test_app();
+        let result = complete_task(&mut app, 1);
         assert!(
-            matches!(result, CommandResult::TaskCompleted { content } if content == "Buy groceries")
+            matches!(result, CommandResult::TaskCompleted { content, .. } if content == "Buy groceries")
+        );
+    }
+
+    #[test]
+    fn test_complete_task_completes_parents() {
+        let (mut app, _temp_dir) = setup_test_app();
+        let milk = execute_add_subtask(&mut app, "Buy groceries", "Buy milk").unwrap();
+        let eggs = execute_add_subtask(&mut app, "Buy groceries", "Buy eggs").unwrap();
+        let fresh = execute_add_subtask_by_id(&mut app, milk, "Check date").unwrap();
+
+        app.auto_complete_parents = false;
+        assert!(matches!(complete_task(&mut app, eggs), CommandResult::TaskCompleted { parents, .. } if parents.is_empty()));
+
+        app.auto_complete_parents = true;
+        execute_complete_by_id_command(&mut app, fresh);
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Completed task: Check date, and its parents: Buy milk, Buy groceries")
         );
+        assert!(app.tasks.iter().filter(|t| [1, milk].contains(&t.id)).all(|t| t.status == TaskStatus::Done));
     }
 
     #[test]
     fn test_complete_already_archived_task() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
 
         // First complete the task
-        let _ = complete_task(&mut app, "Buy groceries", None);
+        let _ = complete_task(&mut app, 1);
 
         // Try to complete it again
-        let result = complete_task(&mut app, "Buy groceries", None);
+        let result = complete_task(&mut app, 1);
         assert!(
             matches!(result, CommandResult::TaskAlreadyArchived(content) if content == "Buy groceries")
         );
@@ -641,70 +1754,70 @@ mod tests {
 
     #[test]
     fn test_complete_nonexistent_task() {
-        let mut app = setup_test_app();
-        let result = complete_task(&mut app, "nonexistent task", None);
+        let (mut app, _temp_dir) = setup_test_app();
+        let result = complete_task(&mut app, 99);
         assert!(matches!(result, CommandResult::NoMatchingTask));
     }
 
     #[test]
     fn test_delete_task_by_content() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let initial_count = app.tasks.len();
 
         // Delete by content match
-        execute_command(&mut app, Some(Command::Delete("Buy groceries".to_string())));
+        execute_command(&mut app, Some(Command::Delete(targets("Buy groceries"))));
         assert_eq!(app.tasks.len(), initial_count - 1);
         assert!(app.tasks.iter().all(|t| t.content != "Buy groceries"));
     }
 
     #[test]
     fn test_delete_task_by_index() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let initial_count = app.tasks.len();
 
         // Update display order first
         app.display_container_state.update_display_order(&app.tasks);
 
         // Delete by index
-        execute_command(&mut app, Some(Command::Delete("1".to_string())));
+        execute_command(&mut app, Some(Command::Delete(targets("1"))));
         assert_eq!(app.tasks.len(), initial_count - 1);
     }
 
     #[test]
     fn test_delete_nonexistent_task() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let initial_count = app.tasks.len();
 
         // Try to delete nonexistent task
         execute_command(
             &mut app,
-            Some(Command::Delete("nonexistent task".to_string())),
+            Some(Command::Delete(targets("nonexistent task"))),
         );
         assert_eq!(app.tasks.len(), initial_count);
     }
 
     #[test]
     fn test_delete_completed_task() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let initial_count = app.tasks.len();
 
         // First complete a task
-        let _ = complete_task(&mut app, "Buy groceries", None);
+        let _ = complete_task(&mut app, 1);
 
         // Then delete it
-        execute_command(&mut app, Some(Command::Delete("Buy groceries".to_string())));
+        execute_command(&mut app, Some(Command::Delete(targets("Buy groceries"))));
         assert_eq!(app.tasks.len(), initial_count - 1);
         assert!(app.tasks.iter().all(|t| t.content != "Buy groceries"));
     }
 
     #[test]
     fn test_move_to_taskpad_success() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let task = &mut app.tasks[0];
         task.container = TaskContainer::Backburner;
         let content = task.content.clone();
 
-        execute_move_to_taskpad_command(&mut app, &content);
+        execute_move_to_taskpad_command(&mut app, &targets(&content));
 
         assert_eq!(app.tasks[0].container, TaskContainer::Taskpad);
         assert_eq!(
@@ -715,11 +1828,11 @@ mod tests {
 
     #[test]
     fn test_move_to_taskpad_already_there() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let task = &app.tasks[0];
         let content = task.content.clone();
 
-        execute_move_to_taskpad_command(&mut app, &content);
+        execute_move_to_taskpad_command(&mut app, &targets(&content));
 
         assert_eq!(app.tasks[0].container, TaskContainer::Taskpad);
         assert_eq!(
@@ -730,11 +1843,11 @@ mod tests {
 
     #[test]
     fn test_move_to_backburner_success() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let task = &app.tasks[0];
         let content = task.content.clone();
 
-        execute_move_to_backburner_command(&mut app, &content);
+        execute_move_to_backburner_command(&mut app, &targets(&content));
 
         assert_eq!(app.tasks[0].container, TaskContainer::Backburner);
         assert_eq!(
@@ -745,12 +1858,12 @@ mod tests {
 
     #[test]
     fn test_move_to_backburner_already_there() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let task = &mut app.tasks[0];
         task.container = TaskContainer::Backburner;
         let content = task.content.clone();
 
-        execute_move_to_backburner_command(&mut app, &content);
+        execute_move_to_backburner_command(&mut app, &targets(&content));
 
         assert_eq!(app.tasks[0].container, TaskContainer::Backburner);
         assert_eq!(
@@ -761,11 +1874,11 @@ mod tests {
 
     #[test]
     fn test_move_to_shelved_success() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let task = &app.tasks[0];
         let content = task.content.clone();
 
-        execute_move_to_shelved_command(&mut app, &content);
+        execute_move_to_shelved_command(&mut app, &targets(&content));
 
         assert_eq!(app.tasks[0].container, TaskContainer::Shelved);
         assert_eq!(
@@ -776,12 +1889,12 @@ mod tests {
 
     #[test]
     fn test_move_to_shelved_already_there() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let task = &mut app.tasks[0];
         task.container = TaskContainer::Shelved;
         let content = task.content.clone();
 
-        execute_move_to_shelved_command(&mut app, &content);
+        execute_move_to_shelved_command(&mut app, &targets(&content));
 
         assert_eq!(app.tasks[0].container, TaskContainer::Shelved);
         assert_eq!(
@@ -792,21 +1905,21 @@ mod tests {
 
     #[test]
     fn test_move_nonexistent_task() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
 
-        execute_move_to_taskpad_command(&mut app, "Nonexistent task");
+        execute_move_to_taskpad_command(&mut app, &targets("Nonexistent task"));
         assert_eq!(
             app.activity_log.latest_message().unwrap(),
             "No matching task found"
         );
 
-        execute_move_to_backburner_command(&mut app, "Nonexistent task");
+        execute_move_to_backburner_command(&mut app, &targets("Nonexistent task"));
         assert_eq!(
             app.activity_log.latest_message().unwrap(),
             "No matching task found"
         );
 
-        execute_move_to_shelved_command(&mut app, "Nonexistent task");
+        execute_move_to_shelved_command(&mut app, &targets("Nonexistent task"));
         assert_eq!(
             app.activity_log.latest_message().unwrap(),
             "No matching task found"
@@ -815,7 +1928,7 @@ mod tests {
 
     #[test]
     fn test_focus_task_by_content() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         app.display_container_state.update_display_order(&app.tasks);
         let result = focus_task(&mut app, "Buy groceries");
         assert!(matches!(
@@ -826,7 +1939,7 @@ mod tests {
 
     #[test]
     fn test_focus_task_by_index_updates_state() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         app.display_container_state.update_display_order(&app.tasks);
 
         let result = focus_task(&mut app, "1");
@@ -839,7 +1952,7 @@ mod tests {
 
     #[test]
     fn test_focus_task_updates_input() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         app.display_container_state.update_display_order(&app.tasks);
 
         // Set initial input
@@ -855,14 +1968,14 @@ mod tests {
 
     #[test]
     fn test_focus_nonexistent_task() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let result = focus_task(&mut app, "nonexistent task");
         assert!(matches!(result, FocusResult::NoMatchingTask));
     }
 
     #[test]
     fn test_show_command() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         assert_eq!(
             app.display_container_state.active_container,
             TaskContainer::Taskpad
@@ -881,7 +1994,7 @@ mod tests {
 
     #[test]
     fn test_add_subtask_success() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let query = "Buy groceries";
         let content = "Subtask content";
 
@@ -901,9 +2014,24 @@ mod tests {
         assert!(parent.child_ids.contains(&subtask.id));
     }
 
+    #[test]
+    fn test_add_subtask_by_display_index() {
+        let (mut app, _temp_dir) = setup_test_app();
+        // Task 1 is archived, so the first task shown has ID 2
+        execute_complete_by_id_command(&mut app, 1);
+        execute_command(&mut app, Some(parse_command("subtask 1 Call back".to_string())));
+
+        let subtask = app.tasks.last().unwrap().clone();
+        assert_eq!(subtask.parent_id, Some(2));
+        assert_eq!(app.activity_log.latest_message(), Some("Added subtask to task 'Call dentist': Call back"));
+
+        execute_command(&mut app, Some(parse_command("subtask 1.1 Ask about Monday".to_string())));
+        assert_eq!(app.tasks.last().unwrap().parent_id, Some(subtask.id));
+    }
+
     #[test]
     fn test_add_subtask_nonexistent_parent() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let invalid_parent_query = "nonexistent task";
         let content = "Subtask content";
         let initial_task_count = app.tasks.len();
@@ -920,7 +2048,7 @@ mod tests {
 
     #[test]
     fn test_delete_subtask_updates_parent() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
 
         // Create a parent task
         execute_create_command(&mut app, "Parent task");
@@ -939,7 +2067,7 @@ mod tests {
         );
 
         // Delete the child task
-        execute_delete_command(&mut app, "Child task");
+        execute_delete_command(&mut app, &targets("Child task"));
 
         // Verify the child is removed from parent's child_ids
         let parent_index = app.tasks.iter().position(|t| t.id == parent_id).unwrap();
@@ -962,7 +2090,7 @@ mod tests {
 
     #[test]
     fn test_toggle_task_expansion() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let query = "Buy groceries";
 
         // Initially, the task should not be in the folded_tasks set
@@ -971,13 +2099,13 @@ mod tests {
         assert!(!app.display_container_state.folded_tasks.contains(&task_id));
 
         // Toggle the task expansion
-        execute_toggle_command(&mut app, query);
+        execute_toggle_command(&mut app, &targets(query));
 
         // Now the task should be in the folded_tasks set
         assert!(app.display_container_state.folded_tasks.contains(&task_id));
 
         // Toggle again
-        execute_toggle_command(&mut app, query);
+        execute_toggle_command(&mut app, &targets(query));
 
         // Now the task should not be in the folded_tasks set
         assert!(!app.display_container_state.folded_tasks.contains(&task_id));
@@ -989,12 +2117,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fold_commands() {
+        let (mut app, _temp_dir) = setup_test_app();
+        let milk = execute_add_subtask(&mut app, "Buy groceries", "Buy milk").unwrap();
+        let fridge = execute_add_subtask_by_id(&mut app, milk, "Check fridge").unwrap();
+        let visible = |app: &App| app.display_container_state.display_to_id.len();
+
+        execute_command(&mut app, Some(Command::CollapseAll));
+        assert_eq!(visible(&app), 3);
+        assert_eq!(app.activity_log.latest_message(), Some("Collapsed all tasks"));
+
+        execute_command(&mut app, Some(Command::ExpandToDepth(2)));
+        assert_eq!(visible(&app), 4);
+        assert!(!app.display_container_state.is_task_expanded(milk));
+        assert_eq!(app.activity_log.latest_message(), Some("Showing 2 levels of tasks"));
+
+        execute_command(&mut app, Some(Command::ExpandAll));
+        assert_eq!(visible(&app), 5);
+
+        // Collapsing a subtree moves the focus to its nearest visible ancestor
+        app.display_container_state.focus_task_and_update_input(Some(fridge), &app.tasks);
+        execute_fold_command(&mut app, &targets("Buy groceries"), false);
+        assert_eq!(visible(&app), 3);
+        assert!(!app.display_container_state.is_task_expanded(milk));
+        assert_eq!(app.display_container_state.focused_task_id(), app.tasks.iter().find(|t| t.content == "Buy groceries").map(|t| t.id));
+        assert_eq!(app.activity_log.latest_message(), Some("Collapsed task: Buy groceries"));
+
+        execute_fold_command(&mut app, &targets("Buy groceries"), true);
+        assert_eq!(visible(&app), 5);
+    }
+
     #[test]
     fn test_toggle_nonexistent_task() {
-        let mut app = setup_test_app();
+        let (mut app, _temp_dir) = setup_test_app();
         let invalid_query = "nonexistent task";
 
-        execute_toggle_command(&mut app, invalid_query);
+        execute_toggle_command(&mut app, &targets(invalid_query));
 
         // Verify activity log message
         assert_eq!(
@@ -1002,4 +2161,587 @@ mod tests {
             Some("No matching task found")
         );
     }
+
+    #[test]
+    fn test_focus_by_id_reveals_task_in_other_container() {
+        let (mut app, _temp_dir) = setup_test_app();
+        execute_add_subtask(&mut app, "Buy groceries", "Buy milk");
+        let child_id = app.tasks.last().unwrap().id;
+
+        // Hide the subtask: fold its parent and move the parent out of view
+        app.display_container_state.folded_tasks.insert(1);
+        app.tasks[0].container = TaskContainer::Backburner;
+        app.display_container_state.update_display_order(&app.tasks);
+
+        assert!(execute_focus_by_id_command(&mut app, child_id));
+        assert_eq!(
+            app.display_container_state.active_container,
+            TaskContainer::Backburner
+        );
+        assert!(app.display_container_state.is_task_expanded(1));
+        assert_eq!(app.display_container_state.input_value(), "Buy milk");
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Focused on task: Buy milk")
+        );
+    }
+
+    fn sample_item() -> GeniusItem {
+        GeniusItem {
+            id: "card-42".to_string(),
+            description: "Try the Pomodoro technique\nWork in 25 minute blocks.".to_string(),
+            metadata: serde_json::json!({"category": "productivity"}),
+            task_id: None,
+            card: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_capture_item_as_task_records_source() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        let task_id = execute_capture_item_command(
+            &mut app,
+            &sample_item(),
+            "genius",
+            "focus tips",
+            CaptureTarget::Task,
+        )
+        .unwrap();
+
+        let task = app.tasks.iter().find(|t| t.id == task_id).unwrap();
+        assert_eq!(task.content, "Try the Pomodoro technique");
+        assert_eq!(task.notes.as_deref(), Some("Work in 25 minute blocks."));
+        let source = task.source.as_ref().unwrap();
+        assert_eq!(source.item_id, "card-42");
+        assert_eq!(source.provider, "genius");
+        assert_eq!(source.query, "focus tips");
+        assert_eq!(source.metadata["category"], "productivity");
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Captured suggestion as task: Try the Pomodoro technique")
+        );
+    }
+
+    #[test]
+    fn test_capture_item_as_subtask() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        let task_id = execute_capture_item_command(
+            &mut app,
+            &sample_item(),
+            "genius",
+            "focus tips",
+            CaptureTarget::Subtask(2),
+        )
+        .unwrap();
+
+        let task = app.tasks.iter().find(|t| t.id == task_id).unwrap();
+        assert_eq!(task.parent_id, Some(2));
+        assert!(app.tasks[1].child_ids.contains(&task_id));
+        assert!(task.source.is_some());
+    }
+
+    #[test]
+    fn test_capture_item_as_note() {
+        let (mut app, _temp_dir) = setup_test_app();
+        let initial_count = app.tasks.len();
+
+        let task_id = execute_capture_item_command(
+            &mut app,
+            &sample_item(),
+            "genius",
+            "focus tips",
+            CaptureTarget::Note(3),
+        );
+
+        assert_eq!(task_id, Some(3));
+        assert_eq!(app.tasks.len(), initial_count);
+        let notes = app.tasks[2].notes.as_deref().unwrap();
+        assert!(notes.starts_with("Try the Pomodoro technique"));
+        assert!(notes.ends_with("(from genius item card-42)"));
+    }
+
+    #[test]
+    fn test_toggle_private() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        execute_command(&mut app, Some(Command::TogglePrivate(targets("1"))));
+        assert!(app.tasks[0].private);
+        assert!(
+            app.activity_log
+                .latest_message()
+                .unwrap()
+                .starts_with("Marked task as private")
+        );
+
+        execute_command(&mut app, Some(Command::TogglePrivate(targets("1"))));
+        assert!(!app.tasks[0].private);
+    }
+
+    #[test]
+    fn test_login_and_logout() {
+        let (mut app, temp_dir) = setup_test_app();
+        app.credentials_file = temp_dir
+            .path()
+            .join("credentials.enc")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // Without saved credentials, login asks for new ones
+        execute_command(&mut app, Some(parse_command("login".to_string())));
+        assert_eq!(app.credential_prompt, Some(CredentialPrompt::ApiKey));
+        execute_credential_input(&mut app, "sk-test-key");
+        execute_credential_input(&mut app, "org-1");
+        execute_credential_input(&mut app, "passphrase");
+        assert_eq!(app.credential_prompt, None);
+        assert!(std::path::Path::new(&app.credentials_file).exists());
+
+        // Saved credentials are unlocked with the passphrase
+        execute_command(&mut app, Some(parse_command("login".to_string())));
+        assert_eq!(app.credential_prompt, Some(CredentialPrompt::Unlock));
+        execute_credential_input(&mut app, "wrong");
+        assert_eq!(app.credential_prompt, Some(CredentialPrompt::Unlock));
+        execute_credential_input(&mut app, "passphrase");
+        assert_eq!(app.credential_prompt, None);
+        assert_eq!(app.activity_log.latest_message(), Some("Logged in"));
+
+        execute_command(&mut app, Some(parse_command("logout".to_string())));
+        assert!(!std::path::Path::new(&app.credentials_file).exists());
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Logged out, saved API key deleted")
+        );
+    }
+
+    #[test]
+    fn test_mistakes_need_second_enter() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        assert_eq!(command_for_enter(&mut app, "delet 3"), None);
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Did you mean 'delete 3'? Press Enter again to create it as a task.")
+        );
+        assert_eq!(
+            command_for_enter(&mut app, "delet 3"),
+            Some(Command::Create("delet 3".to_string()))
+        );
+
+        // Parse errors are reported, changed input asks again
+        assert_eq!(command_for_enter(&mut app, "subtask 1"), None);
+        assert!(app.activity_log.latest_message().unwrap().starts_with("Invalid command: 'subtask' expects"));
+        assert_eq!(command_for_enter(&mut app, "show archivd"), None);
+        assert_eq!(
+            command_for_enter(&mut app, "show archivd"),
+            Some(Command::Create("show archivd".to_string()))
+        );
+
+        // Capitalized commands that only name tasks by content may be tasks
+        for input in ["Tag photos from trip", "Focus group prep"] {
+            assert_eq!(command_for_enter(&mut app, input), None);
+            assert_eq!(command_for_enter(&mut app, input), Some(Command::Create(input.to_string())));
+        }
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Type 'focus' in lowercase to run it as a command. Press Enter again to create it as a task.")
+        );
+        assert_eq!(command_for_enter(&mut app, "focus group prep"), Some(Command::Focus("group prep".to_string())));
+        assert_eq!(command_for_enter(&mut app, "Complete 2"), Some(Command::Complete(vec![Target::Index(TaskIndex::from_str("2").unwrap())])));
+
+        // Ordinary tasks and valid commands never ask
+        assert_eq!(
+            command_for_enter(&mut app, "Buy milk"),
+            Some(Command::Create("Buy milk".to_string()))
+        );
+        assert_eq!(command_for_enter(&mut app, "toggle app mode"), Some(Command::ToggleAppMode));
+        assert_eq!(app.pending_create, None);
+    }
+
+    #[test]
+    fn test_multiple_targets() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        // Indices refer to the list as displayed when the command was entered
+        execute_command(&mut app, Some(parse_command("C 1 3 \"nonexistent\"".to_string())));
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Completed 2 tasks, no match for: nonexistent")
+        );
+        let archived: Vec<&str> = app
+            .tasks
+            .iter()
+            .filter(|t| t.container == TaskContainer::Archived)
+            .map(|t| t.content.as_str())
+            .collect();
+        assert_eq!(archived, ["Buy groceries", "Write report"]);
+
+        execute_command(&mut app, Some(parse_command("mv backburner \"Call dentist\"".to_string())));
+        assert_eq!(app.tasks[1].container, TaskContainer::Backburner);
+
+        execute_command(&mut app, Some(parse_command("complete \"Buy milk".to_string())));
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Invalid command: quote is never closed (column 10)")
+        );
+    }
+
+    #[test]
+    fn test_batch_targets() {
+        let (mut app, _temp_dir) = setup_test_app();
+        app.add_task(TaskBuilder::new(4).content("Plan trip").children(vec![5, 6]).build());
+        app.add_task(TaskBuilder::new(5).content("Book flights").parent(4).build());
+        app.add_task(TaskBuilder::new(6).content("Book hotel").parent(4).children(vec![7]).build());
+        app.add_task(TaskBuilder::new(7).content("Compare prices").parent(6).build());
+
+        // Ranges and lists, tagged with one log entry and one save
+        execute_command(&mut app, Some(parse_command("tag Errands 1-2,4.1".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Tagged #errands on 3 tasks"));
+        let tagged: Vec<u32> = app.tasks.iter().filter(|t| t.has_tag("errands")).map(|t| t.id).collect();
+        assert_eq!(tagged, [1, 2, 5]);
+        let saved = load_tasks(&app.tasks_file).unwrap();
+        assert_eq!(saved.iter().filter(|t| t.has_tag("errands")).count(), 3);
+
+        // Tag queries, searches and subtrees
+        execute_command(&mut app, Some(parse_command("untag errands #errands".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Removed #errands from 3 tasks"));
+        execute_command(&mut app, Some(parse_command("mv backburner /book".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Moved to backburner 2 tasks"));
+        execute_command(&mut app, Some(parse_command("complete 4.*".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Completed 3 tasks"));
+        assert!(app.tasks.iter().filter(|t| t.parent_id.is_some()).all(|t| t.status == TaskStatus::Done));
+
+        // Nothing matched by a set is reported as a summary too
+        execute_command(&mut app, Some(parse_command("rm #nothing".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Deleted 0 tasks, no match for: #nothing"));
+        assert_eq!(app.tasks.len(), 7);
+    }
+
+    #[test]
+    fn test_selection() {
+        let (mut app, _temp_dir) = setup_test_app();
+        app.add_task(TaskBuilder::new(4).content("Plan trip").children(vec![5]).build());
+        app.add_task(TaskBuilder::new(5).content("Book flights").parent(4).build());
+        app.display_container_state.update_display_order(&app.tasks);
+
+        // Shift+Down twice from task 2, then Shift+Up shrinks the selection again
+        let state = &mut app.display_container_state;
+        state.focus_task_and_update_input(Some(2), &app.tasks);
+        for forward in [true, true, false] {
+            let next = state.extend_selection(forward);
+            state.focus_task_and_update_input(next, &app.tasks);
+        }
+        assert_eq!(state.focused_task_id(), Some(3));
+        assert_eq!(state.selected_task_ids(), [2, 3]);
+        state.toggle_selection(4);
+        assert_eq!(state.selected_task_ids(), [2, 3, 4]);
+
+        // Commands without tasks act on the selection
+        let command = command_for_enter(&mut app, "tag home").unwrap();
+        assert_eq!(command, Command::Tag("home".to_string(), vec![Target::Selection]));
+        execute_command(&mut app, Some(command));
+        assert_eq!(app.activity_log.latest_message(), Some("Tagged #home on 3 tasks"));
+        execute_command(&mut app, Some(parse_command("complete @".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Completed 3 tasks"));
+
+        // Completed tasks are no longer visible, so they leave the selection
+        assert!(app.display_container_state.selected_tasks.is_empty());
+        assert_eq!(command_for_enter(&mut app, "complete"), None);
+    }
+
+    #[test]
+    fn test_hierarchy_commands() {
+        let (mut app, _temp_dir) = setup_test_app();
+        app.add_task(Task::new(4, "Plan trip".to_string()));
+        app.display_container_state.update_display_order(&app.tasks);
+        let parent = |app: &App, id: u32| app.tasks[find_task_by_id(&app.tasks, id).unwrap()].parent_id;
+
+        // Indenting 2 and 3 puts both under 1, in order
+        assert_eq!(run(&mut app, "indent 2 3"), "Indented 2 tasks");
+        assert_eq!(app.tasks[0].child_ids, [2, 3]);
+        assert_eq!(run(&mut app, "indent 1"), "No task above to indent under: Buy groceries");
+
+        // Moving a task up and down among its siblings
+        assert_eq!(run(&mut app, "indent 1.2"), "Indented task: Write report");
+        assert_eq!(run(&mut app, "outdent 1.1.1"), "Outdented task: Write report");
+        assert_eq!(run(&mut app, "c 1.2"), "Completed task: Write report");
+        execute_command(&mut app, Some(Command::MoveUp(targets("1.1"))));
+        assert_eq!(app.activity_log.latest_message(), Some("Task is already first: Call dentist"));
+        execute_command(&mut app, Some(Command::MoveDown(targets("1"))));
+        assert_eq!(app.display_container_state.display_to_id, [4, 1, 2, 3]);
+
+        // A parent moves with its subtasks, and never below itself
+        assert_eq!(run(&mut app, "move 2 2.1 under 1"), "Moved under 'Plan trip' 1 task");
+        assert_eq!(parent(&app, 1), Some(4));
+        assert_eq!(parent(&app, 2), Some(1));
+        assert_eq!(run(&mut app, "move 1 under 1.1.1"), "Can't move 'Plan trip' below itself");
+        let saved = load_tasks(&app.tasks_file).unwrap();
+        assert_eq!(saved.iter().find(|t| t.id == 4).unwrap().child_ids, [1]);
+    }
+
+    #[test]
+    fn test_place_command() {
+        let (mut app, _temp_dir) = setup_test_app();
+        let place = |app: &mut App, tasks: &str, placement: Placement| {
+            execute_command(app, Some(Command::Place(targets(tasks), placement)));
+            app.activity_log.latest_message().unwrap().to_string()
+        };
+
+        // Dropping tasks before, after and under another task
+        assert_eq!(place(&mut app, "3", Placement::Before(1)), "Moved task: Write report");
+        assert_eq!(app.display_container_state.display_to_id, [3, 1, 2]);
+        assert_eq!(place(&mut app, "2 3", Placement::LastChildOf(3)), "Moved 2 tasks");
+        assert_eq!(app.tasks[find_task_by_id(&app.tasks, 3).unwrap()].child_ids, [1, 2]);
+        assert_eq!(place(&mut app, "1.1 1.2", Placement::After(3)), "Moved 2 tasks");
+        assert_eq!(app.display_container_state.display_to_id, [3, 1, 2]);
+
+        // Never next to or below itself, and only in manual order at the top level
+        assert_eq!(place(&mut app, "1", Placement::After(3)), "Task is already there: Write report");
+        place(&mut app, "1", Placement::LastChildOf(2));
+        assert_eq!(place(&mut app, "2", Placement::Before(3)), "Can't move 'Call dentist' below itself");
+        execute_command(&mut app, Some(Command::Sort(SortMode::Alphabetical)));
+        assert_eq!(
+            place(&mut app, "1", Placement::After(2)),
+            "Tasks are sorted by name, use 'sort manual' to arrange them: Buy groceries"
+        );
+        let saved = load_tasks(&app.tasks_file).unwrap();
+        assert_eq!(saved.iter().find(|t| t.id == 2).unwrap().child_ids, [3]);
+    }
+
+    #[test]
+    fn test_edit_notes() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        let notes = "## Agenda\n\n- Budget\n- Hiring\n\n";
+        execute_command(&mut app, Some(Command::EditNotes(3, notes.to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Saved notes: Write report"));
+        let saved = load_tasks(&app.tasks_file).unwrap();
+        assert_eq!(saved[2].notes.as_deref(), Some("## Agenda\n\n- Budget\n- Hiring"));
+
+        // Searches look into notes too
+        execute_command(&mut app, Some(parse_command("tag work /hiring".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Tagged #work on 1 task"));
+        assert!(app.tasks[2].has_tag("work"));
+
+        execute_command(&mut app, Some(Command::EditNotes(3, " \n".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Removed notes: Write report"));
+        assert!(app.tasks[2].notes.is_none());
+    }
+
+    #[test]
+    fn test_links_follow_renames() {
+        let (mut app, _temp_dir) = setup_test_app();
+        execute_command(&mut app, Some(Command::Edit(2, "Call dentist about [[Write report]]".to_string())));
+        execute_command(&mut app, Some(Command::EditNotes(1, "Before [[write report]], see [[#3]]".to_string())));
+
+        execute_command(&mut app, Some(Command::Edit(3, "Write annual report".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Task updated, renamed links in 2 tasks"));
+        assert_eq!(app.tasks[1].content, "Call dentist about [[Write annual report]]");
+        assert_eq!(app.tasks[0].notes.as_deref(), Some("Before [[Write annual report]], see [[#3]]"));
+        assert_eq!(crate::links::backlinks(&app.tasks, 3), [1, 2]);
+        let saved = load_tasks(&app.tasks_file).unwrap();
+        assert_eq!(saved[1].content, "Call dentist about [[Write annual report]]");
+
+        // Changing only the case keeps the links as they are
+        execute_command(&mut app, Some(Command::Edit(3, "write annual report".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("Task updated"));
+    }
+
+    #[test]
+    fn test_zoom() {
+        let (mut app, _temp_dir) = setup_test_app();
+        run(&mut app, "indent 2 3");
+
+        // Numbering and content matches become relative to the zoomed task
+        assert_eq!(run(&mut app, "zoom 1"), "Zoomed into: Buy groceries");
+        assert_eq!(app.display_container_state.display_to_id, [2, 3]);
+        assert_eq!(app.display_container_state.focused_task_id(), Some(2));
+        assert_eq!(run(&mut app, "tag home 1"), "Tagged task #home: Call dentist");
+        assert_eq!(run(&mut app, "focus Write report"), "Focused on task: Write report");
+        assert_eq!(run(&mut app, "outdent 2"), "Task is already at the top level: Write report");
+
+        // New tasks land inside the zoomed task
+        let task_id = execute_create_command(&mut app, "Milk");
+        assert_eq!(app.tasks[0].child_ids, [2, 3, task_id]);
+        app.display_container_state.update_display_order(&app.tasks);
+        assert_eq!(app.display_container_state.display_to_id, [2, 3, task_id]);
+
+        // Zooming out shows everything again, focused on the zoomed task
+        assert_eq!(run(&mut app, "zoom out"), "Showing all taskpad tasks");
+        assert_eq!(app.display_container_state.focused_task_id(), Some(1));
+        assert_eq!(run(&mut app, "zoom out"), "Not zoomed in");
+
+        // Deleting the zoomed task ends the zoom
+        run(&mut app, "zoom 1.1");
+        assert_eq!(app.display_container_state.zoom_path(&app.tasks).len(), 2);
+        app.remove_task(find_task_by_id(&app.tasks, 2).unwrap());
+        assert_eq!(app.display_container_state.zoom, None);
+    }
+
+    #[test]
+    fn test_sort_and_due() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        assert_eq!(run(&mut app, "due 2026-11-02 1"), "Task due 2026-11-02: Buy groceries");
+        assert_eq!(run(&mut app, "due 2026-10-31 3"), "Task due 2026-10-31: Write report");
+        assert_eq!(run(&mut app, "sort due"), "Sorting taskpad by due");
+        assert_eq!(app.display_container_state.display_to_id, [3, 1, 2]);
+
+        // Manual moves wait for manual order; the sort mode belongs to the container
+        assert_eq!(run(&mut app, "c 1"), "Completed task: Write report");
+        execute_command(&mut app, Some(Command::MoveUp(targets("2"))));
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Tasks are sorted by due, use 'sort manual' to arrange them: Call dentist")
+        );
+        run(&mut app, "show archived");
+        assert_eq!(app.display_container_state.sort_mode(), SortMode::Manual);
+        run(&mut app, "show taskpad");
+        assert_eq!(run(&mut app, "sort name"), "Sorting taskpad by name");
+        assert_eq!(app.display_container_state.display_to_id, [1, 2]);
+        let saved = crate::sorting::load_sort_modes(&app.sorting_file()).unwrap();
+        assert_eq!(saved.get(TaskContainer::Taskpad), SortMode::Alphabetical);
+
+        // Back in manual order, tasks can be moved again
+        run(&mut app, "sort manual");
+        execute_command(&mut app, Some(Command::MoveUp(targets("2"))));
+        assert_eq!(app.display_container_state.display_to_id, [2, 1]);
+        assert_eq!(run(&mut app, "due none 1"), "Task has no due date: Call dentist");
+    }
+
+    #[test]
+    fn test_recurring_tasks() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        // An inline rule becomes the recurrence, with the first due date
+        let rent = execute_create_command(&mut app, "Pay rent every:1st");
+        let task = &app.tasks[find_task_by_id(&app.tasks, rent).unwrap()];
+        let first_due = Recurrence::Monthly(1).first_due(today());
+        assert_eq!(task.content, "Pay rent");
+        assert_eq!(task.recurrence, Some(Recurrence::Monthly(1)));
+        assert_eq!(task.due, first_due);
+
+        // Completing it archives it and brings the next occurrence back in its place
+        let next_due = Recurrence::Monthly(1).next_due(first_due, today());
+        assert_eq!(run(&mut app, "c 4"), format!("Completed task: Pay rent, next due {next_due}"));
+        let next = app.tasks.iter().find(|t| t.content == "Pay rent" && t.status == TaskStatus::Todo).unwrap();
+        assert_eq!((next.due, next.container), (Some(next_due), TaskContainer::Taskpad));
+        assert_eq!(app.display_container_state.display_to_id.last(), Some(&next.id));
+
+        // Subtasks come back under their parent
+        let milk = execute_add_subtask(&mut app, "Buy groceries", "Buy milk every:3d").unwrap();
+        execute_add_subtask(&mut app, "Buy groceries", "Buy eggs");
+        assert_eq!(app.tasks[find_task_by_id(&app.tasks, milk).unwrap()].due, None);
+        execute_complete_by_id_command(&mut app, milk);
+        let second_milk = app.tasks[0].child_ids[0];
+        execute_complete_by_id_command(&mut app, second_milk);
+        let groceries = &app.tasks[0];
+        assert_eq!(groceries.child_ids.len(), 2);
+        let next_milk = &app.tasks[find_task_by_id(&app.tasks, groceries.child_ids[0]).unwrap()];
+        assert_eq!(next_milk.content, "Buy milk");
+        assert_eq!(next_milk.due, Some(today() + chrono::Days::new(3)));
+        // Completed occurrences are archived on their own and don't count towards progress
+        let archived = &app.tasks[find_task_by_id(&app.tasks, milk).unwrap()];
+        assert_eq!((archived.parent_id, archived.container), (None, TaskContainer::Archived));
+        assert_eq!(operations::progress_by_task(&app.tasks)[&1], operations::Progress { done: 0, total: 2 });
+
+        assert_eq!(run(&mut app, "repeat day 4"), "Task repeats every day: Pay rent");
+        assert_eq!(run(&mut app, "repeat none 4"), "Task no longer repeats: Pay rent");
+        assert_eq!(run(&mut app, "repeat none 4"), "Task doesn't repeat: Pay rent");
+        let count = app.tasks.len();
+        assert_eq!(run(&mut app, "c 4"), "Completed task: Pay rent");
+        assert_eq!(app.tasks.len(), count);
+
+        // A recurring parent comes back when it is completed along with its subtasks
+        app.auto_complete_parents = true;
+        let review = execute_create_command(&mut app, "Weekly review every:fri");
+        let inbox = execute_add_subtask_by_id(&mut app, review, "Clear inbox").unwrap();
+        execute_complete_by_id_command(&mut app, inbox);
+        let next_due = Recurrence::Weekly(vec![chrono::Weekday::Fri]);
+        let next_due = next_due.next_due(next_due.first_due(today()), today());
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Completed task: Clear inbox, and its parent: Weekly review")
+        );
+        let next_review = app.tasks.iter().find(|t| t.content == "Weekly review" && t.status == TaskStatus::Todo).unwrap();
+        assert_eq!(next_review.due, Some(next_due));
+        assert!(next_review.child_ids.is_empty());
+
+        // Recurrences are saved with the tasks
+        let saved = crate::taskstore::load_tasks(&app.tasks_file).unwrap();
+        assert!(saved.iter().any(|t| t.recurrence == Some(Recurrence::AfterCompletion(3))));
+    }
+
+    #[test]
+    fn test_genius_sessions() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        execute_command(&mut app, Some(parse_command("session new".to_string())));
+        let first_id = app.sessions.active().unwrap().id.clone();
+        record_session_query(&mut app, "focus tips");
+
+        execute_command(&mut app, Some(parse_command("session new".to_string())));
+        assert_ne!(app.sessions.active().unwrap().id, first_id);
+
+        execute_command(&mut app, Some(parse_command("sessions".to_string())));
+        let listing = app.activity_log.latest_message().unwrap();
+        assert!(listing.contains("1 queries, last 'focus tips'"));
+        assert!(listing.contains("2. "));
+
+        // Resuming a session without queries only switches the session
+        let second_id = app.sessions.active().unwrap().id.clone();
+        execute_command(&mut app, Some(parse_command(format!("session resume {}", &second_id[..8]))));
+        assert_eq!(app.sessions.active().unwrap().id, second_id);
+
+        let loaded = crate::genius_platform::sessions::load_sessions(&app.sessions_file()).unwrap();
+        assert_eq!(loaded.sessions().len(), 2);
+        assert_eq!(loaded.sessions()[0].queries, vec!["focus tips"]);
+
+        execute_command(&mut app, Some(parse_command("session resume 9".to_string())));
+        assert_eq!(app.activity_log.latest_message(), Some("No matching session found"));
+    }
+
+    #[test]
+    fn test_commit_feed_query() {
+        let (mut app, _temp_dir) = setup_test_app();
+        execute_command(&mut app, Some(parse_command("session new".to_string())));
+        let queries = |app: &App| app.sessions.active().unwrap().queries.clone();
+
+        // Task text typed in PKM mode is never a Feed query
+        commit_feed_query(&mut app, "Buy milk", "Buy milk");
+        assert!(queries(&app).is_empty());
+
+        app.app_mode = AppMode::Feed;
+        commit_feed_query(&mut app, "focus ti", "focus");
+        assert!(queries(&app).is_empty());
+        commit_feed_query(&mut app, "focus tips ", "focus tips");
+        assert_eq!(queries(&app), ["focus tips"]);
+        assert_eq!(app.input_history.entries(AppMode::Feed), ["focus tips"]);
+        assert!(app.input_history.entries(AppMode::Pkm).is_empty());
+
+        // A refined query is a new entry
+        commit_feed_query(&mut app, "rust", "rust");
+        commit_feed_query(&mut app, "rust async", "rust async");
+        assert_eq!(queries(&app), ["focus tips", "rust", "rust async"]);
+    }
+
+    #[test]
+    fn test_toggle_pin_persists_pinboard() {
+        let (mut app, _temp_dir) = setup_test_app();
+
+        assert!(execute_toggle_pin_command(&mut app, &sample_item(), "focus tips"));
+        assert_eq!(
+            app.activity_log.latest_message(),
+            Some("Pinned item: Try the Pomodoro technique")
+        );
+
+        let loaded = crate::genius_platform::pinboard::load_pins(&app.pins_file()).unwrap();
+        assert!(loaded.is_pinned("card-42"));
+        assert_eq!(loaded.items()[0].query, "focus tips");
+
+        assert!(!execute_toggle_pin_command(&mut app, &sample_item(), "focus tips"));
+        let loaded = crate::genius_platform::pinboard::load_pins(&app.pins_file()).unwrap();
+        assert!(loaded.is_empty());
+    }
 }
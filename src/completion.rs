@@ -0,0 +1,381 @@
+//! Completion of commands in the input line.
+//!
+//! While a command is typed on the input line in PKM mode, the completion
+//! engine suggests command keywords, container names, task indices and
+//! sessions. It also recognizes near misses: input that `parse_command` would
+//! turn into a new task but that was most likely meant as a command with a
+//! mistyped keyword, such as `delet 3` or `shwo archived`.
+
+use crate::App;
+use crate::command_parser::is_task_index;
+use crate::sorting::SortMode;
+
+/// Most suggestions shown at once
+pub const MAX_COMPLETIONS: usize = 8;
+
+/// What a command expects after its keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Argument {
+    /// Nothing
+    None,
+    /// A task index or part of its content
+    Task,
+    /// A task followed by the text of a new subtask
+    TaskThenText,
+    /// A tag followed by tasks
+    TagThenTasks,
+    /// A date followed by tasks
+    DateThenTasks,
+    /// A recurrence rule followed by tasks
+    RuleThenTasks,
+    /// A sort order
+    SortMode,
+    /// A container name
+    Container,
+    /// A session number or ID
+    Session,
+}
+
+/// A command keyword and what follows it
+struct CommandSpec {
+    keyword: &'static str,
+    argument: Argument,
+    description: &'static str,
+}
+
+/// All commands understood by `parse_command`
+const COMMANDS: &[CommandSpec] = &[
+    CommandSpec { keyword: "complete", argument: Argument::Task, description: "mark a task done" },
+    CommandSpec { keyword: "delete", argument: Argument::Task, description: "delete a task" },
+    CommandSpec { keyword: "move to taskpad", argument: Argument::Task, description: "move a task to the taskpad" },
+    CommandSpec { keyword: "move to backburner", argument: Argument::Task, description: "move a task to the backburner" },
+    CommandSpec { keyword: "move to shelved", argument: Argument::Task, description: "shelve a task" },
+    CommandSpec { keyword: "move", argument: Argument::TaskThenText, description: "move a task under another, like 'move 3.2 under 5'" },
+    CommandSpec { keyword: "indent", argument: Argument::Task, description: "make a task a subtask of the one above" },
+    CommandSpec { keyword: "outdent", argument: Argument::Task, description: "move a subtask up to its parent's level" },
+    CommandSpec { keyword: "focus", argument: Argument::Task, description: "focus a task" },
+    CommandSpec { keyword: "zoom", argument: Argument::Task, description: "show only a task's subtasks; 'zoom out' goes back" },
+    CommandSpec { keyword: "private", argument: Argument::Task, description: "keep a task out of Genius queries" },
+    CommandSpec { keyword: "toggle", argument: Argument::Task, description: "expand or collapse a task" },
+    CommandSpec { keyword: "expand", argument: Argument::Task, description: "expand a task and its subtasks; 'expand all', 'expand to 2'" },
+    CommandSpec { keyword: "collapse", argument: Argument::Task, description: "collapse a task and its subtasks; 'collapse all'" },
+    CommandSpec { keyword: "tag", argument: Argument::TagThenTasks, description: "tag tasks" },
+    CommandSpec { keyword: "untag", argument: Argument::TagThenTasks, description: "remove a tag from tasks" },
+    CommandSpec { keyword: "due", argument: Argument::DateThenTasks, description: "set a due date, like 'due 2026-10-31 3'" },
+    CommandSpec { keyword: "repeat", argument: Argument::RuleThenTasks, description: "make tasks recur, like 'repeat mon,thu 3'" },
+    CommandSpec { keyword: "sort", argument: Argument::SortMode, description: "sort the tasks of this container" },
+    CommandSpec { keyword: "toggle app mode", argument: Argument::None, description: "switch between PKM and Feed mode" },
+    CommandSpec { keyword: "show", argument: Argument::Container, description: "switch container" },
+    CommandSpec { keyword: "subtask", argument: Argument::TaskThenText, description: "add a subtask" },
+    CommandSpec { keyword: "login", argument: Argument::None, description: "unlock or store the Genius API key" },
+    CommandSpec { keyword: "logout", argument: Argument::None, description: "forget the Genius API key" },
+    CommandSpec { keyword: "session new", argument: Argument::None, description: "start a new Genius session" },
+    CommandSpec { keyword: "session resume", argument: Argument::Session, description: "switch to a Genius session" },
+    CommandSpec { keyword: "sessions", argument: Argument::None, description: "list Genius sessions" },
+];
+
+/// Containers accepted by `show`
+const CONTAINER_NAMES: &[&str] = &["taskpad", "backburner", "shelved", "archived"];
+
+/// A suggestion for the input line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The whole input line after accepting the suggestion
+    pub text: String,
+    /// What the suggestion shows
+    pub label: String,
+    /// Short explanation shown next to the label
+    pub detail: String,
+}
+
+/// Which suggestion is highlighted in the popup
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompletionState {
+    /// Input the suggestions were made for
+    input: String,
+    /// Index of the highlighted suggestion
+    selected: usize,
+}
+
+impl CompletionState {
+    /// Highlight the first suggestion again if the input changed
+    pub fn update(&mut self, input: &str) {
+        if self.input != input {
+            self.input = input.to_string();
+            self.selected = 0;
+        }
+    }
+
+    /// Index of the highlighted suggestion among `count` suggestions
+    pub fn selected(&self, count: usize) -> usize {
+        self.selected.min(count.saturating_sub(1))
+    }
+
+    /// Highlight the next suggestion, wrapping around
+    pub fn select_next(&mut self, count: usize) {
+        if count > 0 {
+            self.selected = (self.selected(count) + 1) % count;
+        }
+    }
+
+    /// Highlight the previous suggestion, wrapping around
+    pub fn select_previous(&mut self, count: usize) {
+        if count > 0 {
+            self.selected = (self.selected(count) + count - 1) % count;
+        }
+    }
+}
+
+/// Suggestions for the input line, at most `MAX_COMPLETIONS`
+pub fn complete(app: &App, input: &str) -> Vec<Completion> {
+    let input = input.trim_start();
+    if input.is_empty() {
+        return Vec::new();
+    }
+    let lower = input.to_lowercase();
+    let mut completions = Vec::new();
+
+    // Keywords the input is the start of
+    for spec in COMMANDS {
+        if spec.keyword.starts_with(&lower) && spec.keyword != lower {
+            let space = if spec.argument == Argument::None { "" } else { " " };
+            completions.push(Completion {
+                text: format!("{}{space}", spec.keyword),
+                label: spec.keyword.to_string(),
+                detail: spec.description.to_string(),
+            });
+        }
+    }
+
+    // Arguments of a keyword that was typed in full
+    for spec in COMMANDS {
+        let Some(argument) = lower.strip_prefix(spec.keyword).and_then(|rest| rest.strip_prefix(' ')) else {
+            continue;
+        };
+        let prefix = format!("{} ", spec.keyword);
+        match spec.argument {
+            Argument::Task => complete_tasks(app, &prefix, argument, &mut completions),
+            Argument::TaskThenText if !argument.contains(' ') => {
+                complete_tasks(app, &prefix, argument, &mut completions);
+            }
+            Argument::TagThenTasks if !argument.contains(' ') => {
+                let mut tags: Vec<&str> = app.tasks.iter().flat_map(|t| t.tags.iter().map(String::as_str)).collect();
+                tags.sort_unstable();
+                tags.dedup();
+                let typed = argument.trim_start_matches('#');
+                for tag in tags.into_iter().filter(|tag| tag.starts_with(typed)) {
+                    completions.push(Completion {
+                        text: format!("{prefix}{tag} "),
+                        label: format!("#{tag}"),
+                        detail: "tag".to_string(),
+                    });
+                }
+            }
+            Argument::SortMode => {
+                for name in SortMode::NAMES.iter().filter(|name| name.starts_with(argument)) {
+                    completions.push(Completion {
+                        text: format!("{prefix}{name}"),
+                        label: (*name).to_string(),
+                        detail: "sort order".to_string(),
+                    });
+                }
+            }
+            Argument::Container => {
+                for name in CONTAINER_NAMES.iter().filter(|name| name.starts_with(argument)) {
+                    completions.push(Completion {
+                        text: format!("{prefix}{name}"),
+                        label: (*name).to_string(),
+                        detail: "container".to_string(),
+                    });
+                }
+            }
+            Argument::Session => {
+                for (index, session) in app.sessions.sessions().iter().enumerate() {
+                    let number = (index + 1).to_string();
+                    if number.starts_with(argument) || session.id.starts_with(argument) {
+                        completions.push(Completion {
+                            text: format!("{prefix}{number}"),
+                            label: format!("{number} {}", session.short_id()),
+                            detail: session.last_query().unwrap_or("no queries").to_string(),
+                        });
+                    }
+                }
+            }
+            Argument::TaskThenText | Argument::TagThenTasks | Argument::DateThenTasks | Argument::RuleThenTasks | Argument::None => {}
+        }
+    }
+
+    completions.truncate(MAX_COMPLETIONS);
+    completions
+}
+
+/// Suggest visible tasks whose index starts with, or whose content contains, `argument`
+fn complete_tasks(app: &App, prefix: &str, argument: &str, completions: &mut Vec<Completion>) {
+    for (index, task_id) in app.display_container_state.visible_task_paths(&app.tasks) {
+        let Some(task) = app.tasks.iter().find(|t| t.id == task_id) else {
+            continue;
+        };
+        let index = index.to_string();
+        if index.starts_with(argument) || task.content.to_lowercase().contains(argument) {
+            completions.push(Completion {
+                text: format!("{prefix}{index}"),
+                label: format!("{index} {}", task.content),
+                detail: "task".to_string(),
+            });
+        }
+    }
+}
+
+/// Explains why input that would become a new task looks like a mistyped command.
+///
+/// Only call this for input that `parse_command` turns into `Command::Create`.
+pub fn near_miss(input: &str) -> Option<String> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let mut best: Option<(usize, String)> = None;
+
+    for spec in COMMANDS {
+        let keyword_len = spec.keyword.split(' ').count();
+        if words.len() < keyword_len {
+            continue;
+        }
+        let typed = words[..keyword_len].join(" ");
+        let rest = words[keyword_len..].join(" ");
+        let distance = edit_distance(&typed.to_lowercase(), spec.keyword);
+        if distance > allowed_typos(spec.keyword) {
+            continue;
+        }
+
+        // Keywords typed correctly are handled by the parser, whatever the case
+        if distance == 0 || !accepts_argument(spec.argument, &rest) {
+            continue;
+        }
+        let rest = match spec.argument {
+            Argument::Container => closest(&rest, CONTAINER_NAMES).unwrap_or(&rest).to_string(),
+            Argument::SortMode => closest(&rest, SortMode::NAMES).unwrap_or(&rest).to_string(),
+            _ => rest,
+        };
+        if best.as_ref().is_none_or(|(best_distance, _)| distance < *best_distance) {
+            let hint = format!("Did you mean '{}'?", format!("{} {rest}", spec.keyword).trim_end());
+            best = Some((distance, hint));
+        }
+    }
+
+    best.map(|(_, hint)| hint)
+}
+
+/// Returns true if `rest` looks like what a command with a mistyped keyword expects.
+///
+/// Tasks are only recognized by their index, so a new task like "Delete old
+/// emails" isn't mistaken for a command.
+fn accepts_argument(argument: Argument, rest: &str) -> bool {
+    match argument {
+        Argument::None => rest.is_empty(),
+        Argument::Container => closest(rest, CONTAINER_NAMES).is_some(),
+        Argument::Task | Argument::Session => is_task_index(rest),
+        Argument::TaskThenText => rest
+            .split_once(' ')
+            .is_some_and(|(target, _)| is_task_index(target)),
+        Argument::TagThenTasks | Argument::DateThenTasks | Argument::RuleThenTasks => rest
+            .split_once(' ')
+            .is_some_and(|(_, targets)| is_task_index(targets)),
+        Argument::SortMode => closest(rest, SortMode::NAMES).is_some(),
+    }
+}
+
+/// Number of typos tolerated in a keyword of this length
+fn allowed_typos(keyword: &str) -> usize {
+    match keyword.len() {
+        0..=3 => 0,
+        4..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// The candidate closest to `text`, if it is close enough
+pub(crate) fn closest<'a>(text: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let text = text.to_lowercase();
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&text, candidate), *candidate))
+        .filter(|(distance, candidate)| *distance <= allowed_typos(candidate))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taskstore::TaskBuilder;
+
+    fn setup_app() -> App {
+        let mut app = App {
+            tasks: vec![
+                TaskBuilder::new(1).content("Buy groceries").children(vec![2]).build(),
+                TaskBuilder::new(2).content("Milk").parent(1).build(),
+                TaskBuilder::new(3).content("Write report").build(),
+            ],
+            ..App::default()
+        };
+        app.display_container_state.update_display_order(&app.tasks);
+        app
+    }
+
+    fn texts(completions: &[Completion]) -> Vec<&str> {
+        completions.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_complete_keywords_and_arguments() {
+        let app = setup_app();
+
+        assert_eq!(texts(&complete(&app, "mo")), ["move to taskpad ", "move to backburner ", "move to shelved ", "move "]);
+        assert_eq!(texts(&complete(&app, "show ar")), ["show archived"]);
+        assert_eq!(texts(&complete(&app, "complete 1")), ["complete 1", "complete 1.1"]);
+        assert_eq!(texts(&complete(&app, "delete rep")), ["delete 2"]);
+        assert_eq!(texts(&complete(&app, "sessions")), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_near_misses() {
+        assert_eq!(near_miss("shw archivd").unwrap(), "Did you mean 'show archived'?");
+        assert_eq!(near_miss("delet 3").unwrap(), "Did you mean 'delete 3'?");
+        assert_eq!(near_miss("Compelte 2").unwrap(), "Did you mean 'complete 2'?");
+        assert_eq!(near_miss("subtsak 1 Milk").unwrap(), "Did you mean 'subtask 1 Milk'?");
+        assert_eq!(near_miss("logut").unwrap(), "Did you mean 'logout'?");
+
+        // Ordinary tasks that merely start like a command
+        assert_eq!(near_miss("Shower plants"), None);
+        assert_eq!(near_miss("shows to watch"), None);
+        assert_eq!(near_miss("Buy groceries"), None);
+        assert_eq!(near_miss("Delete old emails"), None);
+    }
+
+    #[test]
+    fn test_completion_state_wraps() {
+        let mut state = CompletionState::default();
+        state.update("mo");
+        state.select_previous(3);
+        assert_eq!(state.selected(3), 2);
+        state.select_next(3);
+        assert_eq!(state.selected(3), 0);
+
+        state.select_next(3);
+        state.update("move");
+        assert_eq!(state.selected(3), 0);
+    }
+}
@@ -8,6 +8,7 @@
 use config::{Config, ConfigError, File};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::OnceLock;
 
@@ -17,14 +18,30 @@ pub struct AppConfig {
     /// Configuration for the Genius API
     #[serde(default)]
     pub genius: GeniusConfig,
+
+    /// Configuration for the log file
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    /// Configuration for the input line history
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// Configuration for task behaviour
+    #[serde(default)]
+    pub tasks: TasksConfig,
 }
 
 /// Configuration for the Genius API
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GeniusConfig {
-    /// API key for the Genius Platform
+    /// Organization ID for the Genius Platform
     #[serde(default)]
-    pub api_key: Option<String>,
+    pub organization_id: Option<String>,
+
+    /// Command that prints the Genius API key, e.g. `pass show genius/api-key`
+    #[serde(default)]
+    pub api_key_command: Option<String>,
     
     /// Base URL for the Genius API
     #[serde(default = "default_genius_api_url")]
@@ -33,6 +50,97 @@ pub struct GeniusConfig {
     /// Timeout in seconds for API requests
     #[serde(default = "default_timeout_secs")]
     pub timeout_secs: u64,
+
+    /// Suggestion provider for the Genius Feed: "auto", "genius", "mock" or "local"
+    #[serde(default = "default_provider")]
+    pub provider: String,
+
+    /// Append pages to the feed as focus nears the bottom instead of paging
+    #[serde(default)]
+    pub infinite_scroll: bool,
+
+    /// What task context is sent along with Genius queries
+    #[serde(default)]
+    pub context: ContextConfig,
+
+    /// Containers whose tasks are never sent to external providers
+    #[serde(default)]
+    pub private_containers: Vec<String>,
+}
+
+/// Configuration for the task context sent with Genius queries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    /// Include the content of the focused task
+    pub focused_task: bool,
+    /// Include the parent chain of the focused task
+    pub ancestors: bool,
+    /// Include the tasks next to the focused task
+    pub siblings: bool,
+    /// Include the name of the active container
+    pub container: bool,
+    /// Maximum estimated number of tokens the context may use
+    pub token_budget: usize,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            focused_task: true,
+            ancestors: true,
+            siblings: true,
+            container: true,
+            token_budget: 256,
+        }
+    }
+}
+
+/// Configuration for the log file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Level for all modules: "error", "warn", "info", "debug" or "trace"
+    pub level: String,
+    /// Levels for single modules, e.g. `"genius_platform::genius_api" = "debug"`
+    pub modules: HashMap<String, String>,
+    /// Size in KiB at which the log file is rotated
+    pub max_file_size_kb: u64,
+    /// Number of rotated log files to keep
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            modules: HashMap::new(),
+            max_file_size_kb: 1024,
+            max_files: 3,
+        }
+    }
+}
+
+/// Configuration for the input line history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Number of entries remembered per mode
+    pub max_entries: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { max_entries: 500 }
+    }
+}
+
+/// Configuration for task behaviour
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TasksConfig {
+    /// Complete a parent task once all of its subtasks are done
+    pub auto_complete_parents: bool,
 }
 
 /// Default base URL for the Genius API
@@ -45,6 +153,11 @@ fn default_timeout_secs() -> u64 {
     10
 }
 
+/// Default suggestion provider
+fn default_provider() -> String {
+    "auto".to_string()
+}
+
 // Global configuration instance
 static CONFIG: OnceLock<AppConfig> = OnceLock::new();
 
@@ -58,9 +171,12 @@ pub fn get_config() -> &'static AppConfig {
         match load_config() {
             Ok(config) => config,
             Err(e) => {
-                eprintln!("Failed to load configuration: {}", e);
+                log::error!("Failed to load configuration: {}", e);
                 AppConfig {
                     genius: GeniusConfig::default(),
+                    logging: LoggingConfig::default(),
+                    history: HistoryConfig::default(),
+                    tasks: TasksConfig::default(),
                 }
             }
         }
@@ -78,7 +194,9 @@ fn load_config() -> Result<AppConfig, ConfigError> {
     let mut builder = Config::builder()
         // Start with default values
         .set_default("genius.base_url", default_genius_api_url())?
-        .set_default("genius.timeout_secs", default_timeout_secs())?;
+        .set_default("genius.timeout_secs", default_timeout_secs())?
+        .set_default("genius.provider", default_provider())?
+        .set_default("genius.infinite_scroll", false)?;
     
     // Try to load from system config directory
     if let Some(config_path) = get_system_config_path() {
@@ -100,14 +218,18 @@ fn load_config() -> Result<AppConfig, ConfigError> {
     Ok(app_config)
 }
 
+/// Get the system configuration directory
+pub fn config_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "cyberorganism", "cyberorganism")
+        .map(|proj_dirs| proj_dirs.config_dir().to_path_buf())
+}
+
 /// Get the path to the system configuration file
 fn get_system_config_path() -> Option<PathBuf> {
-    // Get the project directories
-    let proj_dirs = ProjectDirs::from("com", "cyberorganism", "cyberorganism")?;
+    let config_dir = config_dir()?;
     
     // Create the config directory if it doesn't exist
-    let config_dir = proj_dirs.config_dir();
-    std::fs::create_dir_all(config_dir).ok()?;
+    std::fs::create_dir_all(&config_dir).ok()?;
     
     // Return the path to the config file
     Some(config_dir.join("config.toml"))
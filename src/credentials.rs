@@ -0,0 +1,401 @@
+//! Secret handling for API credentials.
+//!
+//! The Genius API key is kept in an encrypted file in the configuration
+//! directory, unlocked with a passphrase (the key is derived with Argon2 and
+//! the file sealed with ChaCha20-Poly1305), or read from the output of a
+//! command such as `pass show genius/api-key`. Secrets are wrapped in
+//! [`Secret`], which never prints its value, and every secret in use is
+//! registered so `redact` can strip it from debug output.
+
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Name of the encrypted credentials file in the configuration directory
+pub const CREDENTIALS_FILE_NAME: &str = "credentials.enc";
+
+/// Replacement for secrets in debug output
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Version of the encrypted file format
+const FORMAT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// Secrets shorter than this are not redacted, they would match too much text
+const MIN_REDACTED_LEN: usize = 4;
+
+lazy_static! {
+    static ref BEARER_TOKEN: Regex = Regex::new(r"(?i)(bearer\s+)\S+").unwrap();
+}
+
+/// Secrets registered for redaction
+static KNOWN_SECRETS: Mutex<Vec<Secret>> = Mutex::new(Vec::new());
+
+/// A secret value that is never printed and is wiped from memory when dropped
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Wrap a secret value
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The secret value, for the places that have to send it
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if the secret is empty or whitespace
+    pub fn is_blank(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({REDACTED})")
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Credentials for the Genius API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// API key sent as a Bearer token
+    pub api_key: Secret,
+    /// Organization the feed belongs to
+    pub organization_id: String,
+}
+
+/// Errors while reading or writing credentials
+#[derive(Debug)]
+pub enum CredentialsError {
+    /// The credentials file could not be read or written
+    Io(io::Error),
+    /// The credentials file is not in the expected format
+    Format(String),
+    /// The passphrase does not unlock the credentials file
+    WrongPassphrase,
+    /// The API key command failed
+    Command(String),
+}
+
+impl fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Format(msg) => write!(f, "Invalid credentials file: {msg}"),
+            Self::WrongPassphrase => write!(f, "Wrong passphrase"),
+            Self::Command(msg) => write!(f, "API key command failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for CredentialsError {}
+
+impl From<io::Error> for CredentialsError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// On-disk layout of the encrypted credentials file
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Plaintext sealed inside the credentials file
+#[derive(Serialize, Deserialize)]
+struct StoredCredentials {
+    api_key: String,
+    organization_id: String,
+}
+
+impl Drop for StoredCredentials {
+    fn drop(&mut self) {
+        self.api_key.zeroize();
+    }
+}
+
+/// Default location of the credentials file: the configuration directory,
+/// or the current directory if there is none
+pub fn default_credentials_file() -> String {
+    crate::config::config_dir().map_or_else(
+        || CREDENTIALS_FILE_NAME.to_string(),
+        |dir| dir.join(CREDENTIALS_FILE_NAME).to_string_lossy().into_owned(),
+    )
+}
+
+/// Derive the file key from the passphrase
+fn derive_key(passphrase: &Secret, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, CredentialsError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.expose().as_bytes(), salt, key.as_mut())
+        .map_err(|e| CredentialsError::Format(e.to_string()))?;
+    Ok(key)
+}
+
+/// Encrypt the credentials with the passphrase and write them to `path`
+pub fn save_credentials(
+    path: &str,
+    credentials: &Credentials,
+    passphrase: &Secret,
+) -> Result<(), CredentialsError> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt)?;
+    let stored = StoredCredentials {
+        api_key: credentials.api_key.expose().to_string(),
+        organization_id: credentials.organization_id.clone(),
+    };
+    let plaintext = Zeroizing::new(
+        serde_json::to_vec(&stored).map_err(|e| CredentialsError::Format(e.to_string()))?,
+    );
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|e| CredentialsError::Format(e.to_string()))?;
+
+    let file = EncryptedFile {
+        version: FORMAT_VERSION,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| CredentialsError::Format(e.to_string()))?;
+
+    if let Some(parent) = Path::new(path).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    write_private_file(path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Write a file that only the current user can read
+///
+/// An existing file is restricted too before anything is written to it, as
+/// the mode given to `open` only applies to new files.
+fn write_private_file(path: &str, contents: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    let mut options = fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)
+}
+
+/// Read and decrypt the credentials file at `path`
+pub fn load_credentials(path: &str, passphrase: &Secret) -> Result<Credentials, CredentialsError> {
+    let json = fs::read_to_string(path)?;
+    let file: EncryptedFile =
+        serde_json::from_str(&json).map_err(|e| CredentialsError::Format(e.to_string()))?;
+    if file.version != FORMAT_VERSION {
+        return Err(CredentialsError::Format(format!("unsupported version {}", file.version)));
+    }
+
+    let decode = |field: &str| {
+        BASE64
+            .decode(field)
+            .map_err(|e| CredentialsError::Format(e.to_string()))
+    };
+    let salt = decode(&file.salt)?;
+    let nonce = decode(&file.nonce)?;
+    let ciphertext = decode(&file.ciphertext)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(CredentialsError::Format("invalid nonce".to_string()));
+    }
+
+    let key = derive_key(passphrase, &salt)?;
+    let plaintext = Zeroizing::new(
+        ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| CredentialsError::WrongPassphrase)?,
+    );
+    let mut stored: StoredCredentials = serde_json::from_slice(&plaintext)
+        .map_err(|e| CredentialsError::Format(e.to_string()))?;
+
+    Ok(Credentials {
+        api_key: Secret::new(std::mem::take(&mut stored.api_key)),
+        organization_id: std::mem::take(&mut stored.organization_id),
+    })
+}
+
+/// Delete the credentials file. Returns false if there was none.
+pub fn delete_credentials(path: &str) -> io::Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Read the API key from the first line of a command's output
+pub fn read_api_key_from_command(command: &str) -> Result<Secret, CredentialsError> {
+    #[cfg(windows)]
+    let output = Command::new("cmd").args(["/C", command]).output();
+    #[cfg(not(windows))]
+    let output = Command::new("sh").args(["-c", command]).output();
+
+    let output = output.map_err(|e| CredentialsError::Command(e.to_string()))?;
+    if !output.status.success() {
+        return Err(CredentialsError::Command(format!(
+            "exited with {}",
+            output.status
+        )));
+    }
+
+    let stdout = Zeroizing::new(output.stdout);
+    let key = String::from_utf8_lossy(&stdout)
+        .lines()
+        .next()
+        .map(|line| Secret::new(line.trim()))
+        .unwrap_or_default();
+    if key.is_blank() {
+        return Err(CredentialsError::Command("no output".to_string()));
+    }
+    Ok(key)
+}
+
+/// Remember a secret so `redact` removes it from debug output
+pub fn register_secret(secret: &Secret) {
+    if secret.expose().len() < MIN_REDACTED_LEN {
+        return;
+    }
+    if let Ok(mut secrets) = KNOWN_SECRETS.lock() {
+        if !secrets.contains(secret) {
+            secrets.push(secret.clone());
+        }
+    }
+}
+
+/// Forget all registered secrets, e.g. after logging out
+pub fn forget_secrets() {
+    if let Ok(mut secrets) = KNOWN_SECRETS.lock() {
+        secrets.clear();
+    }
+}
+
+/// Replace registered secrets and Bearer tokens in `text` with `[REDACTED]`
+pub fn redact(text: &str) -> String {
+    let mut redacted = BEARER_TOKEN
+        .replace_all(text, format!("${{1}}{REDACTED}"))
+        .into_owned();
+    if let Ok(secrets) = KNOWN_SECRETS.lock() {
+        for secret in secrets.iter() {
+            redacted = redacted.replace(secret.expose(), REDACTED);
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn test_credentials() -> Credentials {
+        Credentials {
+            api_key: Secret::new("sk-test-1234567890"),
+            organization_id: "org-42".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_credentials_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join(CREDENTIALS_FILE_NAME);
+        let path = path.to_str().unwrap();
+        let passphrase = Secret::new("correct horse");
+
+        save_credentials(path, &test_credentials(), &passphrase).unwrap();
+
+        let contents = fs::read_to_string(path).unwrap();
+        assert!(!contents.contains("sk-test-1234567890"));
+        assert!(!contents.contains("org-42"));
+        assert_eq!(load_credentials(path, &passphrase).unwrap(), test_credentials());
+
+        assert!(matches!(
+            load_credentials(path, &Secret::new("wrong")),
+            Err(CredentialsError::WrongPassphrase)
+        ));
+
+        assert!(delete_credentials(path).unwrap());
+        assert!(!delete_credentials(path).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_saving_restricts_existing_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(CREDENTIALS_FILE_NAME);
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let path = path.to_str().unwrap();
+
+        save_credentials(path, &test_credentials(), &Secret::new("correct horse")).unwrap();
+        assert_eq!(fs::metadata(path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_secrets_are_redacted() {
+        let credentials = test_credentials();
+        assert!(!format!("{credentials:?}").contains("sk-test"));
+
+        register_secret(&Secret::new("sk-redact-me-0987"));
+        let text = "key sk-redact-me-0987, header Authorization: Bearer abc.def";
+        assert_eq!(
+            redact(text),
+            "key [REDACTED], header Authorization: Bearer [REDACTED]"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_api_key_from_command() {
+        let key = read_api_key_from_command("printf 'sk-from-pass\\nsecond line\\n'").unwrap();
+        assert_eq!(key.expose(), "sk-from-pass");
+
+        assert!(read_api_key_from_command("exit 3").is_err());
+        assert!(read_api_key_from_command("true").is_err());
+    }
+}
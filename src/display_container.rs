@@ -56,7 +56,9 @@
 
 use std::fmt;
 
+use crate::sorting::{SortMode, SortModes};
 use crate::taskstore::Task;
+use crate::taskstore::operations;
 
 /// Manages the display state of tasks in the taskpad.
 /// Tasks are displayed as a numbered list (1. Task A, 2. Task B, etc.)
@@ -84,6 +86,19 @@ pub struct DisplayContainerState {
     pub active_container: crate::taskstore::TaskContainer,
     /// Set of task IDs that are folded (not showing their children)
     pub folded_tasks: std::collections::HashSet<u32>,
+    /// How top-level tasks are sorted in each container
+    pub sort_modes: SortModes,
+    /// Set of task IDs selected for task actions, besides the focused one
+    ///
+    /// Only visible tasks stay selected; `update_display_order` drops the rest.
+    pub selected_tasks: std::collections::HashSet<u32>,
+    /// Task where the current Shift+Up/Down selection started
+    selection_anchor: Option<u32>,
+    /// Task whose notes are open in the notes pane
+    pub notes_task: Option<u32>,
+    /// Task the view is zoomed into; its subtasks are shown as the top level
+    /// and numbered from 1
+    pub zoom: Option<u32>,
     /// Flag to indicate initial startup for focus management
     pub initial_startup: bool,
     /// Flag to request focus on the next frame
@@ -110,6 +125,11 @@ impl DisplayContainerState {
             input_cursor: 0,
             active_container: crate::taskstore::TaskContainer::Taskpad,
             folded_tasks: std::collections::HashSet::new(),
+            sort_modes: SortModes::new(),
+            selected_tasks: std::collections::HashSet::new(),
+            selection_anchor: None,
+            notes_task: None,
+            zoom: None,
             initial_startup: true,
             request_focus_next_frame: false,
             request_cursor_at_end: false,
@@ -125,26 +145,32 @@ impl DisplayContainerState {
     /// - Only top-level tasks are shown by default
     /// - Subtasks are shown only when their parent is expanded
     pub fn update_display_order(&mut self, tasks: &[Task]) {
+        // Zooming ends when the zoomed task is deleted
+        if self.zoom.is_some_and(|id| !tasks.iter().any(|t| t.id == id)) {
+            self.zoom = None;
+        }
+
         // First, collect all top-level tasks
         let mut display_ids = Vec::new();
-        for task in tasks
-            .iter()
-            .filter(|t| t.container == self.active_container)
-        {
-            // Only include top-level tasks
-            if task.parent_id.is_none() {
-                display_ids.push(task.id);
-
-                // If this task is expanded, add its children
-                if self.is_task_expanded(task.id) {
-                    // Add all children recursively
-                    self.add_children_recursively(task.id, tasks, &mut display_ids);
-                }
+        for task in self.top_level_tasks(tasks) {
+            display_ids.push(task.id);
+
+            // If this task is expanded, add its children
+            if self.is_task_expanded(task.id) {
+                // Add all children recursively
+                self.add_children_recursively(task.id, tasks, &mut display_ids);
             }
         }
 
         self.display_to_id = display_ids;
 
+        // Hidden, moved or deleted tasks leave the selection
+        let visible = &self.display_to_id;
+        self.selected_tasks.retain(|id| visible.contains(id));
+        if self.selection_anchor.is_some_and(|id| !visible.contains(&id)) {
+            self.selection_anchor = None;
+        }
+
         // Reset focus to 0 if it's beyond the new list length
         if let Some(current) = self.focused_index {
             if current > self.display_to_id.len() {
@@ -157,6 +183,42 @@ impl DisplayContainerState {
         self.update_input_for_focus(tasks);
     }
 
+    /// The top-level tasks of the view: the subtasks of the zoomed task, or
+    /// the top-level tasks of the active container sorted by its sort mode
+    pub fn top_level_tasks<'a>(&self, tasks: &'a [Task]) -> Vec<&'a Task> {
+        if let Some(root) = self.zoom.and_then(|id| tasks.iter().find(|t| t.id == id)) {
+            return root.child_ids.iter().filter_map(|&id| tasks.iter().find(|t| t.id == id)).collect();
+        }
+        let mode = self.sort_mode();
+        let mut top_level: Vec<&Task> = tasks
+            .iter()
+            .filter(|t| t.container == self.active_container && t.parent_id.is_none())
+            .collect();
+        top_level.sort_by(|a, b| mode.compare(a, b));
+        top_level
+    }
+
+    /// The sort mode of the active container
+    pub fn sort_mode(&self) -> SortMode {
+        self.sort_modes.get(self.active_container)
+    }
+
+    /// Whether a task belongs to the view: the active container, or the
+    /// tasks below the zoomed task while zoomed
+    pub fn in_view(&self, task: &Task, tasks: &[Task]) -> bool {
+        match self.zoom {
+            Some(root) => root != task.id && operations::is_ancestor(tasks, root, task.id),
+            None => task.container == self.active_container,
+        }
+    }
+
+    /// The zoomed task and its ancestors, from the top level down
+    pub fn zoom_path<'a>(&self, tasks: &'a [Task]) -> Vec<&'a Task> {
+        let mut path = self.zoom.map(|id| operations::ancestry(tasks, id)).unwrap_or_default();
+        path.reverse();
+        path
+    }
+
     /// Helper function to recursively add children of a task to the display order
     fn add_children_recursively(&self, parent_id: u32, tasks: &[Task], display_ids: &mut Vec<u32>) {
         if let Some(parent_task) = tasks.iter().find(|t| t.id == parent_id) {
@@ -180,10 +242,7 @@ impl DisplayContainerState {
         let path = display_path.path();
 
         // Get all visible top-level tasks
-        let visible_tasks: Vec<&Task> = tasks
-            .iter()
-            .filter(|t| t.container == self.active_container && t.parent_id.is_none())
-            .collect();
+        let visible_tasks = self.top_level_tasks(tasks);
 
         // Get the first task using the first index (1-based)
         let first_pos = path[0].checked_sub(1)?;
@@ -210,6 +269,37 @@ impl DisplayContainerState {
         Some(current_task.id)
     }
 
+    /// Lists the visible tasks with their hierarchical display index, in display order
+    pub fn visible_task_paths(&self, tasks: &[Task]) -> Vec<(TaskIndex, u32)> {
+        let mut paths = Vec::new();
+        for (position, task) in self.top_level_tasks(tasks).into_iter().enumerate() {
+            self.collect_visible_paths(task, vec![position + 1], tasks, &mut paths);
+        }
+        paths
+    }
+
+    /// Helper for `visible_task_paths` that adds a task and its expanded children
+    fn collect_visible_paths(
+        &self,
+        task: &Task,
+        path: Vec<usize>,
+        tasks: &[Task],
+        paths: &mut Vec<(TaskIndex, u32)>,
+    ) {
+        paths.push((TaskIndex { path: path.clone() }, task.id));
+        if self.is_task_expanded(task.id) {
+            let children = task
+                .child_ids
+                .iter()
+                .filter_map(|&id| tasks.iter().find(|t| t.id == id));
+            for (position, child) in children.enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(position + 1);
+                self.collect_visible_paths(child, child_path, tasks, paths);
+            }
+        }
+    }
+
     /// Gets the display index (1-based) for a task ID
     pub fn get_display_index(&self, task_id: u32) -> Option<usize> {
         self.display_to_id
@@ -248,6 +338,78 @@ impl DisplayContainerState {
         });
     }
 
+    /// Returns the ID of the focused task, or None if the input line is focused
+    pub fn focused_task_id(&self) -> Option<u32> {
+        match self.focused_index {
+            Some(idx) if idx > 0 => self.display_to_id.get(idx - 1).copied(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a task is selected
+    pub fn is_selected(&self, task_id: u32) -> bool {
+        self.selected_tasks.contains(&task_id)
+    }
+
+    /// Returns the selected task IDs in display order
+    pub fn selected_task_ids(&self) -> Vec<u32> {
+        self.display_to_id
+            .iter()
+            .copied()
+            .filter(|id| self.selected_tasks.contains(id))
+            .collect()
+    }
+
+    /// Selects or deselects a single task and makes it the anchor of the
+    /// next Shift+Up/Down selection
+    pub fn toggle_selection(&mut self, task_id: u32) {
+        if !self.selected_tasks.remove(&task_id) {
+            self.selected_tasks.insert(task_id);
+        }
+        self.selection_anchor = Some(task_id);
+    }
+
+    /// Extends the selection from the focused task to the one above
+    /// (`forward` false) or below, like Shift+Up/Down in a list.
+    ///
+    /// Going back towards the task where the selection started shrinks it
+    /// again. Returns the task to focus next, or `None` at either end of the
+    /// list since the input line can't be selected.
+    pub fn extend_selection(&mut self, forward: bool) -> Option<u32> {
+        let current = self.focused_task_id()?;
+        let position = self.display_to_id.iter().position(|&id| id == current)?;
+        let next_position = if forward {
+            position + 1
+        } else {
+            position.checked_sub(1)?
+        };
+        let next = *self.display_to_id.get(next_position)?;
+
+        let anchor = *self.selection_anchor.get_or_insert(current);
+        let anchor_position = self
+            .display_to_id
+            .iter()
+            .position(|&id| id == anchor)
+            .unwrap_or(position);
+        self.selected_tasks.insert(current);
+        let shrinking = if forward {
+            position < anchor_position
+        } else {
+            position > anchor_position
+        };
+        if shrinking {
+            self.selected_tasks.remove(&current);
+        }
+        self.selected_tasks.insert(next);
+        Some(next)
+    }
+
+    /// Clears the selection
+    pub fn clear_selection(&mut self) {
+        self.selected_tasks.clear();
+        self.selection_anchor = None;
+    }
+
     /// Clear the current focus
     pub fn clear_focus(&mut self) {
         self.focused_index = None;
@@ -301,7 +463,7 @@ impl DisplayContainerState {
             Some(0) => self.reset_input(),
             _ => {
                 if let Some(content) = self.get_focused_task_content(tasks) {
-                    self.set_input(&content);
+                    self.set_input(content);
                 } else {
                     // If focused task doesn't exist anymore, reset to 0
                     self.focused_index = Some(0);
@@ -358,14 +520,75 @@ impl DisplayContainerState {
     ///
     /// This ensures that the `display_to_id` list is always in sync with the folding state.
     pub fn toggle_task_expansion(&mut self, task_id: u32, tasks: &[Task]) {
-        if self.folded_tasks.contains(&task_id) {
-            self.folded_tasks.remove(&task_id);
-        } else {
-            self.folded_tasks.insert(task_id);
-        }
+        self.refold(tasks, |state| {
+            if !state.folded_tasks.remove(&task_id) {
+                state.folded_tasks.insert(task_id);
+            }
+        });
+    }
+
+    /// Changes the folding state and updates the display order.
+    ///
+    /// The focused task keeps the focus, or its nearest visible ancestor
+    /// when it gets hidden.
+    fn refold(&mut self, tasks: &[Task], change: impl FnOnce(&mut Self)) {
+        let focused = self.focused_task_id();
+        change(self);
 
         // Always update display order after changing folding state
         self.update_display_order(tasks);
+
+        let Some(focused) = focused else {
+            return;
+        };
+        let visible = operations::ancestry(tasks, focused)
+            .into_iter()
+            .find_map(|task| self.get_display_index(task.id).map(|index| (task.id, index)));
+        if let Some((task_id, display_index)) = visible {
+            if self.focused_index != Some(display_index) {
+                self.focus_task_and_update_input(Some(task_id), tasks);
+            }
+        }
+    }
+
+    /// The tasks of the view with their depth (1 for top-level tasks), folded or not
+    fn view_tasks_with_depth(&self, tasks: &[Task]) -> Vec<(u32, usize)> {
+        let mut found = Vec::new();
+        let mut stack: Vec<(u32, usize)> = self.top_level_tasks(tasks).iter().rev().map(|t| (t.id, 1)).collect();
+        while let Some((task_id, depth)) = stack.pop() {
+            // Bounded by the number of tasks in case the hierarchy is broken
+            if found.len() > tasks.len() {
+                break;
+            }
+            found.push((task_id, depth));
+            if let Some(task) = tasks.iter().find(|t| t.id == task_id) {
+                stack.extend(task.child_ids.iter().rev().map(|&id| (id, depth + 1)));
+            }
+        }
+        found
+    }
+
+    /// Expands or collapses a task and every task below it
+    pub fn set_subtree_expanded(&mut self, task_id: u32, expanded: bool, tasks: &[Task]) {
+        let subtree: Vec<u32> = tasks
+            .iter()
+            .filter(|t| operations::is_ancestor(tasks, task_id, t.id))
+            .map(|t| t.id)
+            .collect();
+        self.refold(tasks, |state| {
+            for id in subtree {
+                if expanded {
+                    state.folded_tasks.remove(&id);
+                } else {
+                    state.folded_tasks.insert(id);
+                }
+            }
+        });
+    }
+
+    /// Expands every task of the view
+    pub fn expand_all(&mut self, tasks: &[Task]) {
+        self.expand_to_depth(usize::MAX, tasks);
     }
 
     /// Check if a task is expanded
@@ -373,9 +596,24 @@ impl DisplayContainerState {
         !self.folded_tasks.contains(&task_id)
     }
 
-    /// Collapse all tasks
-    pub fn collapse_all(&mut self) {
-        self.folded_tasks = self.display_to_id.iter().copied().collect();
+    /// Collapse all tasks of the view, leaving only the top-level ones visible
+    pub fn collapse_all(&mut self, tasks: &[Task]) {
+        self.expand_to_depth(1, tasks);
+    }
+
+    /// Shows `depth` levels of tasks: tasks above that depth are expanded,
+    /// the others collapsed
+    pub fn expand_to_depth(&mut self, depth: usize, tasks: &[Task]) {
+        let view = self.view_tasks_with_depth(tasks);
+        self.refold(tasks, |state| {
+            for (task_id, task_depth) in view {
+                if task_depth < depth {
+                    state.folded_tasks.remove(&task_id);
+                } else {
+                    state.folded_tasks.insert(task_id);
+                }
+            }
+        });
     }
 
     /// Fold a specific task
@@ -452,7 +690,7 @@ impl TaskIndex {
 
         match path {
             Ok(path) if path.is_empty() => Err("Empty task index".to_string()),
-            Ok(path) if path.iter().any(|&x| x == 0) => {
+            Ok(path) if path.contains(&0) => {
                 Err("Task indices must be positive".to_string())
             }
             Ok(path) => Ok(Self { path }),
@@ -0,0 +1,160 @@
+//! Typed content of a Genius card.
+//!
+//! The API returns loosely structured card JSON. Instead of handing the raw
+//! value to the UI, it is parsed once into a `GeniusCard` with the fields the
+//! feed knows how to show. Card bodies are markdown and often end with an
+//! attribution line such as `- Ada Lovelace`, which is split off so it can be
+//! rendered separately.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Typed content of a feed item
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeniusCard {
+    /// Short heading of the card
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Main text of the card, in markdown
+    #[serde(default)]
+    pub body: String,
+    /// Who the content is attributed to, e.g. the author of a quote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Link to the original source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Category of the card, e.g. "quote" or "article"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Relevance of the card for the query, if the provider scores results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relevance: Option<f64>,
+}
+
+impl GeniusCard {
+    /// Create a card with just a body
+    pub fn from_body(body: impl Into<String>) -> Self {
+        Self {
+            body: body.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Parse a card from the JSON returned by the Genius API.
+    ///
+    /// Fields are looked up in the card's `product` first and then on the card
+    /// itself. A trailing attribution line in the body is split off when the
+    /// card has no explicit attribution.
+    pub fn from_json(card: &Value) -> Self {
+        let product = card.get("product");
+        let text = |keys: &[&str]| -> Option<String> {
+            keys.iter().find_map(|key| {
+                product
+                    .and_then(|p| p.get(key))
+                    .or_else(|| card.get(key))
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+            })
+        };
+
+        let raw_body = text(&["body", "text"]).unwrap_or_default();
+        let (body, trailing_attribution) = split_attribution(&raw_body);
+
+        Self {
+            title: text(&["title", "headline"]),
+            body,
+            attribution: text(&["attribution", "author"]).or(trailing_attribution),
+            source_url: text(&["source_url", "url"]).or_else(|| {
+                card.pointer("/source/url")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            }),
+            category: text(&["category", "type"]),
+            relevance: ["relevance", "score"].iter().find_map(|key| {
+                product
+                    .and_then(|p| p.get(key))
+                    .or_else(|| card.get(key))
+                    .and_then(Value::as_f64)
+            }),
+        }
+    }
+
+    /// One-line summary: the title, or the first line of the body
+    pub fn headline(&self) -> &str {
+        self.title
+            .as_deref()
+            .unwrap_or_else(|| self.body.lines().next().unwrap_or_default().trim())
+    }
+
+    /// Returns true if the card has more to show than its headline
+    pub fn has_details(&self) -> bool {
+        let body_is_headline = self.title.is_none() && !self.body.trim().contains('\n');
+        !body_is_headline || self.attribution.is_some() || self.source_url.is_some()
+    }
+}
+
+/// Split a trailing attribution line (`- Name` or `— Name`) off a body
+fn split_attribution(body: &str) -> (String, Option<String>) {
+    if let Some((text, last_line)) = body.trim_end().rsplit_once('\n') {
+        let last_line = last_line.trim();
+        for dash in ["—", "–", "-", "~"] {
+            if let Some(name) = last_line.strip_prefix(dash) {
+                // A second list item is part of the body, not an attribution
+                let previous = text.trim_end().lines().last().unwrap_or_default().trim();
+                if !name.trim().is_empty() && !previous.starts_with(dash) {
+                    return (text.trim_end().to_string(), Some(name.trim().to_string()));
+                }
+            }
+        }
+    }
+    (body.to_string(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_card_fields() {
+        let card = GeniusCard::from_json(&serde_json::json!({
+            "id": "card-1",
+            "category": "article",
+            "relevance": 0.8,
+            "product": {
+                "title": "Deep work",
+                "body": "Focus without distraction.",
+                "url": "https://example.com/deep-work"
+            }
+        }));
+
+        assert_eq!(card.title.as_deref(), Some("Deep work"));
+        assert_eq!(card.body, "Focus without distraction.");
+        assert_eq!(card.source_url.as_deref(), Some("https://example.com/deep-work"));
+        assert_eq!(card.category.as_deref(), Some("article"));
+        assert_eq!(card.relevance, Some(0.8));
+        assert_eq!(card.attribution, None);
+    }
+
+    #[test]
+    fn test_trailing_attribution_is_split_off() {
+        let card = GeniusCard::from_json(&serde_json::json!({
+            "product": { "body": "Simplicity is prerequisite for reliability.\n- Edsger W. Dijkstra" }
+        }));
+
+        assert_eq!(card.body, "Simplicity is prerequisite for reliability.");
+        assert_eq!(card.attribution.as_deref(), Some("Edsger W. Dijkstra"));
+        assert_eq!(card.headline(), "Simplicity is prerequisite for reliability.");
+    }
+
+    #[test]
+    fn test_markdown_list_is_not_an_attribution() {
+        let body = "Steps:\n- plan\n- do";
+        let card = GeniusCard::from_json(&serde_json::json!({ "product": { "body": body } }));
+
+        assert_eq!(card.body, body);
+        assert_eq!(card.attribution, None);
+    }
+}
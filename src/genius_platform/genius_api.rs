@@ -23,10 +23,11 @@
 //!    - Update error handling for any API-specific error responses
 //!
 //! 4. **Mock Data**:
-//!    - Update the `mock_query` method to return data that matches the structure
-//!      of the real API responses for testing purposes
+//!    - Update `MockProvider::mock_response` in `mock_provider.rs` to return data
+//!      that matches the structure of the real API responses for testing purposes
 //!
 //! The rest of the application interacts with this API through the `GeniusApiBridge`,
+//! which holds it as one of several `SuggestionProvider` implementations,
 //! so changes should be contained to this file and won't affect other parts of the
 //! application as long as the public interface remains consistent.
 
@@ -35,6 +36,16 @@ use std::error::Error;
 use std::time::Duration;
 use uuid::Uuid;
 
+use crate::credentials::{Secret, redact};
+use log::{debug, trace, warn};
+
+use super::card::GeniusCard;
+use super::query_context::QueryContext;
+use super::suggestion_provider::{ProviderCapabilities, SuggestionProvider, SuggestionRequest};
+
+/// Number of cards requested per page
+pub const BATCH_SIZE: usize = 10;
+
 /// Represents an item returned from the Genius API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeniusItem {
@@ -45,6 +56,24 @@ pub struct GeniusItem {
     /// Additional metadata as a JSON object
     #[serde(default)]
     pub metadata: serde_json::Value,
+    /// ID of the local task this item refers to, if it came from the task store
+    #[serde(default)]
+    pub task_id: Option<u32>,
+    /// Typed content of the item, shown by the feed
+    #[serde(default)]
+    pub card: GeniusCard,
+}
+
+impl GeniusItem {
+    /// The typed card of this item, falling back to the plain description
+    /// for items saved before cards existed
+    pub fn display_card(&self) -> std::borrow::Cow<'_, GeniusCard> {
+        if self.card.body.is_empty() && self.card.title.is_none() {
+            std::borrow::Cow::Owned(GeniusCard::from_body(self.description.trim()))
+        } else {
+            std::borrow::Cow::Borrowed(&self.card)
+        }
+    }
 }
 
 /// Response from the Genius API containing multiple items
@@ -54,6 +83,9 @@ pub struct GeniusResponse {
     pub items: Vec<GeniusItem>,
     /// Status of the response
     pub status: String,
+    /// Whether more results follow this page, if the provider knows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_more: Option<bool>,
 }
 
 /// Error types that can occur during API operations
@@ -85,7 +117,7 @@ impl Error for GeniusApiError {}
 /// Client for interacting with the Genius API
 pub struct GeniusApiClient {
     base_url: String,
-    api_key: Option<String>,
+    api_key: Option<Secret>,
     timeout: Duration,
     organization_id: String,
     session_id: String,
@@ -112,7 +144,7 @@ impl GeniusApiClient {
     ) -> Self {
         Self {
             base_url,
-            api_key,
+            api_key: api_key.map(Secret::new),
             timeout,
             organization_id,
             session_id: Uuid::new_v4().to_string(),
@@ -120,7 +152,7 @@ impl GeniusApiClient {
     }
 
     /// Set the API key
-    pub fn with_api_key(mut self, api_key: String) -> Self {
+    pub fn with_api_key(mut self, api_key: Secret) -> Self {
         self.api_key = Some(api_key);
         self
     }
@@ -141,163 +173,163 @@ impl GeniusApiClient {
         self.timeout
     }
 
-    /// Get the server URL for API requests
-    fn get_server_url(&self) -> String {
+    /// Get the server URL for API requests in the given feed session
+    fn get_server_url(&self, session_id: &str) -> String {
         format!("{}/hackathon/{}/feed/{}", 
             self.base_url, 
             self.organization_id,
-            self.session_id
+            session_id
         )
     }
 
+    /// Returns true if both an API key and an organization ID are set
+    pub fn is_configured(&self) -> bool {
+        self.api_key.as_ref().is_some_and(|k| !k.is_blank())
+            && !self.organization_id.is_empty()
+    }
+
     /// Query the API synchronously with a specific page number
     pub fn query_sync_with_page(&self, input: &str, page: usize) -> Result<GeniusResponse, GeniusApiError> {
-        // When mock-api feature is explicitly enabled, always use mock data
-        #[cfg(feature = "mock-api")]
-        {
-            println!("[DEBUG] Using mock data due to mock-api feature");
-            return Ok(self.mock_query(input));
-        }
+        self.query_sync_with_context(input, page, &QueryContext::new())
+    }
 
-        // In normal mode, try to use real API but fall back to mock if no API key or organization ID
-        #[cfg(not(feature = "mock-api"))]
-        {
-            // If no API key is provided or it's empty, or organization ID is empty, fall back to mock data
-            if self.api_key.is_none() || 
-               self.api_key.as_ref().map_or(true, |k| k.trim().is_empty()) ||
-               self.organization_id.is_empty() {
-                println!("[DEBUG] Using mock data due to missing API key or organization ID");
-                println!("[DEBUG] API key present: {}", self.api_key.is_some());
-                println!("[DEBUG] Organization ID: '{}'", self.organization_id);
-                return Ok(self.mock_query(input));
-            }
-            
-            // API key is available, proceed with real API request
-            let api_key = self.api_key.as_ref().unwrap();
-            let server_url = self.get_server_url();
-            
-            println!("[DEBUG] Sending API request to: {}", server_url);
-            println!("[DEBUG] Query text: '{}' (page {})", input, page);
-            
-            // Create the request client with timeout
-            let client = match reqwest::blocking::Client::builder()
-                .timeout(self.timeout)
-                .build() {
-                    Ok(client) => client,
-                    Err(e) => {
-                        println!("[DEBUG] Failed to build HTTP client: {}", e);
-                        return Err(GeniusApiError::NetworkError(e.to_string()));
-                    }
-                };
-            
-            // Prepare the request body based on the genius-hackathon-skeleton implementation
-            let request_body = serde_json::json!({
-                "search_prompt": input,
-                "page": page,
-                "batch_count": 10
-            });
-            
-            // Comment out detailed request body logging
-            // println!("[DEBUG] Request body: {}", serde_json::to_string_pretty(&request_body).unwrap_or_default());
-            
-            // Debug the full request details - commented out for reduced output
-            let auth_header = format!("Bearer {}", api_key);
-            // println!("[DEBUG] Full request details:");
-            // println!("[DEBUG] URL: {}", server_url);
-            // println!("[DEBUG] Authorization header: {}", auth_header);
-            // println!("[DEBUG] Content-Type: application/json");
-            
-            // Execute the request
-            let response = match client
-                .post(&server_url)
-                .header("Authorization", auth_header)
-                .header("Content-Type", "application/json")
-                .json(&request_body)
-                .send() {
-                    Ok(resp) => {
-                        println!("[DEBUG] Received response with status: {}", resp.status());
-                        
-                        // Comment out detailed response headers logging
-                        // println!("[DEBUG] Response headers:");
-                        // for (name, value) in resp.headers() {
-                        //     println!("[DEBUG]   {}: {}", name, value.to_str().unwrap_or("(invalid header value)"));
-                        // }
-                        
-                        resp
-                    },
-                    Err(e) => {
-                        println!("[DEBUG] Request failed: {}", e);
-                        return Err(GeniusApiError::NetworkError(e.to_string()));
-                    }
-                };
-            
-            // Check the response status
-            if !response.status().is_success() {
-                let error_msg = format!("API returned error status: {}", response.status());
-                println!("[DEBUG] {}", error_msg);
-                
-                // Try to get the response body for more error details
-                match response.text() {
-                    Ok(error_body) => {
-                        // Comment out detailed error body logging
-                        // println!("[DEBUG] Error response body: {}", error_body);
-                        return Err(GeniusApiError::ApiError(format!("{}: {}", error_msg, error_body)));
-                    },
-                    Err(_) => {
-                        return Err(GeniusApiError::ApiError(error_msg));
-                    }
-                }
-            }
-            
-            // Parse the response text first
-            let text = match response.text() {
-                Ok(text) => {
-                    // Comment out full response text logging
-                    // println!("[DEBUG] Response text: {}", text);
-                    text
-                },
+    /// Query the API synchronously with a specific page number and task context
+    ///
+    /// The context is sent as a structured `context` field next to the search prompt.
+    pub fn query_sync_with_context(&self, input: &str, page: usize, context: &QueryContext) -> Result<GeniusResponse, GeniusApiError> {
+        self.query_sync_in_session(input, page, context, &self.session_id)
+    }
+
+    /// Query the API synchronously in a specific feed session
+    ///
+    /// Without a session of its own, the client uses the one it generated at startup.
+    pub fn query_sync_in_session(&self, input: &str, page: usize, context: &QueryContext, session_id: &str) -> Result<GeniusResponse, GeniusApiError> {
+        // Without credentials there is nothing to query; the bridge selects
+        // a different provider when the API isn't configured
+        if !self.is_configured() {
+            debug!("Genius API queried without an API key or organization ID");
+            return Err(GeniusApiError::Other("Genius API is not configured".to_string()));
+        }
+        
+        // API key is available, proceed with real API request
+        let api_key = self.api_key.as_ref().unwrap();
+        let server_url = self.get_server_url(session_id);
+        
+        debug!("Sending API request to: {}", server_url);
+        debug!("Query text: '{}' (page {})", input, page);
+        
+        // Create the request client with timeout
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(self.timeout)
+            .build() {
+                Ok(client) => client,
                 Err(e) => {
-                    println!("[DEBUG] Failed to read response text: {}", e);
+                    warn!("Failed to build HTTP client: {}", e);
                     return Err(GeniusApiError::NetworkError(e.to_string()));
                 }
             };
-            
-            // Parse the JSON response
-            let payload: serde_json::Value = match serde_json::from_str(&text) {
-                Ok(payload) => payload,
+        
+        // Prepare the request body based on the genius-hackathon-skeleton implementation
+        let mut request_body = serde_json::json!({
+            "search_prompt": input,
+            "page": page,
+            "batch_count": BATCH_SIZE
+        });
+        if !context.is_empty() {
+            request_body["context"] = serde_json::json!(context);
+        }
+        
+        // The Authorization header is never logged
+        let auth_header = format!("Bearer {}", api_key.expose());
+        
+        // Execute the request
+        let response = match client
+            .post(&server_url)
+            .header("Authorization", auth_header)
+            .header("Content-Type", "application/json")
+            .json(&request_body)
+            .send() {
+                Ok(resp) => {
+                    debug!("Received response with status: {}", resp.status());
+                    
+                    for (name, value) in resp.headers() {
+                        trace!("Response header {}: {}", name, value.to_str().unwrap_or("(invalid header value)"));
+                    }
+                    
+                    resp
+                },
                 Err(e) => {
-                    println!("[DEBUG] Failed to parse JSON: {}", e);
-                    return Err(GeniusApiError::ParseError(e.to_string()));
-                }
-            };
-            
-            // Extract the cards from the response
-            let cards = match payload.get("cards") {
-                Some(cards) => cards,
-                None => {
-                    println!("[DEBUG] No 'cards' field in response");
-                    return Err(GeniusApiError::ParseError("No cards in response".to_string()));
+                    let error = redact(&e.to_string());
+                    warn!("Request failed: {}", error);
+                    return Err(GeniusApiError::NetworkError(error));
                 }
             };
+        
+        // Check the response status
+        if !response.status().is_success() {
+            let error_msg = format!("API returned error status: {}", response.status());
+            warn!("{}", error_msg);
             
-            // Convert the cards to GeniusItems
-            let items = match self.convert_cards_to_items(cards) {
-                Ok(items) => {
-                    println!("[DEBUG] Successfully converted {} cards to GeniusItems", items.len());
-                    items
+            // Try to get the response body for more error details
+            match response.text() {
+                Ok(error_body) => {
+                    debug!("Error response body: {}", error_body);
+                    return Err(GeniusApiError::ApiError(format!("{}: {}", error_msg, redact(&error_body))));
                 },
-                Err(e) => {
-                    println!("[DEBUG] Failed to convert cards to GeniusItems: {}", e);
-                    return Err(e);
+                Err(_) => {
+                    return Err(GeniusApiError::ApiError(error_msg));
                 }
-            };
-            
-            // Create and return the response
-            Ok(GeniusResponse {
-                items,
-                status: "success".to_string(),
-            })
+            }
         }
+        
+        // Parse the response text first
+        let text = match response.text() {
+            Ok(text) => {
+                trace!("Response text: {}", text);
+                text
+            },
+            Err(e) => {
+                warn!("Failed to read response text: {}", e);
+                return Err(GeniusApiError::NetworkError(e.to_string()));
+            }
+        };
+        
+        // Parse the JSON response
+        let payload: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to parse JSON: {}", e);
+                return Err(GeniusApiError::ParseError(e.to_string()));
+            }
+        };
+        
+        // Extract the cards from the response
+        let cards = match payload.get("cards") {
+            Some(cards) => cards,
+            None => {
+                warn!("No 'cards' field in response");
+                return Err(GeniusApiError::ParseError("No cards in response".to_string()));
+            }
+        };
+        
+        // Convert the cards to GeniusItems
+        let items = match self.convert_cards_to_items(cards) {
+            Ok(items) => {
+                debug!("Successfully converted {} cards to GeniusItems", items.len());
+                items
+            },
+            Err(e) => {
+                warn!("Failed to convert cards to GeniusItems: {}", e);
+                return Err(e);
+            }
+        };
+        
+        // Create and return the response
+        Ok(GeniusResponse {
+            items,
+            status: "success".to_string(),
+            has_more: payload.get("has_more").and_then(serde_json::Value::as_bool),
+        })
     }
 
     /// Query the API synchronously (page 1)
@@ -330,11 +362,9 @@ impl GeniusApiClient {
                 let item = GeniusItem {
                     id,
                     description,
-                    metadata: {
-                        let map = card.clone();
-                        
-                        map
-                    },
+                    metadata: card.clone(),
+                    task_id: None,
+                    card: GeniusCard::from_json(card),
                 };
                 
                 items.push(item);
@@ -348,143 +378,116 @@ impl GeniusApiClient {
         Ok(items)
     }
     
-    /// Create a mock response for testing and development
-    pub fn mock_query(&self, query: &str) -> GeniusResponse {
-        // Create dummy items with simple numeric IDs and static+dynamic descriptions
-        let mut items = Vec::new();
+    /// Query the API asynchronously
+    pub async fn query(&self, input: &str) -> Result<GeniusResponse, GeniusApiError> {
+        if !self.is_configured() {
+            return Err(GeniusApiError::Other("Genius API is not configured".to_string()));
+        }
         
-        // Static descriptions for each item
-        let static_descriptions = [
-            "Implement authentication system",
-            "Create database schema",
-            "Design user interface",
-            "Write documentation",
-            "Set up CI/CD pipeline",
-            "Optimize performance",
-            "Fix security vulnerabilities",
-            "Add analytics tracking",
-        ];
+        // API key is available, proceed with real API request
+        let api_key = self.api_key.as_ref().unwrap();
         
-        for i in 1..=8 {
-            let item = GeniusItem {
-                // Simple numeric IDs for easy debugging
-                id: i.to_string(),
-                // Combine static description with dynamic query information
-                description: format!("Item {}: {} (query: '{}')", i, static_descriptions[i-1], query),
-                metadata: serde_json::json!({}),
+        // Create the request client with timeout
+        let client = match reqwest::Client::builder()
+            .timeout(self.timeout)
+            .build() {
+                Ok(client) => client,
+                Err(e) => return Err(GeniusApiError::NetworkError(e.to_string())),
+            };
+        
+        // Prepare the request body based on the genius-hackathon-skeleton implementation
+        let request_body = serde_json::json!({
+            "search_prompt": input,
+            "page": 1,
+            "batch_count": BATCH_SIZE
+        });
+        
+        // Execute the request
+        let response = match client
+            .post(self.get_server_url(&self.session_id))
+            .header("Authorization", format!("Bearer {}", api_key.expose()))
+            .header("Content-Type", "application/json")
+            .json(&request_body)
+            .send()
+            .await {
+                Ok(resp) => resp,
+                Err(e) => return Err(GeniusApiError::NetworkError(redact(&e.to_string()))),
             };
-            items.push(item);
+        
+        // Check the response status
+        if !response.status().is_success() {
+            return Err(GeniusApiError::ApiError(
+                format!("API returned error status: {}", response.status())
+            ));
         }
         
-        // Create a mock response
-        GeniusResponse {
+        // Parse the response text first
+        let text = match response.text().await {
+            Ok(text) => text,
+            Err(e) => return Err(GeniusApiError::NetworkError(e.to_string())),
+        };
+        
+        // Parse the JSON response
+        let payload: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(payload) => payload,
+            Err(e) => return Err(GeniusApiError::ParseError(e.to_string())),
+        };
+        
+        // Extract the cards from the response
+        let cards = match payload.get("cards") {
+            Some(cards) => cards,
+            None => return Err(GeniusApiError::ParseError("No cards in response".to_string())),
+        };
+        
+        // Convert the cards to GeniusItems
+        let items = match self.convert_cards_to_items(cards) {
+            Ok(items) => items,
+            Err(e) => return Err(e),
+        };
+        
+        // Create and return the response
+        Ok(GeniusResponse {
             items,
             status: "success".to_string(),
-        }
+            has_more: payload.get("has_more").and_then(serde_json::Value::as_bool),
+        })
     }
+}
 
-    /// Query the API asynchronously
-    pub async fn query(&self, input: &str) -> Result<GeniusResponse, GeniusApiError> {
-        // When mock-api feature is explicitly enabled, always use mock data
-        #[cfg(feature = "mock-api")]
-        {
-            return Ok(self.mock_query(input));
-        }
+impl SuggestionProvider for GeniusApiClient {
+    fn name(&self) -> &'static str {
+        "genius"
+    }
 
-        // In normal mode, try to use real API but fall back to mock if no API key or organization ID
-        #[cfg(not(feature = "mock-api"))]
-        {
-            // If no API key is provided or it's empty, or organization ID is empty, fall back to mock data
-            if self.api_key.is_none() || 
-               self.api_key.as_ref().map_or(true, |k| k.trim().is_empty()) ||
-               self.organization_id.is_empty() {
-                return Ok(self.mock_query(input));
-            }
-            
-            // API key is available, proceed with real API request
-            let api_key = self.api_key.as_ref().unwrap();
-            
-            // Create the request client with timeout
-            let client = match reqwest::Client::builder()
-                .timeout(self.timeout)
-                .build() {
-                    Ok(client) => client,
-                    Err(e) => return Err(GeniusApiError::NetworkError(e.to_string())),
-                };
-            
-            // Prepare the request body based on the genius-hackathon-skeleton implementation
-            let request_body = serde_json::json!({
-                "search_prompt": input,
-                "page": 1,
-                "batch_count": 10
-            });
-            
-            // Execute the request
-            let response = match client
-                .post(&self.get_server_url())
-                .header("Authorization", format!("Bearer {}", api_key))
-                .header("Content-Type", "application/json")
-                .json(&request_body)
-                .send()
-                .await {
-                    Ok(resp) => resp,
-                    Err(e) => return Err(GeniusApiError::NetworkError(e.to_string())),
-                };
-            
-            // Check the response status
-            if !response.status().is_success() {
-                return Err(GeniusApiError::ApiError(
-                    format!("API returned error status: {}", response.status())
-                ));
-            }
-            
-            // Parse the response text first
-            let text = match response.text().await {
-                Ok(text) => text,
-                Err(e) => return Err(GeniusApiError::NetworkError(e.to_string())),
-            };
-            
-            // Parse the JSON response
-            let payload: serde_json::Value = match serde_json::from_str(&text) {
-                Ok(payload) => payload,
-                Err(e) => return Err(GeniusApiError::ParseError(e.to_string())),
-            };
-            
-            // Extract the cards from the response
-            let cards = match payload.get("cards") {
-                Some(cards) => cards,
-                None => return Err(GeniusApiError::ParseError("No cards in response".to_string())),
-            };
-            
-            // Convert the cards to GeniusItems
-            let items = match self.convert_cards_to_items(cards) {
-                Ok(items) => items,
-                Err(e) => return Err(e),
-            };
-            
-            // Create and return the response
-            Ok(GeniusResponse {
-                items,
-                status: "success".to_string(),
-            })
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            paginated: true,
+            page_size: BATCH_SIZE,
+            requires_network: true,
+            links_to_tasks: false,
         }
     }
+
+    fn query_page(
+        &self,
+        request: &SuggestionRequest<'_>,
+        page: usize,
+    ) -> Result<GeniusResponse, GeniusApiError> {
+        let session_id = request.session_id.unwrap_or(&self.session_id);
+        self.query_sync_in_session(request.input, page, request.context, session_id)
+    }
 }
 
 /// Module containing mock implementations for testing
 pub mod mock {
     use super::*;
 
-    /// Creates a mock API client that returns predefined responses
-    pub fn create_mock_client() -> GeniusApiClient {
-        GeniusApiClient::new()
-    }
-
     /// Creates a mock response with the given items
     pub fn create_mock_response(items: Vec<GeniusItem>) -> GeniusResponse {
         GeniusResponse {
             items,
             status: "success".to_string(),
+            has_more: None,
         }
     }
 }
@@ -3,7 +3,16 @@
 #![allow(unused_imports)]
 
 use super::genius_api::{GeniusApiClient, GeniusApiError, GeniusResponse, GeniusItem};
+use super::query_context::{QueryContext, build_query_context};
+use super::query_filter::PrivacyFilter;
+use super::result_pages::ResultPages;
+use super::suggestion_provider::{
+    ProviderCapabilities, ProviderKind, SuggestionProvider, SuggestionRequest, create_provider,
+};
 use crate::App;
+use crate::credentials::{Credentials, Secret, forget_secrets, register_secret};
+use crate::taskstore::Task;
+use log::{debug, warn};
 use serde_json;
 
 /// Bridge between the application UI and the Genius API
@@ -60,56 +69,112 @@ use serde_json;
 ///
 /// This bridge is the primary interface between the application and the Genius API.
 /// All API communication should go through this bridge to ensure proper isolation.
+/// The bridge does not talk to a concrete client; it delegates to whichever
+/// `SuggestionProvider` was selected by the `genius.provider` setting.
 /// 
 /// TODO: When the actual JSON schema is finalized, update:
 /// 1. This documentation with the correct request/response formats
 /// 2. The GeniusApiClient in genius_api.rs to match the schema
 /// 3. Ensure the bridge methods properly transform data between the app and API
 pub struct GeniusApiBridge {
-    /// The API client used to make requests
-    api_client: GeniusApiClient,
+    /// The provider used to answer queries
+    provider: Box<dyn SuggestionProvider>,
+    /// The provider kind selected in the configuration
+    provider_kind: ProviderKind,
     /// The most recent API response
     last_response: Option<GeniusResponse>,
     /// Flag indicating if a request is in progress
     request_in_progress: bool,
-    /// Current page number (1-based)
-    current_page: usize,
     /// Current query text
     current_query: String,
-    /// All items loaded so far (across all pages)
-    all_items: Vec<GeniusItem>,
+    /// Pages loaded so far for the current query
+    pages: ResultPages,
+    /// Task context sent with the current query
+    last_context: QueryContext,
+    /// Feed session the queries are sent in
+    session_id: Option<String>,
 }
 
 impl GeniusApiBridge {
-    /// Create a new API bridge with default settings
+    /// Create a new API bridge using the provider selected in the configuration
     pub fn new() -> Self {
+        let kind = ProviderKind::configured();
+        let mut bridge = Self::with_provider(create_provider(kind, None));
+        bridge.provider_kind = kind;
+        bridge
+    }
+
+    /// Create a new API bridge with a specific suggestion provider
+    pub fn with_provider(provider: Box<dyn SuggestionProvider>) -> Self {
         Self {
-            api_client: GeniusApiClient::new(),
+            provider,
+            provider_kind: ProviderKind::default(),
             last_response: None,
             request_in_progress: false,
-            current_page: 1,
             current_query: String::new(),
-            all_items: Vec::new(),
+            pages: ResultPages::new(),
+            last_context: QueryContext::new(),
+            session_id: None,
         }
     }
 
     /// Create a new API bridge with a custom API client
     pub fn with_client(api_client: GeniusApiClient) -> Self {
-        Self {
-            api_client,
-            last_response: None,
-            request_in_progress: false,
-            current_page: 1,
-            current_query: String::new(),
-            all_items: Vec::new(),
-        }
+        Self::with_provider(Box::new(api_client))
     }
 
     /// Configure the API client with the given API key and organization ID
+    ///
+    /// The Genius client only becomes the active provider if the configuration
+    /// selects it (or leaves the choice on `auto`).
     pub fn configure(&mut self, api_key: &str, organization_id: &str) {
-        self.api_client = GeniusApiClient::new()
-            .with_api_key(api_key.to_string())
-            .with_organization_id(organization_id.to_string());
+        self.configure_with_credentials(&Credentials {
+            api_key: Secret::new(api_key),
+            organization_id: organization_id.to_string(),
+        });
+    }
+
+    /// Configure the API client with credentials and register them for redaction
+    pub fn configure_with_credentials(&mut self, credentials: &Credentials) {
+        register_secret(&credentials.api_key);
+        register_secret(&Secret::new(credentials.organization_id.as_str()));
+        let client = GeniusApiClient::new()
+            .with_api_key(credentials.api_key.clone())
+            .with_organization_id(credentials.organization_id.clone());
+        self.provider = create_provider(self.provider_kind, Some(client));
+    }
+
+    /// Drop the API credentials and fall back to the configured provider
+    pub fn clear_credentials(&mut self) {
+        self.provider = create_provider(self.provider_kind, None);
+        forget_secrets();
+    }
+
+    /// Switch to another feed session and forget the results of the previous one
+    pub fn set_session(&mut self, session_id: &str) {
+        if self.session_id.as_deref() == Some(session_id) {
+            return;
+        }
+        self.session_id = Some(session_id.to_string());
+        self.last_response = None;
+        self.current_query.clear();
+        self.pages.clear();
+        self.last_context = QueryContext::new();
+    }
+
+    /// The feed session queries are sent in
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Name of the active suggestion provider
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    /// Capabilities of the active suggestion provider
+    pub fn capabilities(&self) -> ProviderCapabilities {
+        self.provider.capabilities()
     }
 
     /// Get the input query from the application state
@@ -126,52 +191,56 @@ impl GeniusApiBridge {
     /// and sends it to the API. It returns the API response or an error.
     pub fn query_with_app(&mut self, app: &App) -> Result<GeniusResponse, GeniusApiError> {
         let query = self.get_query_from_app(app);
-        self.execute_query(&query)
+        self.query_with_input(app, &query)
     }
 
     /// Query the API with specific input text
     /// 
-    /// This method takes a reference to the App (for potential future context)
-    /// and the input text to query. It returns the API response or an error.
-    pub fn query_with_input(&mut self, _app: &App, input: &str) -> Result<GeniusResponse, GeniusApiError> {
-        // If the query text has changed, reset pagination
-        if input != self.current_query {
-            self.current_page = 1;
-            self.current_query = input.to_string();
-            self.all_items.clear();
-        }
+    /// This method takes a reference to the App (whose task store is handed to
+    /// local providers) and the input text to query. It returns the API response or an error.
+    pub fn query_with_input(&mut self, app: &App, input: &str) -> Result<GeniusResponse, GeniusApiError> {
+        // Context comes from the task focused in PKM mode; later pages reuse it
+        self.last_context = build_query_context(
+            &app.tasks,
+            app.display_container_state.focused_task_id(),
+            app.display_container_state.active_container,
+            &crate::config::get_config().genius.context,
+            &PrivacyFilter::from_config(&app.tasks),
+        );
         
-        self.execute_query_with_page(input, self.current_page)
+        // A new query always starts over at the first page
+        self.execute_query_with_page(input, 1, &app.tasks)
     }
 
     /// Load the next page of results for the current query
-    pub fn load_next_page(&mut self) -> Result<GeniusResponse, GeniusApiError> {
-        println!("[DEBUG] GeniusApiBridge: load_next_page() called (current_page: {}, current_query: '{}')", 
-            self.current_page, self.current_query);
+    pub fn load_next_page(&mut self, app: &App) -> Result<GeniusResponse, GeniusApiError> {
+        debug!("load_next_page() called (loaded pages: {}, current_query: '{}')", 
+            self.pages.page_count(), self.current_query);
             
         if self.current_query.is_empty() {
-            println!("[DEBUG] GeniusApiBridge: load_next_page() failed - empty query");
+            debug!("load_next_page() failed - empty query");
             return Err(GeniusApiError::Other("No current query to load more results for".to_string()));
         }
         
-        // Increment the page number
-        self.current_page += 1;
-        println!("[DEBUG] GeniusApiBridge: Incrementing page to {}", self.current_page);
+        if !self.has_more_pages() {
+            debug!("load_next_page() skipped - end of results");
+            return Err(GeniusApiError::Other("No more results for the current query".to_string()));
+        }
         
         // Create local copies of the values we need
         let query = self.current_query.clone();
-        let page = self.current_page;
+        let page = self.pages.page_count() + 1;
         
         // Call execute_query_with_page with the local copies
-        let result = self.execute_query_with_page(&query, page);
+        let result = self.execute_query_with_page(&query, page, &app.tasks);
         
         // Log the result
         match &result {
             Ok(response) => {
-                println!("[DEBUG] GeniusApiBridge: load_next_page() succeeded - got {} items", response.items.len());
+                debug!("load_next_page() succeeded - got {} items", response.items.len());
             },
             Err(e) => {
-                println!("[DEBUG] GeniusApiBridge: load_next_page() failed - {}", e);
+                debug!("load_next_page() failed - {}", e);
             }
         }
         
@@ -182,35 +251,40 @@ impl GeniusApiBridge {
     /// 
     /// This is the core method that actually sends the query to the API
     /// and handles the response.
-    fn execute_query_with_page(&mut self, query: &str, page: usize) -> Result<GeniusResponse, GeniusApiError> {
+    fn execute_query_with_page(&mut self, query: &str, page: usize, tasks: &[Task]) -> Result<GeniusResponse, GeniusApiError> {
         // Mark that a request is in progress
         self.request_in_progress = true;
         
-        println!("[DEBUG] GeniusApiBridge: Executing query: '{}' (page {})", query, page);
+        debug!("Executing query: '{}' (page {}) via {} provider",
+            query, page, self.provider.name());
         
-        // Execute the query using the API client with the specified page
-        let result = self.api_client.query_sync_with_page(query, page);
+        // Execute the query using the active provider with the specified page
+        let request = SuggestionRequest::new(query, tasks)
+            .with_context(&self.last_context)
+            .with_session(self.session_id.as_deref());
+        let result = self.provider.query_page(&request, page);
         
         // Update the last response and request status
         match &result {
             Ok(response) => {
-                println!("[DEBUG] GeniusApiBridge: Query successful, received {} items", response.items.len());
+                debug!("Query successful, received {} items", response.items.len());
                 
                 // Store the response
                 self.last_response = Some(response.clone());
                 
-                // If this is page 1, clear the all_items list
+                // If this is page 1, forget the pages of the previous query
                 if page == 1 {
-                    self.all_items.clear();
+                    self.current_query = query.to_string();
+                    self.pages.clear();
                 }
                 
-                // Add the new items to the all_items list
-                self.all_items.extend(response.items.clone());
+                // Store the new page and check whether it was the last one
+                self.pages.push(response, self.provider.capabilities().page_size);
                 
                 self.request_in_progress = false;
             }
             Err(e) => {
-                println!("[DEBUG] GeniusApiBridge: Query failed: {}", e);
+                warn!("Query failed: {}", e);
                 self.request_in_progress = false;
             }
         }
@@ -221,8 +295,8 @@ impl GeniusApiBridge {
     /// Execute a query with the given input string (page 1)
     /// 
     /// This is a wrapper around execute_query_with_page for backward compatibility
-    fn execute_query(&mut self, query: &str) -> Result<GeniusResponse, GeniusApiError> {
-        self.execute_query_with_page(query, 1)
+    fn execute_query(&mut self, query: &str, tasks: &[Task]) -> Result<GeniusResponse, GeniusApiError> {
+        self.execute_query_with_page(query, 1, tasks)
     }
 
     /// Get the descriptions from the last API response
@@ -246,6 +320,8 @@ impl GeniusApiBridge {
     /// Set a test response directly (for unit testing)
     #[cfg(test)]
     pub fn set_test_response(&mut self, response: GeniusResponse) {
+        self.pages.clear();
+        self.pages.push(&response, self.provider.capabilities().page_size);
         self.last_response = Some(response);
         self.request_in_progress = false;
     }
@@ -262,25 +338,39 @@ impl GeniusApiBridge {
 
     /// Get all items loaded so far (across all pages)
     pub fn all_items(&self) -> &[GeniusItem] {
-        &self.all_items
+        self.pages.items()
+    }
+
+    /// Get the items of a loaded page (1-based)
+    pub fn page_items(&self, page: usize) -> Option<&[GeniusItem]> {
+        self.pages.page(page)
     }
 
-    /// Get the current page number
-    pub fn current_page(&self) -> usize {
-        self.current_page
+    /// Get the text of the current query
+    pub fn current_query(&self) -> &str {
+        &self.current_query
+    }
+
+    /// Get the task context sent with the current query
+    pub fn last_context(&self) -> &QueryContext {
+        &self.last_context
+    }
+
+    /// Get the number of pages loaded for the current query
+    pub fn loaded_page_count(&self) -> usize {
+        self.pages.page_count()
     }
 
     /// Check if there are more pages to load
     /// 
-    /// For now, we'll assume there are always more pages to load
-    /// In a real implementation, this would check if we've reached the end of the results
+    /// The end of the results is detected from the last loaded page, see
+    /// `ResultPages::push`.
     pub fn has_more_pages(&self) -> bool {
-        // In a real implementation, this would check if we've reached the end of the results
-        // For now, we'll assume there are always more pages to load if we have a current query
-        let has_more = !self.current_query.is_empty();
-        println!("[DEBUG] GeniusApiBridge: has_more_pages() = {} (current_query: '{}', current_page: {})", 
-            has_more, self.current_query, self.current_page);
-        has_more
+        // Called every frame by the feed header, so this doesn't log
+        self.provider.capabilities().paginated
+            && !self.current_query.is_empty()
+            && self.pages.page_count() > 0
+            && !self.pages.end_reached()
     }
 }
 
@@ -288,6 +378,7 @@ impl GeniusApiBridge {
 pub mod factory {
     use super::*;
     use std::env;
+    use log::{info, warn};
 
     /// Create a default API bridge
     pub fn create_default_bridge() -> GeniusApiBridge {
@@ -296,8 +387,7 @@ pub mod factory {
 
     /// Create a mock API bridge for testing
     pub fn create_mock_bridge() -> GeniusApiBridge {
-        let mock_client = super::super::genius_api::mock::create_mock_client();
-        GeniusApiBridge::with_client(mock_client)
+        GeniusApiBridge::with_provider(Box::new(super::super::mock_provider::MockProvider))
     }
 
     /// Create a configured API bridge with the given API key and organization ID
@@ -315,20 +405,20 @@ pub mod factory {
     /// # Returns
     /// 
     /// Returns a configured GeniusApiBridge if both environment variables
-    /// are found, otherwise returns a default bridge that will use mock data.
+    /// are found, otherwise returns a default bridge using the configured provider.
     pub fn create_from_env() -> GeniusApiBridge {
         let api_key = env::var("GENIUS_API_KEY").ok();
         let org_id = env::var("GENIUS_ORGANIZATION_ID").ok();
         
         match (api_key, org_id) {
             (Some(key), Some(org)) if !key.is_empty() && !org.is_empty() => {
-                println!("[INFO] Configuring Genius API with environment variables");
+                info!("Configuring Genius API with environment variables");
                 create_configured_bridge(&key, &org)
             },
             _ => {
-                println!("[WARN] Missing environment variables for Genius API");
-                println!("[WARN] Set GENIUS_API_KEY and GENIUS_ORGANIZATION_ID to use the real API");
-                println!("[WARN] Falling back to mock data");
+                warn!("Missing environment variables for Genius API");
+                warn!("Set GENIUS_API_KEY and GENIUS_ORGANIZATION_ID to use the real API");
+                warn!("Falling back to the configured suggestion provider");
                 create_default_bridge()
             }
         }
@@ -342,11 +432,11 @@ mod tests {
     #[test]
     fn test_genius_api_dummy_data() {
         // Create the API bridge
-        let mut api_bridge = GeniusApiBridge::new();
+        let mut api_bridge = factory::create_mock_bridge();
         
         // Access the execute_query method directly
         let test_input = "test query";
-        let result = api_bridge.execute_query(test_input);
+        let result = api_bridge.execute_query(test_input, &[]);
         
         // Verify the result
         assert!(result.is_ok(), "Query should succeed");
@@ -372,4 +462,54 @@ mod tests {
         // Check that request_in_progress is false
         assert!(!api_bridge.is_request_in_progress(), "request_in_progress should be false");
     }
+
+    #[test]
+    fn test_bridge_uses_local_provider() {
+        use crate::taskstore::TaskBuilder;
+
+        let tasks = vec![
+            TaskBuilder::new(1).content("Plan garden beds").build(),
+            TaskBuilder::new(2).content("Call dentist").build(),
+        ];
+        let mut api_bridge = GeniusApiBridge::with_provider(Box::new(
+            super::super::local_provider::LocalProvider::new(),
+        ));
+
+        let response = api_bridge.execute_query("garden", &tasks).unwrap();
+
+        assert_eq!(api_bridge.provider_name(), "local");
+        assert!(api_bridge.capabilities().links_to_tasks);
+        assert_eq!(response.items.len(), 1);
+        assert_eq!(response.items[0].description, "Plan garden beds");
+    }
+
+    #[test]
+    fn test_pagination_stops_at_end_of_results() {
+        use crate::taskstore::TaskBuilder;
+
+        let app = App {
+            tasks: (1..=12)
+                .map(|id| TaskBuilder::new(id).content(format!("Garden chore {id}")).build())
+                .collect(),
+            ..App::default()
+        };
+        let mut api_bridge = GeniusApiBridge::with_provider(Box::new(
+            super::super::local_provider::LocalProvider::new(),
+        ));
+
+        api_bridge.query_with_input(&app, "garden").unwrap();
+        assert_eq!(api_bridge.page_items(1).unwrap().len(), 10);
+        assert!(api_bridge.has_more_pages());
+
+        api_bridge.load_next_page(&app).unwrap();
+        assert_eq!(api_bridge.loaded_page_count(), 2);
+        assert_eq!(api_bridge.page_items(2).unwrap().len(), 2);
+        assert_eq!(api_bridge.all_items().len(), 12);
+        assert!(!api_bridge.has_more_pages());
+        assert!(api_bridge.load_next_page(&app).is_err());
+
+        // A new query starts over at the first page
+        api_bridge.query_with_input(&app, "chore 3").unwrap();
+        assert_eq!(api_bridge.loaded_page_count(), 1);
+    }
 }
@@ -7,8 +7,19 @@
 use eframe::egui;
 use crate::App;
 use crate::commands;
+use crate::commands::CaptureTarget;
 use crate::gui::genius_feed;
 use crate::genius_platform::GeniusApiBridge;
+use log::debug;
+
+/// Number of items from the bottom at which infinite scroll loads the next page
+const INFINITE_SCROLL_THRESHOLD: usize = 3;
+
+/// Records the query on the input line if it is the one the feed shows
+fn commit_query(app: &mut App, input_text: &str) {
+    let sent_query = GeniusApiBridge::global().current_query().to_string();
+    commands::commit_feed_query(app, input_text, &sent_query);
+}
 
 /// Handles keyboard input for Genius Feed mode
 pub struct GeniusKeyHandler {
@@ -16,6 +27,8 @@ pub struct GeniusKeyHandler {
     shift_pressed: bool,
     /// Whether control key is currently pressed
     ctrl_pressed: bool,
+    /// Whether alt key is currently pressed
+    alt_pressed: bool,
 }
 
 impl GeniusKeyHandler {
@@ -24,6 +37,7 @@ impl GeniusKeyHandler {
         Self {
             shift_pressed: false,
             ctrl_pressed: false,
+            alt_pressed: false,
         }
     }
     
@@ -33,6 +47,7 @@ impl GeniusKeyHandler {
         ctx.input(|i| {
             self.shift_pressed = i.modifiers.shift;
             self.ctrl_pressed = i.modifiers.ctrl;
+            self.alt_pressed = i.modifiers.alt;
         });
     }
     
@@ -54,6 +69,8 @@ impl GeniusKeyHandler {
         ctx.input(|i| {
             // Mode switching with Ctrl+Space
             if i.key_pressed(egui::Key::Space) && self.ctrl_pressed {
+                commit_query(app, input_text);
+                
                 // Toggle the app mode
                 let previous_mode = app.app_mode;
                 app.app_mode = commands::toggle_app_mode(app, app.app_mode);
@@ -81,17 +98,35 @@ impl GeniusKeyHandler {
                 handled = true;
             }
             
+            // Ctrl+I shows or hides the task context sent with the query
+            if i.key_pressed(egui::Key::I) && self.ctrl_pressed {
+                crate::gui::genius_feed::GeniusFeedState::toggle_context();
+                handled = true;
+            }
+            
+            // Ctrl+P switches between the query results and the pinboard
+            if i.key_pressed(egui::Key::P) && self.ctrl_pressed {
+                crate::gui::genius_feed::GeniusFeedState::toggle_pinboard();
+                handled = true;
+            }
+            
             // Get the number of items in the feed for navigation bounds
-            let total_items = GeniusApiBridge::global().all_items().len();
+            let total_items = crate::gui::genius_feed::GeniusFeedState::navigable_item_count(&app.pinboard);
             
             // Handle Shift+Down/Up for page navigation
             if i.key_pressed(egui::Key::ArrowDown) && self.shift_pressed {
                 let current_page = crate::gui::genius_feed::GeniusFeedState::get_current_page();
-                
-                // Load the next page
                 let mut api_bridge = crate::genius_platform::get_api_bridge();
-                if !api_bridge.is_request_in_progress() && api_bridge.has_more_pages() {
-                    let _ = api_bridge.load_next_page();
+                
+                // Show an already loaded page, or load the next one if there is more
+                let page_available = current_page < api_bridge.loaded_page_count()
+                    || (!api_bridge.is_request_in_progress()
+                        && api_bridge.has_more_pages()
+                        && api_bridge.load_next_page(app).is_ok()
+                        && current_page < api_bridge.loaded_page_count());
+                
+                // With infinite scroll the new page is simply appended
+                if page_available && !crate::gui::genius_feed::GeniusFeedState::is_infinite_scroll() {
                     crate::gui::genius_feed::GeniusFeedState::next_page();
                 }
                 handled = true;
@@ -106,6 +141,17 @@ impl GeniusKeyHandler {
             // Handle regular Up/Down for item navigation
             else if i.key_pressed(egui::Key::ArrowDown) && !self.ctrl_pressed {
                 crate::gui::genius_feed::GeniusFeedState::focus_next(total_items);
+                
+                // With infinite scroll, load the next page before focus reaches the bottom
+                if crate::gui::genius_feed::GeniusFeedState::is_infinite_scroll() {
+                    let focused_idx = crate::gui::genius_feed::GeniusFeedState::get_focused_index().unwrap_or(0);
+                    if focused_idx + INFINITE_SCROLL_THRESHOLD >= total_items {
+                        let mut api_bridge = crate::genius_platform::get_api_bridge();
+                        if !api_bridge.is_request_in_progress() && api_bridge.has_more_pages() {
+                            let _ = api_bridge.load_next_page(app);
+                        }
+                    }
+                }
                 handled = true;
             } else if i.key_pressed(egui::Key::ArrowUp) && !self.ctrl_pressed {
                 crate::gui::genius_feed::GeniusFeedState::focus_previous(total_items);
@@ -113,29 +159,30 @@ impl GeniusKeyHandler {
             }
             // Handle Ctrl+Up/Down for toggling expansion
             else if (i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::ArrowDown)) && self.ctrl_pressed {
-                println!("[DEBUG] GeniusKeyHandler: Ctrl+Arrow pressed, ctrl_pressed={}, shift_pressed={}", 
+                debug!("Ctrl+Arrow pressed, ctrl_pressed={}, shift_pressed={}", 
                     self.ctrl_pressed, self.shift_pressed);
                 
                 // Ctrl+Up/Down toggles expansion of the currently focused item
-                if let Some(focused_idx) = crate::gui::genius_feed::GeniusFeedState::get_focused_index() {
-                    println!("[DEBUG] GeniusKeyHandler: Focused index is {}, total_items={}", focused_idx, total_items);
-                    if focused_idx < total_items {
-                        println!("[DEBUG] GeniusKeyHandler: Toggling expansion for item {}", focused_idx);
-                        crate::gui::genius_feed::GeniusFeedState::toggle_item_expansion(focused_idx);
-                        handled = true;
-                    } else {
-                        println!("[DEBUG] GeniusKeyHandler: Focused index {} is out of bounds (total_items: {})", 
-                            focused_idx, total_items);
-                    }
+                if let Some(item) = crate::gui::genius_feed::GeniusFeedState::get_focused_item(&app.pinboard) {
+                    debug!("Toggling expansion for item {}", item.id);
+                    crate::gui::genius_feed::GeniusFeedState::toggle_item_expansion(&item.id);
+                    handled = true;
                 } else {
-                    println!("[DEBUG] GeniusKeyHandler: No focused index found");
+                    debug!("No focused item found");
                 }
             }
             
+            // Enter, with or without modifiers, commits the query
+            if i.key_pressed(egui::Key::Enter) {
+                commit_query(app, input_text);
+            }
+            
             // Ctrl+Enter key to toggle pinning the currently focused item
             if i.key_pressed(egui::Key::Enter) && self.ctrl_pressed {
-                if let Some(focused_item) = crate::gui::genius_feed::GeniusFeedState::get_focused_item() {
-                    crate::gui::genius_feed::GeniusFeedState::toggle_item_pinned(&focused_item.id);
+                if let Some(focused_item) = crate::gui::genius_feed::GeniusFeedState::get_focused_item(&app.pinboard) {
+                    // Pins remember the query that produced the item
+                    let query = GeniusApiBridge::global().current_query().to_string();
+                    commands::execute_toggle_pin_command(app, &focused_item, &query);
                     
                     // Request focus back to the input field for the next frame
                     app.display_container_state.request_focus_next_frame = true;
@@ -143,6 +190,44 @@ impl GeniusKeyHandler {
                     handled = true;
                 }
             }
+            
+            // Enter captures the focused item into the PKM:
+            // - Enter: jump to a linked local task, or create a new task from the item
+            // - Shift+Enter: add the item as a subtask of the task focused in PKM mode
+            // - Alt+Enter: append the item as a note to the task focused in PKM mode
+            if i.key_pressed(egui::Key::Enter) && !self.ctrl_pressed {
+                if let Some(item) = crate::gui::genius_feed::GeniusFeedState::get_focused_item(&app.pinboard) {
+                    let pkm_focus = app.display_container_state.focused_task_id();
+                    let target = if self.shift_pressed {
+                        pkm_focus.map(CaptureTarget::Subtask)
+                    } else if self.alt_pressed {
+                        pkm_focus.map(CaptureTarget::Note)
+                    } else {
+                        Some(CaptureTarget::Task)
+                    };
+                    
+                    match (target, item.task_id) {
+                        (Some(CaptureTarget::Task), Some(task_id)) => {
+                            if commands::execute_focus_by_id_command(app, task_id) {
+                                // Switch to PKM mode without logging so the focus message stays visible
+                                app.app_mode = crate::commands::AppMode::Pkm;
+                                *input_text = app.display_container_state.input_value().to_string();
+                            }
+                        }
+                        (Some(target), _) => {
+                            let (provider, query) = {
+                                let api_bridge = GeniusApiBridge::global();
+                                (api_bridge.provider_name(), api_bridge.current_query().to_string())
+                            };
+                            commands::execute_capture_item_command(app, &item, provider, &query, target);
+                        }
+                        (None, _) => {
+                            app.log_activity("Focus a task in PKM mode first".to_string());
+                        }
+                    }
+                    handled = true;
+                }
+            }
         });
         
         handled
@@ -0,0 +1,324 @@
+//! Local suggestion provider
+//!
+//! Suggests related tasks from the user's own task store, including archived
+//! ones. This provider works entirely offline, so the feed stays useful
+//! without an API key.
+//!
+//! # Ranking
+//!
+//! Each task is scored twice:
+//! - BM25 over the words in `Task.content`, which rewards tasks sharing rare
+//!   words with the input
+//! - `SkimMatcherV2` fuzzy matching, which still finds tasks while a word is
+//!   only partially typed or misspelled
+//!
+//! Both scores are normalized to the best score of the query and blended
+//! with `BM25_WEIGHT`.
+
+use std::collections::HashMap;
+
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+
+use super::card::GeniusCard;
+use super::genius_api::{GeniusApiError, GeniusItem, GeniusResponse};
+use super::suggestion_provider::{ProviderCapabilities, SuggestionProvider, SuggestionRequest};
+use crate::taskstore::{Task, TaskStatus};
+
+/// Number of suggestions returned per page
+const PAGE_SIZE: usize = 10;
+
+/// BM25 term frequency saturation parameter
+const BM25_K1: f64 = 1.2;
+
+/// BM25 document length normalization parameter
+const BM25_B: f64 = 0.75;
+
+/// Share of the blended score that comes from BM25 (the rest is fuzzy matching)
+const BM25_WEIGHT: f64 = 0.7;
+
+/// Provider that suggests related tasks from the task store
+pub struct LocalProvider {
+    matcher: SkimMatcherV2,
+}
+
+impl Default for LocalProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalProvider {
+    /// Create a new local provider
+    pub fn new() -> Self {
+        Self {
+            matcher: SkimMatcherV2::default().ignore_case(),
+        }
+    }
+
+    /// Rank all tasks against the query, best match first
+    pub fn rank<'a>(&self, query: &str, tasks: &'a [Task]) -> Vec<(f64, &'a Task)> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        // The task currently being typed is not a useful suggestion
+        let candidates: Vec<&Task> = tasks
+            .iter()
+            .filter(|task| !task.content.trim().is_empty() && task.content.trim() != query)
+            .collect();
+
+        let bm25 = Bm25Index::new(candidates.iter().map(|task| task.content.as_str()));
+        let query_terms = tokenize(query);
+
+        let bm25_scores: Vec<f64> = (0..candidates.len())
+            .map(|doc| bm25.score(doc, &query_terms))
+            .collect();
+        #[allow(clippy::cast_precision_loss)]
+        let fuzzy_scores: Vec<f64> = candidates
+            .iter()
+            .map(|task| {
+                self.matcher
+                    .fuzzy_match(&task.content, query)
+                    .map_or(0.0, |score| score as f64)
+            })
+            .collect();
+
+        let max_bm25 = bm25_scores.iter().copied().fold(0.0, f64::max);
+        let max_fuzzy = fuzzy_scores.iter().copied().fold(0.0, f64::max);
+
+        let mut ranked: Vec<(f64, &Task)> = candidates
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, task)| {
+                let bm25 = normalize(bm25_scores[i], max_bm25);
+                let fuzzy = normalize(fuzzy_scores[i], max_fuzzy);
+                let score = BM25_WEIGHT * bm25 + (1.0 - BM25_WEIGHT) * fuzzy;
+                (score > 0.0).then_some((score, task))
+            })
+            .collect();
+
+        // Best score first; prefer open tasks over archived ones on ties
+        ranked.sort_by(|a, b| {
+            b.0.total_cmp(&a.0)
+                .then((a.1.status == TaskStatus::Done).cmp(&(b.1.status == TaskStatus::Done)))
+                .then(a.1.id.cmp(&b.1.id))
+        });
+        ranked
+    }
+
+    /// Convert a task into a feed item that links back to it
+    fn task_to_item(task: &Task, score: f64) -> GeniusItem {
+        GeniusItem {
+            id: format!("task-{}", task.id),
+            description: task.content.clone(),
+            metadata: serde_json::json!({
+                "container": task.container.display_name(),
+                "relevance": score,
+            }),
+            task_id: Some(task.id),
+            card: GeniusCard {
+                body: task.content.clone(),
+                category: Some(task.container.display_name().to_string()),
+                relevance: Some(score),
+                ..GeniusCard::default()
+            },
+        }
+    }
+}
+
+impl SuggestionProvider for LocalProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            paginated: true,
+            page_size: PAGE_SIZE,
+            requires_network: false,
+            links_to_tasks: true,
+        }
+    }
+
+    fn query_page(
+        &self,
+        request: &SuggestionRequest<'_>,
+        page: usize,
+    ) -> Result<GeniusResponse, GeniusApiError> {
+        let ranked = self.rank(request.input, request.tasks);
+        let start = page.saturating_sub(1) * PAGE_SIZE;
+        let items = ranked
+            .iter()
+            .skip(start)
+            .take(PAGE_SIZE)
+            .map(|(score, task)| Self::task_to_item(task, *score))
+            .collect();
+
+        Ok(GeniusResponse {
+            items,
+            status: "success".to_string(),
+            has_more: Some(ranked.len() > start + PAGE_SIZE),
+        })
+    }
+}
+
+/// Scale a score into 0..=1 relative to the best score of the query
+fn normalize(score: f64, max: f64) -> f64 {
+    if max > 0.0 { score / max } else { 0.0 }
+}
+
+/// Split text into lowercase alphanumeric terms
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// A small in-memory BM25 index over a set of documents
+struct Bm25Index {
+    /// Term frequencies for each document
+    term_freqs: Vec<HashMap<String, usize>>,
+    /// Length of each document in terms
+    doc_lengths: Vec<usize>,
+    /// Number of documents containing each term
+    doc_freqs: HashMap<String, usize>,
+    /// Average document length in terms
+    avg_doc_length: f64,
+}
+
+impl Bm25Index {
+    /// Build an index over the given documents
+    #[allow(clippy::cast_precision_loss)]
+    fn new<'a>(documents: impl Iterator<Item = &'a str>) -> Self {
+        let mut term_freqs = Vec::new();
+        let mut doc_lengths = Vec::new();
+        let mut doc_freqs: HashMap<String, usize> = HashMap::new();
+
+        for document in documents {
+            let terms = tokenize(document);
+            let mut freqs: HashMap<String, usize> = HashMap::new();
+            for term in &terms {
+                *freqs.entry(term.clone()).or_default() += 1;
+            }
+            for term in freqs.keys() {
+                *doc_freqs.entry(term.clone()).or_default() += 1;
+            }
+            doc_lengths.push(terms.len());
+            term_freqs.push(freqs);
+        }
+
+        let avg_doc_length = if doc_lengths.is_empty() {
+            0.0
+        } else {
+            doc_lengths.iter().sum::<usize>() as f64 / doc_lengths.len() as f64
+        };
+
+        Self {
+            term_freqs,
+            doc_lengths,
+            doc_freqs,
+            avg_doc_length,
+        }
+    }
+
+    /// BM25 score of a document for the given query terms
+    #[allow(clippy::cast_precision_loss)]
+    fn score(&self, doc: usize, query_terms: &[String]) -> f64 {
+        let doc_count = self.term_freqs.len() as f64;
+        let length_ratio = if self.avg_doc_length > 0.0 {
+            self.doc_lengths[doc] as f64 / self.avg_doc_length
+        } else {
+            0.0
+        };
+
+        query_terms
+            .iter()
+            .filter_map(|term| {
+                let tf = *self.term_freqs[doc].get(term)? as f64;
+                let df = self.doc_freqs[term] as f64;
+                let idf = ((doc_count - df + 0.5) / (df + 0.5)).ln_1p();
+                Some(
+                    idf * tf * (BM25_K1 + 1.0)
+                        / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * length_ratio)),
+                )
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taskstore::{TaskBuilder, TaskContainer};
+
+    fn setup_tasks() -> Vec<Task> {
+        vec![
+            TaskBuilder::new(1).content("Buy groceries").build(),
+            TaskBuilder::new(2).content("Call dentist").build(),
+            TaskBuilder::new(3).content("Buy birthday present for Sam").build(),
+            TaskBuilder::new(4)
+                .content("Renew passport")
+                .container(TaskContainer::Archived)
+                .build(),
+        ]
+    }
+
+    #[test]
+    fn test_local_provider_suggests_matching_tasks() {
+        let tasks = setup_tasks();
+        let provider = LocalProvider::new();
+
+        let response = provider
+            .query(&SuggestionRequest::new("buy", &tasks))
+            .unwrap();
+
+        let ids: Vec<Option<u32>> = response.items.iter().map(|i| i.task_id).collect();
+        assert_eq!(response.items.len(), 2);
+        assert!(ids.contains(&Some(1)));
+        assert!(ids.contains(&Some(3)));
+        assert_eq!(response.items[0].metadata["container"], "taskpad");
+    }
+
+    #[test]
+    fn test_local_provider_includes_archived_tasks() {
+        let tasks = setup_tasks();
+        let provider = LocalProvider::new();
+
+        let ranked = provider.rank("passport photos", &tasks);
+
+        assert_eq!(ranked[0].1.id, 4);
+    }
+
+    #[test]
+    fn test_rare_terms_rank_higher() {
+        let tasks = vec![
+            TaskBuilder::new(1).content("Write weekly report").build(),
+            TaskBuilder::new(2).content("Write quarterly tax report").build(),
+            TaskBuilder::new(3).content("Write blog post").build(),
+        ];
+        let provider = LocalProvider::new();
+
+        let ranked = provider.rank("write tax", &tasks);
+
+        assert_eq!(ranked[0].1.id, 2);
+    }
+
+    #[test]
+    fn test_query_matching_task_is_skipped() {
+        let tasks = setup_tasks();
+        let provider = LocalProvider::new();
+
+        let ranked = provider.rank("Call dentist", &tasks);
+
+        assert!(ranked.iter().all(|(_, task)| task.id != 2));
+    }
+
+    #[test]
+    fn test_empty_query_has_no_suggestions() {
+        let tasks = setup_tasks();
+        assert!(LocalProvider::new().rank("   ", &tasks).is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+//! Mock suggestion provider
+//!
+//! Returns static placeholder items that echo the query. Useful for
+//! developing the feed UI without network access or credentials.
+
+use super::card::GeniusCard;
+use super::genius_api::{GeniusApiError, GeniusItem, GeniusResponse};
+use super::suggestion_provider::{ProviderCapabilities, SuggestionProvider, SuggestionRequest};
+
+/// Number of items in a full page; the mock data is shorter, so it is a single page
+const PAGE_SIZE: usize = 10;
+
+/// Provider that returns static placeholder data
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MockProvider;
+
+impl MockProvider {
+    /// Create a mock response for the given query
+    pub fn mock_response(query: &str) -> GeniusResponse {
+        // Static descriptions for each item
+        let static_descriptions = [
+            "Implement authentication system",
+            "Create database schema",
+            "Design user interface",
+            "Write documentation",
+            "Set up CI/CD pipeline",
+            "Optimize performance",
+            "Fix security vulnerabilities",
+            "Add analytics tracking",
+        ];
+
+        // Create dummy items with simple numeric IDs and static+dynamic descriptions
+        let items = static_descriptions
+            .iter()
+            .enumerate()
+            .map(|(i, description)| {
+                // Combine static description with dynamic query information
+                let description = format!("Item {}: {} (query: '{}')", i + 1, description, query);
+                GeniusItem {
+                    // Simple numeric IDs for easy debugging
+                    id: (i + 1).to_string(),
+                    card: GeniusCard::from_body(description.clone()),
+                    description,
+                    metadata: serde_json::json!({}),
+                    task_id: None,
+                }
+            })
+            .collect();
+
+        GeniusResponse {
+            items,
+            status: "success".to_string(),
+            has_more: None,
+        }
+    }
+}
+
+impl SuggestionProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            paginated: true,
+            page_size: PAGE_SIZE,
+            requires_network: false,
+            links_to_tasks: false,
+        }
+    }
+
+    fn query_page(
+        &self,
+        request: &SuggestionRequest<'_>,
+        _page: usize,
+    ) -> Result<GeniusResponse, GeniusApiError> {
+        Ok(Self::mock_response(request.input))
+    }
+}
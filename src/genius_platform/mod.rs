@@ -2,12 +2,23 @@
 //! 
 //! This module provides integration with the Genius Platform API,
 //! allowing the application to query the API and display results.
+//! Suggestions are produced by a pluggable `SuggestionProvider`, so the
+//! feed can also run against mock data or the local task store.
 
 #![allow(dead_code)]
 
+pub mod card;
 pub mod genius_api;
 pub mod genius_api_bridge;
 pub mod genius_keyhandler;
+pub mod local_provider;
+pub mod mock_provider;
+pub mod pinboard;
+pub mod query_context;
+pub mod query_filter;
+pub mod result_pages;
+pub mod sessions;
+pub mod suggestion_provider;
 
 // Re-export key types for convenience
 pub use genius_api::GeniusItem;
@@ -17,6 +28,8 @@ use std::sync::Mutex;
 use lazy_static::lazy_static;
 use std::env;
 use dotenv::dotenv;
+use crate::credentials::{Credentials, read_api_key_from_command};
+use log::{debug, warn};
 
 // Create a global instance of GeniusApiBridge
 // This allows us to have a single instance that's shared throughout the application
@@ -43,38 +56,74 @@ pub fn get_api_bridge() -> std::sync::MutexGuard<'static, GeniusApiBridge> {
 ///
 /// Returns true if the API was successfully configured, false otherwise.
 pub fn initialize_from_env() -> bool {
-    println!("[DEBUG] Initializing Genius API from environment variables");
+    debug!("Initializing Genius API from environment variables");
     
     // Load .env file if it exists
     match dotenv() {
-        Ok(_) => println!("[DEBUG] Loaded environment variables from .env file"),
-        Err(e) => println!("[DEBUG] Could not load .env file: {}", e),
+        Ok(_) => debug!("Loaded environment variables from .env file"),
+        Err(e) => debug!("Could not load .env file: {}", e),
     }
     
     let api_key = env::var("GENIUS_API_KEY").ok();
     let org_id = env::var("GENIUS_ORGANIZATION_ID").ok();
     
-    println!("[DEBUG] GENIUS_API_KEY present: {}", api_key.is_some());
-    println!("[DEBUG] GENIUS_ORGANIZATION_ID present: {}", org_id.is_some());
+    debug!("GENIUS_API_KEY present: {}", api_key.is_some());
+    debug!("GENIUS_ORGANIZATION_ID present: {}", org_id.is_some());
     
     if let (Some(api_key), Some(org_id)) = (api_key.clone(), org_id.clone()) {
         if api_key.trim().is_empty() || org_id.trim().is_empty() {
-            println!("[DEBUG] API key or organization ID is empty");
+            debug!("API key or organization ID is empty");
             return false;
         }
         
-        println!("[DEBUG] Configuring API bridge with API key and organization ID");
-        println!("[DEBUG] Organization ID: '{}'", org_id);
+        debug!("Configuring API bridge with API key and organization ID");
         
         let mut bridge = get_api_bridge();
         bridge.configure(&api_key, &org_id);
         true
     } else {
-        println!("[DEBUG] Missing environment variables for Genius API");
+        debug!("Missing environment variables for Genius API");
         false
     }
 }
 
+/// Initialize the Genius API with the key printed by `genius.api_key_command`
+///
+/// The organization ID comes from `genius.organization_id` or the
+/// GENIUS_ORGANIZATION_ID environment variable.
+///
+/// Returns true if the API was successfully configured, false otherwise.
+pub fn initialize_from_command() -> bool {
+    let config = &crate::config::get_config().genius;
+    let Some(command) = config.api_key_command.as_deref() else {
+        return false;
+    };
+
+    let org_id = config
+        .organization_id
+        .clone()
+        .or_else(|| env::var("GENIUS_ORGANIZATION_ID").ok())
+        .filter(|id| !id.trim().is_empty());
+    let Some(organization_id) = org_id else {
+        warn!("genius.api_key_command is set but no organization ID is configured");
+        return false;
+    };
+
+    match read_api_key_from_command(command) {
+        Ok(api_key) => {
+            get_api_bridge().configure_with_credentials(&Credentials {
+                api_key,
+                organization_id,
+            });
+            true
+        }
+        Err(e) => {
+            warn!("{}", e);
+            false
+        }
+    }
+}
+
 /// Initialize the Genius API with the provided credentials
 ///
 /// This function configures the API bridge with the given API key and organization ID.
@@ -0,0 +1,155 @@
+//! Durable pinned Genius items.
+//!
+//! Pins keep a full copy of the pinned `GeniusItem` together with the query
+//! that produced it, so they can be browsed in the pinboard view even when the
+//! API is offline or the item no longer appears in the results. Pins are saved
+//! next to the tasks file of the workspace.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::genius_api::GeniusItem;
+use crate::taskstore::{load_json, save_json};
+
+/// File name of the pinboard inside the workspace
+pub const PINS_FILE_NAME: &str = "pins.json";
+
+/// A Genius item pinned by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedItem {
+    /// Full copy of the pinned item
+    pub item: GeniusItem,
+    /// Query that produced the item
+    pub query: String,
+    /// When the item was pinned
+    pub pinned_at: DateTime<Utc>,
+}
+
+/// Collection of pinned items, newest first
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Pinboard {
+    items: Vec<PinnedItem>,
+}
+
+impl Pinboard {
+    /// Create an empty pinboard
+    pub const fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    /// All pinned items, newest first
+    pub fn items(&self) -> &[PinnedItem] {
+        &self.items
+    }
+
+    /// Number of pinned items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if nothing is pinned
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Check if an item with the given ID is pinned
+    pub fn is_pinned(&self, item_id: &str) -> bool {
+        self.items.iter().any(|pin| pin.item.id == item_id)
+    }
+
+    /// Pin an item, or unpin it if it is already pinned.
+    ///
+    /// Returns true if the item is pinned afterwards.
+    pub fn toggle(&mut self, item: &GeniusItem, query: &str) -> bool {
+        if let Some(index) = self.items.iter().position(|pin| pin.item.id == item.id) {
+            self.items.remove(index);
+            false
+        } else {
+            self.items.insert(
+                0,
+                PinnedItem {
+                    item: item.clone(),
+                    query: query.to_string(),
+                    pinned_at: Utc::now(),
+                },
+            );
+            true
+        }
+    }
+}
+
+/// Saves the pinboard to a JSON file.
+///
+/// ### Arguments
+/// * `pinboard` - The pinboard to save
+/// * `path` - Path to the pins storage file
+pub fn save_pins(pinboard: &Pinboard, path: &str) -> std::io::Result<()> {
+    save_json(pinboard, path)
+}
+
+/// Loads the pinboard from a JSON file.
+///
+/// ### Arguments
+/// * `path` - Path to the pins storage file
+pub fn load_pins(path: &str) -> std::io::Result<Pinboard> {
+    load_json(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn item(id: &str) -> GeniusItem {
+        GeniusItem {
+            id: id.to_string(),
+            description: format!("Item {id}"),
+            metadata: serde_json::json!({}),
+            task_id: None,
+            card: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_toggle_pin() {
+        let mut pinboard = Pinboard::new();
+
+        assert!(pinboard.toggle(&item("a"), "first query"));
+        assert!(pinboard.toggle(&item("b"), "second query"));
+        assert!(pinboard.is_pinned("a"));
+        assert_eq!(pinboard.items()[0].item.id, "b");
+        assert_eq!(pinboard.items()[1].query, "first query");
+
+        assert!(!pinboard.toggle(&item("a"), "ignored"));
+        assert!(!pinboard.is_pinned("a"));
+        assert_eq!(pinboard.len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load_pins() -> std::io::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(PINS_FILE_NAME);
+        let path = path.to_str().unwrap();
+
+        let mut pinboard = Pinboard::new();
+        pinboard.toggle(&item("a"), "query");
+        save_pins(&pinboard, path)?;
+
+        let loaded = load_pins(path)?;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.items()[0].item.description, "Item a");
+        assert_eq!(loaded.items()[0].query, "query");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_pins_nonexistent_file() -> std::io::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(PINS_FILE_NAME);
+
+        assert!(load_pins(path.to_str().unwrap())?.is_empty());
+
+        Ok(())
+    }
+}
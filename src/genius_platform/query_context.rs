@@ -0,0 +1,257 @@
+//! Task context for Genius queries.
+//!
+//! Besides the raw input, a query can carry context from the task store: the
+//! focused task, its parent chain, its siblings and the active container. The
+//! context is assembled according to `genius.context` in the configuration and
+//! kept within a token budget. When the budget runs out, lower priority context
+//! is dropped first, in this order of priority:
+//!
+//! 1. the focused task (truncated if it alone exceeds the budget)
+//! 2. the active container
+//! 3. the parent chain, nearest parent first
+//! 4. the siblings, in task order
+//!
+//! Private tasks are left out of the context entirely.
+
+use serde::Serialize;
+
+use super::query_filter::PrivacyFilter;
+use crate::config::ContextConfig;
+use crate::taskstore::operations::ancestry;
+use crate::taskstore::{Task, TaskContainer};
+
+/// Context sent along with a query, as structured fields
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct QueryContext {
+    /// Content of the focused task
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focused_task: Option<String>,
+    /// Content of the parents of the focused task, root first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ancestors: Vec<String>,
+    /// Content of the tasks sharing the focused task's parent
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub siblings: Vec<String>,
+    /// Name of the active container
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+}
+
+impl QueryContext {
+    /// Create an empty context
+    pub const fn new() -> Self {
+        Self {
+            focused_task: None,
+            ancestors: Vec::new(),
+            siblings: Vec::new(),
+            container: None,
+        }
+    }
+
+    /// Returns true if there is no context to send
+    pub fn is_empty(&self) -> bool {
+        self == &Self::new()
+    }
+
+    /// Rough number of tokens the context uses
+    pub fn estimated_tokens(&self) -> usize {
+        self.focused_task.iter()
+            .chain(&self.ancestors)
+            .chain(&self.siblings)
+            .chain(&self.container)
+            .map(|text| estimate_tokens(text))
+            .sum()
+    }
+}
+
+/// Rough token estimate: about four characters per token
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Build the context for a query from the task store.
+///
+/// ### Arguments
+/// * `tasks` - The task store
+/// * `focused_task_id` - The task focused in PKM mode, if any
+/// * `container` - The active container
+/// * `config` - Which context to include and the token budget
+/// * `privacy` - Decides which tasks must be left out
+pub fn build_query_context(
+    tasks: &[Task],
+    focused_task_id: Option<u32>,
+    container: TaskContainer,
+    config: &ContextConfig,
+    privacy: &PrivacyFilter<'_>,
+) -> QueryContext {
+    let mut context = QueryContext::new();
+    let mut budget = config.token_budget;
+
+    // Take text from the budget, returning None if it doesn't fit
+    let mut take = |text: &str| -> Option<String> {
+        let tokens = estimate_tokens(text);
+        (tokens <= budget).then(|| {
+            budget -= tokens;
+            text.to_string()
+        })
+    };
+
+    let focused = focused_task_id.and_then(|id| tasks.iter().find(|t| t.id == id));
+
+    if config.focused_task {
+        if let Some(task) = focused.filter(|task| !privacy.is_private(task)) {
+            // The focused task is the most important context, so it is truncated rather than dropped
+            let content: String = task.content.chars().take(config.token_budget * 4).collect();
+            context.focused_task = take(&content);
+        }
+    }
+
+    if config.container {
+        context.container = take(container.display_name());
+    }
+
+    if config.ancestors {
+        if let Some(task) = focused {
+            for parent in ancestry(tasks, task.id).into_iter().skip(1) {
+                if privacy.is_private(parent) {
+                    break;
+                }
+                match take(&parent.content) {
+                    Some(content) => context.ancestors.push(content),
+                    None => break,
+                }
+            }
+            context.ancestors.reverse();
+        }
+    }
+
+    if config.siblings {
+        if let Some(task) = focused {
+            let siblings = tasks.iter().filter(|t| {
+                t.id != task.id
+                    && t.parent_id == task.parent_id
+                    && (task.parent_id.is_some() || t.container == task.container)
+                    && !privacy.is_private(t)
+            });
+            for sibling in siblings {
+                match take(&sibling.content) {
+                    Some(content) => context.siblings.push(content),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taskstore::TaskBuilder;
+
+    fn setup_tasks() -> Vec<Task> {
+        vec![
+            TaskBuilder::new(1).content("Plan trip").children(vec![2]).build(),
+            TaskBuilder::new(2).content("Book hotel").parent(1).children(vec![3, 4]).build(),
+            TaskBuilder::new(3).content("Compare prices").parent(2).build(),
+            TaskBuilder::new(4).content("Check reviews").parent(2).build(),
+            TaskBuilder::new(5).content("Unrelated").build(),
+        ]
+    }
+
+    #[test]
+    fn test_build_full_context() {
+        let tasks = setup_tasks();
+        let context = build_query_context(
+            &tasks,
+            Some(3),
+            TaskContainer::Taskpad,
+            &ContextConfig::default(),
+            &PrivacyFilter::new(&tasks, Vec::new()),
+        );
+
+        assert_eq!(context.focused_task.as_deref(), Some("Compare prices"));
+        assert_eq!(context.ancestors, vec!["Plan trip", "Book hotel"]);
+        assert_eq!(context.siblings, vec!["Check reviews"]);
+        assert_eq!(context.container.as_deref(), Some("taskpad"));
+    }
+
+    #[test]
+    fn test_context_respects_config() {
+        let tasks = setup_tasks();
+        let config = ContextConfig {
+            ancestors: false,
+            siblings: false,
+            container: false,
+            ..ContextConfig::default()
+        };
+
+        let context = build_query_context(&tasks, Some(3), TaskContainer::Taskpad, &config, &PrivacyFilter::new(&tasks, Vec::new()));
+
+        assert_eq!(context.focused_task.as_deref(), Some("Compare prices"));
+        assert!(context.ancestors.is_empty());
+        assert!(context.siblings.is_empty());
+        assert!(context.container.is_none());
+        assert!(build_query_context(&tasks, None, TaskContainer::Taskpad, &config, &PrivacyFilter::new(&tasks, Vec::new())).is_empty());
+    }
+
+    #[test]
+    fn test_token_budget_drops_low_priority_context() {
+        let tasks = setup_tasks();
+        // "Compare prices" (4) + "taskpad" (2) + "Book hotel" (3) = 9 tokens
+        let config = ContextConfig {
+            token_budget: 10,
+            ..ContextConfig::default()
+        };
+
+        let context = build_query_context(&tasks, Some(3), TaskContainer::Taskpad, &config, &PrivacyFilter::new(&tasks, Vec::new()));
+
+        assert_eq!(context.ancestors, vec!["Book hotel"]);
+        assert!(context.siblings.is_empty());
+        assert!(context.estimated_tokens() <= 10);
+    }
+
+    #[test]
+    fn test_long_focused_task_is_truncated() {
+        let tasks = vec![TaskBuilder::new(1).content("x".repeat(100)).build()];
+        let config = ContextConfig {
+            token_budget: 5,
+            ..ContextConfig::default()
+        };
+
+        let context = build_query_context(&tasks, Some(1), TaskContainer::Taskpad, &config, &PrivacyFilter::new(&tasks, Vec::new()));
+
+        assert_eq!(context.focused_task.map(|t| t.len()), Some(20));
+        assert!(context.container.is_none());
+    }
+
+    #[test]
+    fn test_private_tasks_are_left_out() {
+        let mut tasks = setup_tasks();
+        tasks[3].private = true;
+        let privacy = PrivacyFilter::new(&tasks, Vec::new());
+
+        let context = build_query_context(
+            &tasks,
+            Some(3),
+            TaskContainer::Taskpad,
+            &ContextConfig::default(),
+            &privacy,
+        );
+        assert!(context.siblings.is_empty());
+
+        // Subtasks of a private task are private as well
+        tasks[0].private = true;
+        let privacy = PrivacyFilter::new(&tasks, Vec::new());
+        let context = build_query_context(
+            &tasks,
+            Some(3),
+            TaskContainer::Taskpad,
+            &ContextConfig::default(),
+            &privacy,
+        );
+        assert!(context.focused_task.is_none());
+        assert!(context.ancestors.is_empty());
+    }
+}
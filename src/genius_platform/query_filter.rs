@@ -0,0 +1,200 @@
+//! Decides which input is sent to suggestion providers.
+//!
+//! The input box holds commands and task edits as well as queries. Before the
+//! feed queries a provider, the input is classified with the same grammar as
+//! `parse_command`, and only real queries are sent. Content of private tasks,
+//! and of tasks in containers listed in `genius.private_containers`, is never
+//! sent to providers that need network access.
+
+use crate::App;
+use crate::command_parser::{ParseError, ParseErrorKind};
+use crate::commands::{AppMode, Command, parse_command};
+use crate::taskstore::operations::ancestry;
+use crate::taskstore::{Task, TaskContainer};
+
+/// What the text in the input box is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    /// A query worth sending to the provider
+    Query,
+    /// A command such as `delete 3`
+    Command,
+    /// The focused task being edited in PKM mode
+    TaskEdit,
+    /// Content of a private task
+    Private,
+}
+
+impl InputKind {
+    /// Returns true if the input should be sent to the provider
+    pub const fn is_query(self) -> bool {
+        matches!(self, Self::Query)
+    }
+}
+
+/// Decides which tasks are private
+#[derive(Debug, Clone)]
+pub struct PrivacyFilter<'a> {
+    tasks: &'a [Task],
+    private_containers: Vec<TaskContainer>,
+}
+
+impl<'a> PrivacyFilter<'a> {
+    /// Create a filter with an explicit list of private containers
+    pub const fn new(tasks: &'a [Task], private_containers: Vec<TaskContainer>) -> Self {
+        Self {
+            tasks,
+            private_containers,
+        }
+    }
+
+    /// Create a filter with the private containers from the configuration
+    pub fn from_config(tasks: &'a [Task]) -> Self {
+        let private_containers = crate::config::get_config()
+            .genius
+            .private_containers
+            .iter()
+            .filter_map(|name| {
+                let container = TaskContainer::from_name(name);
+                if container.is_none() {
+                    log::warn!("Unknown private container '{name}' in configuration");
+                }
+                container
+            })
+            .collect();
+        Self::new(tasks, private_containers)
+    }
+
+    /// Returns true if the task, one of its parents or its container is private
+    pub fn is_private(&self, task: &Task) -> bool {
+        let parents = ancestry(self.tasks, task.id).into_iter().skip(1);
+        std::iter::once(task)
+            .chain(parents)
+            .any(|task| task.private || self.private_containers.contains(&task.container))
+    }
+}
+
+/// Classify the input before it is sent to a provider.
+///
+/// ### Arguments
+/// * `app` - The application state
+/// * `input` - The text in the input box
+/// * `external` - Whether the provider needs network access, which makes privacy apply
+pub fn classify_input(app: &App, input: &str, external: bool) -> InputKind {
+    let input = input.trim();
+
+    // Incomplete commands are commands too, but input that only starts like
+    // `show`, such as "show me focus tips", is still a query
+    match parse_command(input.to_string()) {
+        Command::Create(_) => {}
+        Command::Invalid(ParseError { kind: ParseErrorKind::UnknownContainer { .. }, .. })
+            if input.split_whitespace().next().is_some_and(|word| word.eq_ignore_ascii_case("show")) => {}
+        _ => return InputKind::Command,
+    }
+
+    // In PKM mode the input of a focused task is its content; changing it is an edit
+    let focused_task = app
+        .display_container_state
+        .focused_task_id()
+        .and_then(|id| app.tasks.iter().find(|t| t.id == id));
+    if app.app_mode == AppMode::Pkm {
+        if let Some(task) = focused_task {
+            if task.content.trim() != input {
+                return InputKind::TaskEdit;
+            }
+        }
+    }
+
+    if external {
+        let privacy = PrivacyFilter::from_config(&app.tasks);
+        let matches_private_task = app
+            .tasks
+            .iter()
+            .any(|task| task.content.trim() == input && privacy.is_private(task));
+        if matches_private_task {
+            return InputKind::Private;
+        }
+    }
+
+    InputKind::Query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taskstore::TaskBuilder;
+
+    fn setup_app() -> App {
+        let mut app = App {
+            tasks: vec![
+                TaskBuilder::new(1).content("Plan trip").build(),
+                TaskBuilder::new(2).content("Salary talk").private().children(vec![3]).build(),
+                TaskBuilder::new(3).content("Gather numbers").parent(2).build(),
+                TaskBuilder::new(4)
+                    .content("Old idea")
+                    .container(TaskContainer::Shelved)
+                    .build(),
+            ],
+            ..App::default()
+        };
+        app.display_container_state.update_display_order(&app.tasks);
+        app
+    }
+
+    #[test]
+    fn test_commands_are_not_queries() {
+        let app = setup_app();
+
+        assert_eq!(classify_input(&app, "delete 3", false), InputKind::Command);
+        assert_eq!(classify_input(&app, "move to shelved 1.2", false), InputKind::Command);
+        assert_eq!(classify_input(&app, "show archived", false), InputKind::Command);
+        assert_eq!(classify_input(&app, "show me travel ideas", false), InputKind::Query);
+
+        // Commands that are still being typed aren't queries either
+        assert_eq!(classify_input(&app, "delete ", false), InputKind::Command);
+        assert_eq!(classify_input(&app, "complete \"Buy", false), InputKind::Command);
+        assert_eq!(classify_input(&app, "due tomorrow 3", false), InputKind::Command);
+    }
+
+    #[test]
+    fn test_task_edit_is_not_a_query() {
+        let mut app = setup_app();
+        app.display_container_state
+            .focus_task_and_update_input(Some(1), &app.tasks);
+
+        assert_eq!(classify_input(&app, "Plan trip", false), InputKind::Query);
+        assert_eq!(classify_input(&app, "Plan trip to", false), InputKind::TaskEdit);
+
+        app.app_mode = AppMode::Feed;
+        assert_eq!(classify_input(&app, "Plan trip to", false), InputKind::Query);
+    }
+
+    #[test]
+    fn test_private_content_is_not_sent_externally() {
+        let app = setup_app();
+
+        assert_eq!(classify_input(&app, "Gather numbers", true), InputKind::Private);
+        assert_eq!(classify_input(&app, "Gather numbers", false), InputKind::Query);
+    }
+
+    #[test]
+    fn test_private_containers() {
+        let app = setup_app();
+        let privacy = PrivacyFilter::new(&app.tasks, vec![TaskContainer::Shelved]);
+
+        assert!(privacy.is_private(&app.tasks[3]));
+        assert!(privacy.is_private(&app.tasks[2]));
+        assert!(!privacy.is_private(&app.tasks[0]));
+    }
+
+    #[test]
+    fn test_parent_cycle_is_not_private() {
+        let tasks = vec![
+            TaskBuilder::new(1).content("Plan trip").parent(2).build(),
+            TaskBuilder::new(2).content("Book flights").parent(1).build(),
+        ];
+        let privacy = PrivacyFilter::new(&tasks, Vec::new());
+
+        assert!(!privacy.is_private(&tasks[0]));
+    }
+}
@@ -0,0 +1,152 @@
+//! Paginated results of a Genius Feed query.
+//!
+//! Pages are stored one after another in a single list, so the feed can show
+//! either one page at a time or everything loaded so far (infinite scroll).
+//! The end of the results is detected from each response: providers that know
+//! it report `has_more`, otherwise a page shorter than the provider's page size
+//! is taken as the last one.
+
+use super::genius_api::{GeniusItem, GeniusResponse};
+
+/// Results of the current query, stored page by page
+#[derive(Debug, Default, Clone)]
+pub struct ResultPages {
+    /// All items loaded so far, in page order
+    items: Vec<GeniusItem>,
+    /// Index into `items` where each page starts
+    page_starts: Vec<usize>,
+    /// Whether the last loaded page was the final one
+    end_reached: bool,
+}
+
+impl ResultPages {
+    /// Create an empty result set
+    pub const fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            page_starts: Vec::new(),
+            end_reached: false,
+        }
+    }
+
+    /// Forget all loaded pages, e.g. when the query changes
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.page_starts.clear();
+        self.end_reached = false;
+    }
+
+    /// Append the next page from a provider response.
+    ///
+    /// `page_size` is the number of items the provider returns for a full page.
+    pub fn push(&mut self, response: &GeniusResponse, page_size: usize) {
+        self.end_reached = match response.has_more {
+            Some(has_more) => !has_more,
+            None => response.items.is_empty() || response.items.len() < page_size,
+        };
+
+        // An empty page only tells us the results are exhausted
+        if response.items.is_empty() {
+            return;
+        }
+
+        self.page_starts.push(self.items.len());
+        self.items.extend(response.items.iter().cloned());
+    }
+
+    /// Items of a page (1-based), if it has been loaded
+    pub fn page(&self, page: usize) -> Option<&[GeniusItem]> {
+        let start = *self.page_starts.get(page.checked_sub(1)?)?;
+        let end = self
+            .page_starts
+            .get(page)
+            .copied()
+            .unwrap_or(self.items.len());
+        Some(&self.items[start..end])
+    }
+
+    /// All items loaded so far, across all pages
+    pub fn items(&self) -> &[GeniusItem] {
+        &self.items
+    }
+
+    /// Number of pages loaded so far
+    pub fn page_count(&self) -> usize {
+        self.page_starts.len()
+    }
+
+    /// Returns true if the provider has no more results for the query
+    pub fn end_reached(&self) -> bool {
+        self.end_reached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(ids: &[&str], has_more: Option<bool>) -> GeniusResponse {
+        GeniusResponse {
+            items: ids
+                .iter()
+                .map(|id| GeniusItem {
+                    id: id.to_string(),
+                    description: format!("Item {id}"),
+                    metadata: serde_json::json!({}),
+                    task_id: None,
+                    card: Default::default(),
+                })
+                .collect(),
+            status: "success".to_string(),
+            has_more,
+        }
+    }
+
+    #[test]
+    fn test_pages_are_stored_separately() {
+        let mut pages = ResultPages::new();
+        pages.push(&response(&["a", "b"], None), 2);
+        pages.push(&response(&["c"], None), 2);
+
+        assert_eq!(pages.page_count(), 2);
+        assert_eq!(pages.page(1).unwrap().len(), 2);
+        assert_eq!(pages.page(2).unwrap()[0].id, "c");
+        assert!(pages.page(0).is_none());
+        assert!(pages.page(3).is_none());
+        assert_eq!(pages.items().len(), 3);
+    }
+
+    #[test]
+    fn test_short_page_ends_results() {
+        let mut pages = ResultPages::new();
+        pages.push(&response(&["a", "b"], None), 2);
+        assert!(!pages.end_reached());
+
+        pages.push(&response(&["c"], None), 2);
+        assert!(pages.end_reached());
+    }
+
+    #[test]
+    fn test_has_more_overrides_page_size() {
+        let mut pages = ResultPages::new();
+        pages.push(&response(&["a"], Some(true)), 10);
+        assert!(!pages.end_reached());
+
+        pages.push(&response(&["b", "c"], Some(false)), 2);
+        assert!(pages.end_reached());
+    }
+
+    #[test]
+    fn test_empty_page_is_not_stored() {
+        let mut pages = ResultPages::new();
+        pages.push(&response(&["a", "b"], None), 2);
+        pages.push(&response(&[], None), 2);
+
+        assert_eq!(pages.page_count(), 1);
+        assert!(pages.end_reached());
+
+        pages.clear();
+        assert_eq!(pages.page_count(), 0);
+        assert!(!pages.end_reached());
+    }
+}
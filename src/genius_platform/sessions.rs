@@ -0,0 +1,216 @@
+//! Genius feed sessions.
+//!
+//! The Genius API keeps a feed per session ID. Sessions are saved next to the
+//! tasks file of the workspace together with the queries asked in them, so the
+//! last feed is resumed on the next launch and older feeds can be switched back
+//! to with `session resume`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::taskstore::{load_json, save_json};
+
+/// File name of the session list inside the workspace
+pub const SESSIONS_FILE_NAME: &str = "genius_sessions.json";
+
+/// Shortest session ID prefix accepted by `SessionStore::resume`
+const MIN_ID_PREFIX_LEN: usize = 4;
+
+/// A Genius feed session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeniusSession {
+    /// Session ID sent to the Genius API
+    pub id: String,
+    /// When the session was started
+    pub started_at: DateTime<Utc>,
+    /// Queries asked in this session, oldest first
+    #[serde(default)]
+    pub queries: Vec<String>,
+}
+
+impl GeniusSession {
+    /// Start a session with a fresh ID
+    pub fn new() -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            started_at: Utc::now(),
+            queries: Vec::new(),
+        }
+    }
+
+    /// The first characters of the ID, enough to tell sessions apart
+    pub fn short_id(&self) -> &str {
+        self.id.get(..8).unwrap_or(&self.id)
+    }
+
+    /// The most recent query of the session
+    pub fn last_query(&self) -> Option<&str> {
+        self.queries.last().map(String::as_str)
+    }
+}
+
+impl Default for GeniusSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// All sessions of a workspace and which one is active
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SessionStore {
+    /// Sessions, oldest first
+    sessions: Vec<GeniusSession>,
+    /// ID of the active session
+    active: Option<String>,
+}
+
+impl SessionStore {
+    /// Create a store without sessions
+    pub const fn new() -> Self {
+        Self {
+            sessions: Vec::new(),
+            active: None,
+        }
+    }
+
+    /// All sessions, oldest first
+    pub fn sessions(&self) -> &[GeniusSession] {
+        &self.sessions
+    }
+
+    /// Position of the active session in the list
+    fn active_index(&self) -> Option<usize> {
+        let active = self.active.as_deref()?;
+        self.sessions.iter().position(|session| session.id == active)
+    }
+
+    /// The active session, if any
+    pub fn active(&self) -> Option<&GeniusSession> {
+        self.active_index().map(|index| &self.sessions[index])
+    }
+
+    /// The active session, started if there is none
+    pub fn active_or_start(&mut self) -> &GeniusSession {
+        match self.active_index() {
+            Some(index) => &self.sessions[index],
+            None => self.start_new(),
+        }
+    }
+
+    /// Start a new session and make it the active one
+    pub fn start_new(&mut self) -> &GeniusSession {
+        let session = GeniusSession::new();
+        self.active = Some(session.id.clone());
+        self.sessions.push(session);
+        self.sessions.last().unwrap()
+    }
+
+    /// Make a session the active one.
+    ///
+    /// `query` is a 1-based position in the session list or the start of a
+    /// session ID, at least `MIN_ID_PREFIX_LEN` characters long.
+    pub fn resume(&mut self, query: &str) -> Option<&GeniusSession> {
+        let query = query.trim();
+        let by_position = query
+            .parse::<usize>()
+            .ok()
+            .and_then(|position| position.checked_sub(1))
+            .filter(|&index| index < self.sessions.len());
+        let index = by_position.or_else(|| {
+            (query.len() >= MIN_ID_PREFIX_LEN)
+                .then(|| self.sessions.iter().position(|session| session.id.starts_with(query)))
+                .flatten()
+        })?;
+        self.active = Some(self.sessions[index].id.clone());
+        Some(&self.sessions[index])
+    }
+
+    /// Add a query to the history of the active session.
+    ///
+    /// Queries are recorded when they are committed, so every query is kept
+    /// except an exact repeat of the previous one.
+    pub fn record_query(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        let Some(index) = self.active_index() else {
+            return;
+        };
+
+        let queries = &mut self.sessions[index].queries;
+        if queries.last().is_none_or(|last| last != query) {
+            queries.push(query.to_string());
+        }
+    }
+}
+
+/// Saves the sessions to a JSON file.
+///
+/// ### Arguments
+/// * `sessions` - The sessions to save
+/// * `path` - Path to the sessions storage file
+pub fn save_sessions(sessions: &SessionStore, path: &str) -> std::io::Result<()> {
+    save_json(sessions, path)
+}
+
+/// Loads the sessions from a JSON file.
+///
+/// ### Arguments
+/// * `path` - Path to the sessions storage file
+pub fn load_sessions(path: &str) -> std::io::Result<SessionStore> {
+    load_json(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record_query_skips_repeats() {
+        let mut store = SessionStore::new();
+        store.start_new();
+
+        for query in ["focus", "focus tips", "focus tips ", "sleep", "focus"] {
+            store.record_query(query);
+        }
+
+        assert_eq!(store.active().unwrap().queries, vec!["focus", "focus tips", "sleep", "focus"]);
+    }
+
+    #[test]
+    fn test_resume_by_position_or_id() {
+        let mut store = SessionStore::new();
+        let first_id = store.start_new().id.clone();
+        store.start_new();
+
+        assert_eq!(store.resume("1").unwrap().id, first_id);
+        assert_eq!(store.active().unwrap().id, first_id);
+
+        let second_id = store.sessions()[1].id.clone();
+        assert_eq!(store.resume(&second_id[..6]).unwrap().id, second_id);
+
+        assert!(store.resume("3").is_none());
+        assert!(store.resume("0").is_none());
+        assert!(store.resume("not-a-session").is_none());
+        assert_eq!(store.active().unwrap().id, second_id);
+    }
+
+    #[test]
+    fn test_sessions_persist() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SESSIONS_FILE_NAME);
+        let path = path.to_str().unwrap();
+
+        let mut store = SessionStore::new();
+        let id = store.start_new().id.clone();
+        store.record_query("focus tips");
+        save_sessions(&store, path).unwrap();
+
+        let loaded = load_sessions(path).unwrap();
+        assert_eq!(loaded.active().unwrap().id, id);
+        assert_eq!(loaded.active().unwrap().last_query(), Some("focus tips"));
+    }
+}
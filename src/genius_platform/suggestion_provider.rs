@@ -0,0 +1,180 @@
+//! Pluggable suggestion providers for the Genius Feed.
+//!
+//! The feed no longer talks to a concrete API client. Instead, the
+//! `GeniusApiBridge` owns a boxed `SuggestionProvider`, which is chosen at
+//! runtime from the `genius.provider` configuration setting. Every provider
+//! returns the same `GeniusResponse` shape so the feed UI does not need to know
+//! where suggestions came from.
+//!
+//! Available providers:
+//! - `genius`: the remote Genius Platform API (`GeniusApiClient`)
+//! - `mock`: static placeholder data for development
+//! - `local`: related tasks from the user's own task store, works offline
+
+use super::genius_api::{GeniusApiClient, GeniusApiError, GeniusResponse};
+use super::local_provider::LocalProvider;
+use super::mock_provider::MockProvider;
+use super::query_context::QueryContext;
+use crate::taskstore::Task;
+
+/// Everything a provider needs to answer a query
+#[derive(Debug, Clone, Copy)]
+pub struct SuggestionRequest<'a> {
+    /// The text the user typed
+    pub input: &'a str,
+    /// Snapshot of the task store, used by providers that work on local data
+    pub tasks: &'a [Task],
+    /// Task context for providers that accept it
+    pub context: &'a QueryContext,
+    /// Feed session for providers that keep one
+    pub session_id: Option<&'a str>,
+}
+
+/// Context of requests that don't carry any
+static EMPTY_CONTEXT: QueryContext = QueryContext::new();
+
+impl<'a> SuggestionRequest<'a> {
+    /// Create a request for the given input and task store
+    pub const fn new(input: &'a str, tasks: &'a [Task]) -> Self {
+        Self {
+            input,
+            tasks,
+            context: &EMPTY_CONTEXT,
+            session_id: None,
+        }
+    }
+
+    /// Attach task context to the request
+    pub const fn with_context(mut self, context: &'a QueryContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Send the request in a feed session
+    pub const fn with_session(mut self, session_id: Option<&'a str>) -> Self {
+        self.session_id = session_id;
+        self
+    }
+}
+
+/// Describes what a provider can do, so the UI can adapt to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderCapabilities {
+    /// Whether the provider can return more than one page of results
+    pub paginated: bool,
+    /// Number of items in a full page, used to detect the last page
+    pub page_size: usize,
+    /// Whether the provider needs network access to answer queries
+    pub requires_network: bool,
+    /// Whether the returned items refer to tasks in the local store
+    pub links_to_tasks: bool,
+}
+
+/// A source of suggestions for the Genius Feed
+pub trait SuggestionProvider: Send {
+    /// Short, human-readable name shown in the feed header
+    fn name(&self) -> &'static str;
+
+    /// The capabilities of this provider
+    fn capabilities(&self) -> ProviderCapabilities;
+
+    /// Fetch a specific page (1-based) of suggestions for the request
+    fn query_page(
+        &self,
+        request: &SuggestionRequest<'_>,
+        page: usize,
+    ) -> Result<GeniusResponse, GeniusApiError>;
+
+    /// Fetch the first page of suggestions for the request
+    fn query(&self, request: &SuggestionRequest<'_>) -> Result<GeniusResponse, GeniusApiError> {
+        self.query_page(request, 1)
+    }
+}
+
+/// Which provider to use, as selected in the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProviderKind {
+    /// Use the Genius API when credentials are configured, local tasks otherwise
+    #[default]
+    Auto,
+    /// Always use the Genius API
+    Genius,
+    /// Always use mock data
+    Mock,
+    /// Suggest related tasks from the local task store
+    Local,
+}
+
+impl ProviderKind {
+    /// Parse a provider name from the configuration (case insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "auto" | "" => Some(Self::Auto),
+            "genius" => Some(Self::Genius),
+            "mock" => Some(Self::Mock),
+            "local" => Some(Self::Local),
+            _ => None,
+        }
+    }
+
+    /// Returns the provider kind selected in the configuration.
+    ///
+    /// The `mock-api` cargo feature still forces mock data, regardless of
+    /// what the configuration says.
+    pub fn configured() -> Self {
+        if cfg!(feature = "mock-api") {
+            return Self::Mock;
+        }
+
+        let name = &crate::config::get_config().genius.provider;
+        Self::from_name(name).unwrap_or_else(|| {
+            log::warn!("Unknown suggestion provider '{name}', falling back to auto");
+            Self::Auto
+        })
+    }
+}
+
+/// Create a provider of the given kind.
+///
+/// `client` is the configured Genius API client, if credentials are available.
+pub fn create_provider(
+    kind: ProviderKind,
+    client: Option<GeniusApiClient>,
+) -> Box<dyn SuggestionProvider> {
+    match (kind, client) {
+        (ProviderKind::Mock, _) => Box::new(MockProvider),
+        (ProviderKind::Local, _) => Box::new(LocalProvider::new()),
+        (ProviderKind::Genius | ProviderKind::Auto, Some(client)) => Box::new(client),
+        // An explicitly selected but unconfigured client reports its own errors
+        (ProviderKind::Genius, None) => Box::new(GeniusApiClient::new()),
+        (ProviderKind::Auto, None) => Box::new(LocalProvider::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_kind_from_name() {
+        assert_eq!(ProviderKind::from_name("Local"), Some(ProviderKind::Local));
+        assert_eq!(ProviderKind::from_name(" mock "), Some(ProviderKind::Mock));
+        assert_eq!(ProviderKind::from_name("genius"), Some(ProviderKind::Genius));
+        assert_eq!(ProviderKind::from_name(""), Some(ProviderKind::Auto));
+        assert_eq!(ProviderKind::from_name("bing"), None);
+    }
+
+    #[test]
+    fn test_create_provider_selection() {
+        assert_eq!(create_provider(ProviderKind::Auto, None).name(), "local");
+        assert_eq!(create_provider(ProviderKind::Local, None).name(), "local");
+        assert_eq!(
+            create_provider(ProviderKind::Auto, Some(GeniusApiClient::new())).name(),
+            "genius"
+        );
+        assert_eq!(
+            create_provider(ProviderKind::Mock, Some(GeniusApiClient::new())).name(),
+            "mock"
+        );
+    }
+}
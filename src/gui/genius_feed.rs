@@ -5,10 +5,13 @@
 
 use eframe::egui;
 use crate::genius_platform::{GeniusItem, GeniusApiBridge};
+use crate::genius_platform::pinboard::Pinboard;
+use crate::genius_platform::query_context::QueryContext;
 use crate::App;
 use std::time::{Duration, Instant};
 use std::cell::RefCell;
 use std::collections::HashSet;
+use log::debug;
 
 // Thread-local cache for rate limiting API requests
 thread_local! {
@@ -37,14 +40,18 @@ impl ApiRequestCache {
 pub struct GeniusFeedState {
     /// Index of the currently focused item (0-based)
     pub focused_index: Option<usize>,
-    /// Set of expanded item indices that show metadata
-    pub expanded_items: HashSet<usize>,
+    /// IDs of expanded items that show their full text
+    pub expanded_items: HashSet<String>,
     /// Flag indicating that more items should be loaded
     pub should_load_more: bool,
-    /// Set of pinned item IDs that should persist across queries
-    pub pinned_items: HashSet<String>,
+    /// Whether the pinboard is shown instead of the query results
+    pub show_pinboard: bool,
     /// Current page being displayed (1-based)
     pub current_page: usize,
+    /// Whether all loaded pages are shown as one list that grows while scrolling
+    pub infinite_scroll: bool,
+    /// Whether the task context sent with the query is shown in the header
+    pub show_context: bool,
 }
 
 impl GeniusFeedState {
@@ -53,8 +60,10 @@ impl GeniusFeedState {
             focused_index: Some(0), // Start with the first item focused
             expanded_items: HashSet::new(),
             should_load_more: false,
-            pinned_items: HashSet::new(),
+            show_pinboard: false,
             current_page: 1,
+            infinite_scroll: crate::config::get_config().genius.infinite_scroll,
+            show_context: false,
         }
     }
 
@@ -69,93 +78,69 @@ impl GeniusFeedState {
     }
 
     /// Check if an item is expanded
-    pub fn is_item_expanded(index: usize) -> bool {
-        FEED_STATE.with(|state| {
-            let state = state.borrow();
-            let global_index = ((state.current_page - 1) * 10) + index;
-            let is_expanded = state.expanded_items.contains(&global_index);
-            println!("[DEBUG] is_item_expanded: index={}, current_page={}, global_index={}, is_expanded={}", 
-                index, state.current_page, global_index, is_expanded);
-            is_expanded
-        })
+    pub fn is_item_expanded(item_id: &str) -> bool {
+        FEED_STATE.with(|state| state.borrow().expanded_items.contains(item_id))
     }
 
     /// Toggle the expanded state of an item
-    pub fn toggle_item_expansion(index: usize) {
+    pub fn toggle_item_expansion(item_id: &str) {
         FEED_STATE.with(|state| {
             let mut state = state.borrow_mut();
-            let global_index = ((state.current_page - 1) * 10) + index;
-            println!("[DEBUG] toggle_item_expansion: index={}, current_page={}, global_index={}, expanded_items={:?}", 
-                index, state.current_page, global_index, state.expanded_items);
-            
-            if state.expanded_items.contains(&global_index) {
-                state.expanded_items.remove(&global_index);
-                println!("[DEBUG] toggle_item_expansion: Removed global_index {} from expanded_items", global_index);
-            } else {
-                state.expanded_items.insert(global_index);
-                println!("[DEBUG] toggle_item_expansion: Added global_index {} to expanded_items", global_index);
+            if !state.expanded_items.remove(item_id) {
+                state.expanded_items.insert(item_id.to_string());
             }
-            
-            println!("[DEBUG] toggle_item_expansion AFTER: expanded_items={:?}", state.expanded_items);
+            debug!("toggle_item_expansion: item_id={}, expanded_items={:?}", 
+                item_id, state.expanded_items);
         });
     }
 
-    /// Check if an item is pinned
-    pub fn is_item_pinned(item_id: &str) -> bool {
-        FEED_STATE.with(|state| state.borrow().pinned_items.contains(item_id))
+    /// Check if the query context is shown in the header
+    pub fn is_context_shown() -> bool {
+        FEED_STATE.with(|state| state.borrow().show_context)
     }
-    
-    /// Toggle the pinned state of an item
-    pub fn toggle_item_pinned(item_id: &str) {
+
+    /// Show or hide the query context in the header
+    pub fn toggle_context() {
         FEED_STATE.with(|state| {
             let mut state = state.borrow_mut();
-            if state.pinned_items.contains(item_id) {
-                state.pinned_items.remove(item_id);
-            } else {
-                state.pinned_items.insert(item_id.to_string());
-            }
+            state.show_context = !state.show_context;
         });
     }
+
+    /// Check if infinite scroll is enabled
+    pub fn is_infinite_scroll() -> bool {
+        FEED_STATE.with(|state| state.borrow().infinite_scroll)
+    }
+
+    /// Check if the pinboard is shown instead of the query results
+    pub fn is_pinboard_shown() -> bool {
+        FEED_STATE.with(|state| state.borrow().show_pinboard)
+    }
     
-    /// Get all pinned item IDs
-    pub fn get_pinned_items() -> HashSet<String> {
-        FEED_STATE.with(|state| state.borrow().pinned_items.clone())
+    /// Switch between the pinboard and the query results
+    pub fn toggle_pinboard() {
+        FEED_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            state.show_pinboard = !state.show_pinboard;
+            // Start at the top of the newly shown list
+            state.focused_index = Some(0);
+            state.expanded_items.clear();
+        });
     }
 
     /// Get the item at the focused index, taking into account sorting and pinning
-    pub fn get_focused_item() -> Option<crate::genius_platform::genius_api::GeniusItem> {
+    pub fn get_focused_item(pinboard: &Pinboard) -> Option<crate::genius_platform::genius_api::GeniusItem> {
         let focused_idx = Self::get_focused_index()?;
         
-        // Store the API bridge in a variable to avoid temporary value issues
-        let api_bridge = crate::genius_platform::genius_api_bridge::GeniusApiBridge::global();
-        // Clone the response to ensure we own the data
-        let response = api_bridge.last_response()?.clone();
-        let mut items = response.items.clone();
-        
-        // Prioritize pinned items
-        let pinned_item_ids = Self::get_pinned_items();
-        if !pinned_item_ids.is_empty() {
-            let mut pinned_items = Vec::new();
-            let mut unpinned_items = Vec::new();
-            
-            for item in items {
-                if pinned_item_ids.contains(&item.id) {
-                    pinned_items.push(item);
-                } else {
-                    unpinned_items.push(item);
-                }
-            }
-            
-            items = pinned_items;
-            items.extend(unpinned_items);
-        }
+        let items = visible_items(&GeniusApiBridge::global(), pinboard);
         
         // Return the item at the focused index if it exists
-        if focused_idx < items.len() {
-            Some(items[focused_idx].clone())
-        } else {
-            None
-        }
+        items.get(focused_idx).cloned()
+    }
+
+    /// Number of items that can be focused in the current view
+    pub fn navigable_item_count(pinboard: &Pinboard) -> usize {
+        visible_items(&GeniusApiBridge::global(), pinboard).len()
     }
 
     /// Move focus up
@@ -231,8 +216,6 @@ impl GeniusFeedState {
             state.current_page += 1;
             // Reset focus to the first item on the new page
             state.focused_index = Some(0);
-            // Clear expanded items when changing pages
-            state.expanded_items.clear();
         });
     }
 
@@ -244,13 +227,43 @@ impl GeniusFeedState {
                 state.current_page -= 1;
                 // Reset focus to the first item on the new page
                 state.focused_index = Some(0);
-                // Clear expanded items when changing pages
-                state.expanded_items.clear();
             }
         });
     }
 }
 
+/// The items shown in the feed, in display order
+///
+/// This is the pinboard, every loaded page (infinite scroll) or the current
+/// page, with pinned items moved to the top. Focus indices refer to this list.
+fn visible_items(api_bridge: &GeniusApiBridge, pinboard: &Pinboard) -> Vec<GeniusItem> {
+    // The pinboard works offline, straight from the saved pins
+    if GeniusFeedState::is_pinboard_shown() {
+        return pinboard.items().iter().map(|pin| pin.item.clone()).collect();
+    }
+    
+    let items = if GeniusFeedState::is_infinite_scroll() {
+        api_bridge.all_items().to_vec()
+    } else {
+        api_bridge
+            .page_items(GeniusFeedState::get_current_page())
+            .map(<[GeniusItem]>::to_vec)
+            .unwrap_or_default()
+    };
+    
+    // Prioritize pinned items by moving them to the top
+    prioritize_pinned(items, pinboard)
+}
+
+/// Move pinned items to the top of the list, keeping the order otherwise
+fn prioritize_pinned(items: Vec<GeniusItem>, pinboard: &Pinboard) -> Vec<GeniusItem> {
+    let (mut pinned, unpinned): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .partition(|item| pinboard.is_pinned(&item.id));
+    pinned.extend(unpinned);
+    pinned
+}
+
 /// Query the API if conditions are met (rate limiting and input changed)
 /// 
 /// This function checks if an API request should be made based on:
@@ -272,6 +285,15 @@ pub fn maybe_query_api(app: &mut App, input_text: &str) {
             return;
         }
         
+        // Only send real queries: no commands, task edits or private content
+        let external = GeniusApiBridge::global().capabilities().requires_network;
+        let input_kind = crate::genius_platform::query_filter::classify_input(app, input_text, external);
+        if !input_kind.is_query() {
+            debug!("maybe_query_api: Not querying {:?} input", input_kind);
+            cache.last_query_text = input_text.to_string();
+            return;
+        }
+        
         // Check if enough time has passed since the last request
         let should_query = match cache.last_api_request {
             Some(last_time) => {
@@ -286,12 +308,13 @@ pub fn maybe_query_api(app: &mut App, input_text: &str) {
             cache.last_api_request = Some(Instant::now());
             cache.last_query_text = input_text.to_string();
             
-            // Reset to page 1 when the query changes
+            // Reset to the top of page 1 when the query changes
             GeniusFeedState::set_current_page(1);
+            GeniusFeedState::set_focused_index(Some(0));
             
             // Query the API using the global API bridge
-            let mut api_bridge = crate::genius_platform::get_api_bridge();
-            let _ = api_bridge.query_with_input(app, input_text);
+            // (the query is recorded once it is committed, see `commit_feed_query`)
+            let _ = crate::genius_platform::get_api_bridge().query_with_input(app, input_text);
         }
     });
 }
@@ -299,50 +322,23 @@ pub fn maybe_query_api(app: &mut App, input_text: &str) {
 /// Render the Genius Feed widget
 /// 
 /// This function displays items from the Genius API as a bulleted list.
-pub fn render_genius_feed(ui: &mut egui::Ui, api_bridge: &GeniusApiBridge, app_mode: crate::commands::AppMode) {
+pub fn render_genius_feed(ui: &mut egui::Ui, api_bridge: &GeniusApiBridge, app: &App) {
     // Determine if we're in feed mode for highlighting
-    let is_feed_mode = matches!(app_mode, crate::commands::AppMode::Feed);
+    let is_feed_mode = matches!(app.app_mode, crate::commands::AppMode::Feed);
     
     // Create a frame with some padding and a visible border
     egui::Frame::none()
         .inner_margin(egui::style::Margin::symmetric(8.0, 4.0))
         .stroke(egui::Stroke::new(1.0, ui.visuals().widgets.noninteractive.bg_stroke.color))
         .show(ui, |ui| {
+            if GeniusFeedState::is_pinboard_shown() {
+                render_pinboard(ui, &app.pinboard, is_feed_mode);
+                return;
+            }
+            
             // Check if there's any data to display
-            if let Some(response) = api_bridge.last_response() {
-                let mut items = response.items.clone();
-                
-                // EMERGENCY HACKATHON FIX: Filter out problematic items that cause rendering issues
-                // We've identified that items with specific patterns cause zero-width UI issues
-                // For the presentation, we'll completely filter these out
-                items = items.into_iter()
-                    .filter(|item| {
-                        // Filter out items matching the problematic pattern we identified
-                        // (items with attribution format that cause zero-width UI issues)
-                        !(item.description.contains("\n-") && 
-                          item.description.len() > 140 && 
-                          item.description.len() < 170 &&
-                          item.description.contains('\n'))
-                    })
-                    .collect();
-                
-                // Prioritize pinned items by moving them to the top
-                let pinned_item_ids = GeniusFeedState::get_pinned_items();
-                if !pinned_item_ids.is_empty() {
-                    let mut pinned_items = Vec::new();
-                    let mut unpinned_items = Vec::new();
-                    
-                    for item in items {
-                        if pinned_item_ids.contains(&item.id) {
-                            pinned_items.push(item);
-                        } else {
-                            unpinned_items.push(item);
-                        }
-                    }
-                    
-                    items = pinned_items;
-                    items.extend(unpinned_items);
-                }
+            if api_bridge.last_response().is_some() {
+                let items = visible_items(api_bridge, &app.pinboard);
                 
                 // Get the currently focused index
                 let focused_index = GeniusFeedState::get_focused_index();
@@ -356,16 +352,40 @@ pub fn render_genius_feed(ui: &mut egui::Ui, api_bridge: &GeniusApiBridge, app_m
                 let item_count = items.len();
                 let current_page = GeniusFeedState::get_current_page();
                 let total_items = api_bridge.all_items().len();
+                let infinite_scroll = GeniusFeedState::is_infinite_scroll();
+                
+                // "+" marks that more pages can still be loaded
+                let loaded_pages = format!("{}{}", api_bridge.loaded_page_count(),
+                    if api_bridge.has_more_pages() { "+" } else { "" });
                 
                 // Add debugging information at the top
                 ui.horizontal(|ui| {
-                    ui.label(format!("Page: {} | Items on page: {} | Total Items: {}", 
-                        current_page, item_count, total_items));
+                    if infinite_scroll {
+                        ui.label(format!("Provider: {} | Pages: {} | Items: {}", 
+                            api_bridge.provider_name(), loaded_pages, total_items));
+                    } else {
+                        ui.label(format!("Provider: {} | Page: {} of {} | Items on page: {} | Total Items: {}", 
+                            api_bridge.provider_name(), current_page, loaded_pages, item_count, total_items));
+                    }
+                    
+                    // Toggle for the task context that was sent with the query (also Ctrl+I)
+                    if ui.selectable_label(GeniusFeedState::is_context_shown(), "Context").clicked() {
+                        GeniusFeedState::toggle_context();
+                    }
                 });
                 
+                if GeniusFeedState::is_context_shown() {
+                    render_query_context(ui, api_bridge.last_context());
+                }
+                
                 // Add page navigation instructions
                 ui.horizontal(|ui| {
-                    ui.label(egui::RichText::new("Navigation: Up/Down to move focus, Shift+Up/Down to change page").small().weak()); // TODO: Figure out why unicode arrows aren't rendering on my machine
+                    let navigation = if infinite_scroll {
+                        "Navigation: Up/Down to move focus, more results load near the bottom, Ctrl+P for pinboard"
+                    } else {
+                        "Navigation: Up/Down to move focus, Shift+Up/Down to change page, Ctrl+P for pinboard"
+                    };
+                    ui.label(egui::RichText::new(navigation).small().weak()); // TODO: Figure out why unicode arrows aren't rendering on my machine
                 });
                 
                 ui.add_space(4.0);
@@ -382,15 +402,13 @@ pub fn render_genius_feed(ui: &mut egui::Ui, api_bridge: &GeniusApiBridge, app_m
                 egui::ScrollArea::vertical()
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                        // Calculate the global item index for numbering (for debugging)
-                        let start_index = (current_page - 1) * 10; // Assuming 10 items per page
-                        
                         for (idx, item) in items.iter().enumerate() {
                             // Only highlight if we're in Feed mode
                             let is_focused = is_feed_mode && focused_index == Some(idx);
                             
                             // We need to wrap this in a container to capture item-specific interactions
-                            let item_response = render_genius_item(ui, item, is_focused, start_index + idx);
+                            let is_pinned = app.pinboard.is_pinned(&item.id);
+                            let item_response = render_genius_item(ui, item, is_focused, is_pinned);
                             
                             // If this item is focused, scroll to make it visible
                             if is_focused {
@@ -416,6 +434,9 @@ pub fn render_genius_feed(ui: &mut egui::Ui, api_bridge: &GeniusApiBridge, app_m
                                 ui.spinner();
                                 ui.label("Loading results...");
                             });
+                        } else if !api_bridge.has_more_pages() && (infinite_scroll || current_page == api_bridge.loaded_page_count()) {
+                            ui.add_space(4.0);
+                            ui.label(egui::RichText::new("End of results").small().weak());
                         }
                     });
                 
@@ -429,21 +450,91 @@ pub fn render_genius_feed(ui: &mut egui::Ui, api_bridge: &GeniusApiBridge, app_m
                 ui.label("Loading results...");
             } else {
                 ui.label("Type to see Genius suggestions");
+                if !app.pinboard.is_empty() {
+                    ui.label(egui::RichText::new(format!("{} pinned items, Ctrl+P to browse them", app.pinboard.len())).small().weak());
+                }
             }
         });
 }
 
-/// Render a single Genius item
-fn render_genius_item(ui: &mut egui::Ui, item: &GeniusItem, is_focused: bool, item_index: usize) -> egui::Response {
-    // Get the current page and calculate the local index for expansion check
-    let current_page = GeniusFeedState::get_current_page();
-    let local_index = item_index - ((current_page - 1) * 10);
+/// Render the task context that was sent with the current query
+fn render_query_context(ui: &mut egui::Ui, context: &QueryContext) {
+    let mut lines = Vec::new();
+    if let Some(focused_task) = &context.focused_task {
+        lines.push(format!("Focused: {}", focused_task));
+    }
+    if !context.ancestors.is_empty() {
+        lines.push(format!("Parents: {}", context.ancestors.join(" > ")));
+    }
+    if !context.siblings.is_empty() {
+        lines.push(format!("Siblings: {}", context.siblings.join(", ")));
+    }
+    if let Some(container) = &context.container {
+        lines.push(format!("Container: {}", container));
+    }
     
-    // Check if this item is expanded using the local index
-    let is_expanded = GeniusFeedState::is_item_expanded(local_index);
+    if lines.is_empty() {
+        lines.push("No task context was sent".to_string());
+    } else {
+        lines.push(format!("~{} tokens", context.estimated_tokens()));
+    }
     
-    // Check if this item is pinned
-    let is_pinned = GeniusFeedState::is_item_pinned(&item.id);
+    for line in lines {
+        ui.add(egui::Label::new(egui::RichText::new(line).small().weak()).truncate(true));
+    }
+}
+
+/// Render the pinboard with all saved pins, newest first
+///
+/// This does not need the API, the pins are read from the workspace.
+fn render_pinboard(ui: &mut egui::Ui, pinboard: &Pinboard, is_feed_mode: bool) {
+    ui.horizontal(|ui| {
+        ui.label(format!("Pinboard | Pinned items: {}", pinboard.len()));
+    });
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Navigation: Up/Down to move focus, Ctrl+Enter to unpin, Ctrl+P for results").small().weak());
+    });
+    ui.add_space(4.0);
+    
+    if pinboard.is_empty() {
+        GeniusFeedState::set_focused_index(None);
+        ui.label("Nothing pinned yet. Pin items in the feed with Ctrl+Enter");
+        return;
+    }
+    
+    // Keep the focus inside the pinboard
+    let focused_index = GeniusFeedState::get_focused_index().unwrap_or(0).min(pinboard.len() - 1);
+    GeniusFeedState::set_focused_index(Some(focused_index));
+    
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for (idx, pin) in pinboard.items().iter().enumerate() {
+                let is_focused = is_feed_mode && focused_index == idx;
+                let item_response = render_genius_item(ui, &pin.item, is_focused, true);
+                
+                // Show where the pin came from
+                ui.label(egui::RichText::new(format!(
+                    "    pinned {} from query '{}'",
+                    pin.pinned_at.with_timezone(&chrono::Local).format("%Y-%m-%d"),
+                    pin.query
+                )).small().weak());
+                
+                if is_focused {
+                    item_response.scroll_to_me(Some(egui::Align::Center));
+                }
+            }
+        });
+}
+
+/// Render a single Genius item
+///
+/// Collapsed items show the card headline on one line. Expanded items also show
+/// the markdown body, the attribution and the source link of the card.
+fn render_genius_item(ui: &mut egui::Ui, item: &GeniusItem, is_focused: bool, is_pinned: bool) -> egui::Response {
+    // Expansion is tracked by item ID, so it survives paging and re-sorting
+    let is_expanded = GeniusFeedState::is_item_expanded(&item.id);
+    let card = item.display_card();
     
     // Define colors
     let accent_color = egui::Color32::from_rgb(57, 255, 20);
@@ -473,79 +564,63 @@ fn render_genius_item(ui: &mut egui::Ui, item: &GeniusItem, is_focused: bool, it
     
     // Use a single frame for the entire item including expanded content
     frame.show(ui, |ui| {
-        // Main row with bullet, pin icon, and description
-        ui.horizontal(|ui| {
-            // Display bullet instead of item number
-            ui.label(egui::RichText::new("• ").color(text_color));
+        // Main row: the right-aligned labels are laid out first, so the
+        // headline only gets the remaining width and is truncated to it
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            // Add a small indicator for items with more to show
+            let expand_indicator = if card.has_details() && !is_expanded { "▶" } else { "  " }; // Use two spaces to maintain consistent width
+            ui.label(egui::RichText::new(expand_indicator).weak().color(text_color));
             
-            // Display pin icon if pinned
-            if is_pinned {
-                ui.label(egui::RichText::new("📌 ").color(text_color));
+            // Items from the local task store link back to their task
+            if let Some(task_id) = item.task_id {
+                ui.label(egui::RichText::new(format!("#{} {}", task_id, card.category.as_deref().unwrap_or_default())).small().color(text_color));
+            } else if let Some(category) = &card.category {
+                ui.label(egui::RichText::new(category).small().color(text_color));
             }
             
-            // For expanded items with multiple lines, show the full text
-            if is_expanded && item.description.contains('\n') {
-                // Create a fixed width container for the text to prevent resizing
-                let available_width = ui.available_width() - 20.0; // Reserve space for the expand indicator
-                let text_width = available_width.min(ui.available_width() * 0.85); // Cap at 85% of available width
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                // Display bullet instead of item number
+                ui.label(egui::RichText::new("• ").color(text_color));
+                
+                // Display pin icon if pinned
+                if is_pinned {
+                    ui.label(egui::RichText::new("📌 ").color(text_color));
+                }
+                
+                let mut headline = egui::RichText::new(card.headline()).color(text_color);
+                if card.title.is_some() {
+                    headline = headline.strong();
+                }
+                ui.add(egui::Label::new(headline).truncate(true));
+            });
+        });
+        
+        if is_expanded {
+            ui.indent(&item.id, |ui| {
+                // Without a title the first line of the body is already the headline
+                let body = if card.title.is_some() {
+                    card.body.trim()
+                } else {
+                    card.body.trim().split_once('\n').map_or("", |(_, rest)| rest.trim())
+                };
+                if !body.is_empty() {
+                    crate::gui::markdown::render_markdown(ui, body, text_color);
+                }
                 
-                // Display the full text with explicit wrapping enabled
-                ui.allocate_ui_with_layout(
-                    egui::vec2(text_width, ui.available_height()),
-                    egui::Layout::left_to_right(egui::Align::TOP),
-                    |ui| {
-                        ui.add(egui::Label::new(
-                            egui::RichText::new(item.description.trim()).color(text_color)
-                        ).wrap(true));
+                if let Some(attribution) = &card.attribution {
+                    ui.label(egui::RichText::new(format!("- {}", attribution)).italics().color(text_color));
+                }
+                
+                ui.horizontal_wrapped(|ui| {
+                    if let Some(url) = &card.source_url {
+                        ui.hyperlink_to(egui::RichText::new(url).small(), url);
+                    }
+                    if let Some(relevance) = card.relevance {
+                        ui.label(egui::RichText::new(format!("relevance {:.2}", relevance)).small().weak().color(text_color));
                     }
-                );
-            } else {
-                // For non-expanded items or single-line items, show standard layout
-                // Create a layout that gives text a specific amount of space and reserves space for the right elements
-                ui.horizontal(|ui| {
-                    // Calculate available width for text
-                    // Reserve space for the expand indicator (approximately 20 pixels)
-                    let available_width = ui.available_width() - 20.0;
-                    
-                    // Process the description text
-                    let display_text = if is_expanded {
-                        // When expanded with a single line, show the full text
-                        item.description.trim().to_string()
-                    } else {
-                        // When not expanded, truncate to first line and add ellipsis if needed
-                        let first_line = item.description.lines().next().unwrap_or("").trim().to_string();
-                        
-                        // Only add ellipsis if there are multiple lines
-                        if item.description.contains('\n') {
-                            // If text has multiple lines, add ellipsis to indicate more content
-                            first_line + "..."
-                        } else {
-                            // For single line text, just show it as is
-                            first_line
-                        }
-                    };
-                    
-                    // Create a fixed width container for the text to prevent resizing
-                    let text_width = available_width.min(ui.available_width() * 0.85); // Cap at 85% of available width
-                    
-                    // Display the processed text without automatic truncation in a fixed-width container
-                    ui.allocate_ui_with_layout(
-                        egui::vec2(text_width, ui.available_height()),
-                        egui::Layout::left_to_right(egui::Align::Center),
-                        |ui| {
-                            ui.label(egui::RichText::new(&display_text).color(text_color));
-                        }
-                    );
                 });
-            }
-            
-            // Add right-aligned elements with fixed layout
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                // Add a small indicator for expanded state
-                let expand_indicator = if is_expanded { "  " } else { "▶" }; // Use two spaces to maintain consistent width
-                ui.label(egui::RichText::new(expand_indicator).weak().color(text_color));
             });
-        });
+        }
     }).response
 }
 
@@ -566,6 +641,8 @@ mod tests {
                 id: format!("item-{}", i),
                 description: format!("Item {} - This is a dummy item for test", i),
                 metadata: serde_json::json!({}),
+                task_id: None,
+                card: Default::default(),
             };
             items.push(item);
         }
@@ -574,6 +651,7 @@ mod tests {
         let response = GeniusResponse {
             items,
             status: "success".to_string(),
+            has_more: None,
         };
         
         // Set the response in the bridge
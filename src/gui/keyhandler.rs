@@ -27,8 +27,48 @@
 
 use eframe::egui;
 use crate::App;
-use crate::commands::{Command, parse_command, execute_command, execute_create_command, execute_add_subtask, toggle_app_mode};
+use crate::command_parser::Target;
+use crate::commands::{AppMode, Command, command_for_enter, execute_command, execute_create_command, execute_add_subtask_by_id, toggle_app_mode, execute_credential_input, cancel_credential_prompt, record_input, execute_zoom_by_id_command, execute_zoom_out_command};
+use crate::completion;
 use crate::gui::genius_feed;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Keys that show one to nine levels of tasks with Ctrl
+const DEPTH_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+/// Runs a command on the selected tasks, or on the focused task if none are
+/// selected, and keeps the focus on the same task.
+///
+/// Returns false if no task is focused.
+fn run_on_focused_tasks(app: &mut App, input_text: &mut String, command: fn(Vec<Target>) -> Command) -> bool {
+    let state = &app.display_container_state;
+    let Some(task_id) = state.focused_task_id() else {
+        return false;
+    };
+    let targets = if state.selected_tasks.is_empty() {
+        let Some((index, _)) = state.visible_task_paths(&app.tasks).into_iter().find(|(_, id)| *id == task_id) else {
+            return false;
+        };
+        vec![Target::Index(index)]
+    } else {
+        vec![Target::Selection]
+    };
+    
+    execute_command(app, Some(command(targets)));
+    app.display_container_state.focus_task_and_update_input(Some(task_id), &app.tasks);
+    *input_text = app.display_container_state.input_value().to_string();
+    true
+}
 
 /// Handles keyboard shortcuts and input events
 pub struct KeyHandler {
@@ -36,6 +76,8 @@ pub struct KeyHandler {
     shift_pressed: bool,
     /// Whether control key is currently pressed
     ctrl_pressed: bool,
+    /// Whether alt key is currently pressed
+    alt_pressed: bool,
 }
 
 impl KeyHandler {
@@ -44,6 +86,7 @@ impl KeyHandler {
         Self {
             shift_pressed: false,
             ctrl_pressed: false,
+            alt_pressed: false,
         }
     }
     
@@ -53,9 +96,145 @@ impl KeyHandler {
         ctx.input(|i| {
             self.shift_pressed = i.modifiers.shift;
             self.ctrl_pressed = i.modifiers.ctrl;
+            self.alt_pressed = i.modifiers.alt;
         });
     }
     
+    /// Handle keyboard input while the `login` flow waits for an answer
+    ///
+    /// Answers never reach the command parser or the Genius feed.
+    pub fn handle_credential_prompt(&mut self, app: &mut App, ctx: &egui::Context, input_text: &mut String) -> bool {
+        let mut handled = false;
+        
+        ctx.input(|i| {
+            if i.key_pressed(egui::Key::Enter) {
+                let answer = Zeroizing::new(std::mem::take(input_text));
+                execute_credential_input(app, &answer);
+                handled = true;
+            } else if i.key_pressed(egui::Key::Escape) {
+                input_text.zeroize();
+                cancel_credential_prompt(app);
+                handled = true;
+            }
+        });
+        
+        handled
+    }
+    
+    /// Handle the input history keys of both modes
+    ///
+    /// Alt+Up/Down recall older and newer entries on the input line and Ctrl+R
+    /// starts a reverse search. Returns true if a key was handled.
+    pub fn handle_history_keys(&mut self, app: &mut App, ctx: &egui::Context, input_text: &mut String) -> bool {
+        let mut handled = false;
+        self.update_modifiers(ctx);
+        
+        // In PKM mode the input line only holds commands while no task is focused
+        let mode = app.app_mode;
+        let on_input_line = mode == AppMode::Feed
+            || matches!(app.display_container_state.focused_index, Some(0) | None);
+        if !on_input_line {
+            return false;
+        }
+        
+        ctx.input(|i| {
+            let recalled = if self.alt_pressed && i.key_pressed(egui::Key::ArrowUp) {
+                handled = true;
+                app.input_history.recall_previous(mode, input_text)
+            } else if self.alt_pressed && i.key_pressed(egui::Key::ArrowDown) {
+                handled = true;
+                app.input_history.recall_next(mode, input_text)
+            } else {
+                if self.ctrl_pressed && i.key_pressed(egui::Key::R) {
+                    app.input_history.start_search(mode, input_text);
+                    input_text.clear();
+                    handled = true;
+                }
+                None
+            };
+            
+            if let Some(entry) = recalled {
+                *input_text = entry;
+                app.display_container_state.request_cursor_at_end = true;
+            }
+        });
+        
+        handled
+    }
+    
+    /// Handle keyboard input while a reverse history search (Ctrl+R) runs
+    ///
+    /// The input line holds the search text. Enter puts the match on the input
+    /// line, Ctrl+R moves to an older match and Escape restores the input.
+    pub fn handle_history_search(&mut self, app: &mut App, ctx: &egui::Context, input_text: &mut String) -> bool {
+        let mut handled = false;
+        self.update_modifiers(ctx);
+        app.input_history.set_search_query(input_text);
+        
+        ctx.input(|i| {
+            let finished = if i.key_pressed(egui::Key::Enter) {
+                app.input_history.accept_search()
+            } else if i.key_pressed(egui::Key::Escape) {
+                app.input_history.cancel_search()
+            } else {
+                if self.ctrl_pressed && i.key_pressed(egui::Key::R) {
+                    app.input_history.search_older();
+                    handled = true;
+                }
+                None
+            };
+            
+            if let Some(text) = finished {
+                *input_text = text;
+                app.display_container_state.request_cursor_at_end = true;
+                handled = true;
+            }
+        });
+        
+        handled
+    }
+    
+    /// Handle the completion popup of the input line in PKM mode
+    ///
+    /// Tab accepts the highlighted suggestion and Ctrl+Up/Down moves the
+    /// highlight. Returns true if a key was handled.
+    pub fn handle_completion_keys(&mut self, app: &mut App, ctx: &egui::Context, input_text: &mut String) -> bool {
+        if !matches!(app.display_container_state.focused_index, Some(0) | None) {
+            return false;
+        }
+        self.update_modifiers(ctx);
+        
+        let completions = completion::complete(app, input_text);
+        app.completion_state.update(input_text);
+        if completions.is_empty() {
+            return false;
+        }
+        
+        // Take Tab away from egui so it doesn't move keyboard focus
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+            let selected = app.completion_state.selected(completions.len());
+            input_text.clone_from(&completions[selected].text);
+            app.completion_state.update(input_text);
+            app.display_container_state.request_cursor_at_end = true;
+            return true;
+        }
+        
+        let mut handled = false;
+        ctx.input(|i| {
+            if self.ctrl_pressed && self.shift_pressed {
+                // Ctrl+Shift+Up/Down fold tasks, see `handle_input`
+            } else if self.ctrl_pressed && i.key_pressed(egui::Key::ArrowDown) {
+                app.completion_state.select_next(completions.len());
+                handled = true;
+            } else if self.ctrl_pressed && i.key_pressed(egui::Key::ArrowUp) {
+                app.completion_state.select_previous(completions.len());
+                handled = true;
+            }
+        });
+        
+        handled
+    }
+    
     /// Handle keyboard input for task creation or editing
     pub fn handle_input(&mut self, app: &mut App, ctx: &egui::Context, input_text: &mut String) -> bool {
         let mut handled = false;
@@ -66,6 +245,17 @@ impl KeyHandler {
         // Check if we should query the API based on input changes and rate limiting
         genius_feed::maybe_query_api(app, input_text);
         
+        // Tab and Shift+Tab indent and outdent tasks; take them away from egui
+        // so they don't move keyboard focus
+        if app.display_container_state.focused_task_id().is_some() {
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::SHIFT, egui::Key::Tab)) {
+                return run_on_focused_tasks(app, input_text, Command::Outdent);
+            }
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+                return run_on_focused_tasks(app, input_text, Command::Indent);
+            }
+        }
+        
         // Check for key presses
         ctx.input(|i| {
             // TODO: Tab key navigation is problematic in egui and causes focus issues.
@@ -79,9 +269,46 @@ impl KeyHandler {
                 return;
             }
             
+            // Ctrl+S selects or deselects the focused task, Escape clears the selection
+            if self.ctrl_pressed && i.key_pressed(egui::Key::S) {
+                if let Some(task_id) = app.display_container_state.focused_task_id() {
+                    app.display_container_state.toggle_selection(task_id);
+                    handled = true;
+                }
+            }
+            // Ctrl+N opens the notes of the focused task, or closes the notes pane
+            if self.ctrl_pressed && i.key_pressed(egui::Key::N) {
+                let state = &mut app.display_container_state;
+                state.notes_task = state.focused_task_id().filter(|&id| state.notes_task != Some(id));
+                handled = true;
+            }
+            if i.key_pressed(egui::Key::Escape) && !app.display_container_state.selected_tasks.is_empty() {
+                app.display_container_state.clear_selection();
+                app.log_activity("Selection cleared".to_string());
+                handled = true;
+            }
+            
             // Handle Enter key with modifiers
             if i.key_pressed(egui::Key::Enter) {
-                if self.ctrl_pressed {
+                if self.ctrl_pressed && !app.display_container_state.selected_tasks.is_empty() {
+                    // Complete the selected tasks (Ctrl+Enter with a selection)
+                    let focused_task_id = app.display_container_state.focused_task_id();
+                    if let Some(task_id) = focused_task_id {
+                        if !input_text.is_empty() {
+                            execute_command(app, Some(Command::Edit(task_id, input_text.clone())));
+                        }
+                    }
+                    
+                    // Stay near the focused task unless it goes away with the selection
+                    let state = &app.display_container_state;
+                    let nearest_task_id = focused_task_id
+                        .and_then(|id| if state.is_selected(id) { state.find_nearest_task_at_same_level(&app.tasks, id) } else { Some(id) })
+                        .filter(|&id| !state.is_selected(id));
+                    
+                    execute_command(app, Some(Command::Complete(vec![Target::Selection])));
+                    app.display_container_state.focus_task_and_update_input(nearest_task_id, &app.tasks);
+                    *input_text = app.display_container_state.input_value().to_string();
+                } else if self.ctrl_pressed {
                     // Toggle task status (Ctrl+Enter)
                     if let Some(index) = app.display_container_state.focused_index {
                         if index > 0 && (index - 1) < app.display_container_state.display_to_id.len() {
@@ -127,7 +354,7 @@ impl KeyHandler {
                             app.display_container_state.original_focus = Some(index);
                             
                             // Create an empty subtask
-                            let subtask_id = execute_add_subtask(app, &parent_id.to_string(), "");
+                            let subtask_id = execute_add_subtask_by_id(app, parent_id, "");
                             
                             // Make sure the parent task is expanded
                             if !app.display_container_state.is_task_expanded(parent_id) {
@@ -153,8 +380,14 @@ impl KeyHandler {
                         // Check if we're focused on a task or the input line
                         match app.display_container_state.focused_index {
                             Some(0) | None => {
-                                // On input line - parse and execute the command
-                                let command = parse_command(input.clone());
+                                // On input line - parse, remember and execute the command
+                                // A mistyped command only becomes a task on a second Enter
+                                let Some(command) = command_for_enter(app, &input) else {
+                                    handled = true;
+                                    return;
+                                };
+                                
+                                record_input(app, AppMode::Pkm, &input);
                                 execute_command(app, Some(command));
                                 
                                 // Explicitly refocus on the input line after command execution
@@ -208,8 +441,46 @@ impl KeyHandler {
                 handled = true;
             }
             
-            // Handle navigation keys - only if Ctrl is not pressed
-            if !self.ctrl_pressed {
+            // Shift+Up/Down extends the selection from the focused task
+            if self.shift_pressed && !self.ctrl_pressed && !self.alt_pressed
+                && (i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::ArrowDown))
+            {
+                let forward = i.key_pressed(egui::Key::ArrowDown);
+                if let Some(task_id) = app.display_container_state.extend_selection(forward) {
+                    if app.display_container_state.focus_task_and_update_input(Some(task_id), &app.tasks) {
+                        *input_text = app.display_container_state.input_value().to_string();
+                    }
+                }
+                handled = true;
+            }
+            
+            // Alt+Up/Down move tasks among their siblings
+            // (on the input line they recall history, see `handle_history_keys`)
+            if self.alt_pressed && !self.ctrl_pressed {
+                if i.key_pressed(egui::Key::ArrowUp) {
+                    handled = run_on_focused_tasks(app, input_text, Command::MoveUp);
+                } else if i.key_pressed(egui::Key::ArrowDown) {
+                    handled = run_on_focused_tasks(app, input_text, Command::MoveDown);
+                }
+            }
+            
+            // Alt+Right zooms into the focused task, Alt+Left zooms back out
+            if self.alt_pressed && !self.ctrl_pressed && !self.shift_pressed {
+                if i.key_pressed(egui::Key::ArrowRight) {
+                    if let Some(task_id) = app.display_container_state.focused_task_id() {
+                        execute_zoom_by_id_command(app, Some(task_id));
+                        *input_text = app.display_container_state.input_value().to_string();
+                        handled = true;
+                    }
+                } else if i.key_pressed(egui::Key::ArrowLeft) && app.display_container_state.zoom.is_some() {
+                    execute_zoom_out_command(app);
+                    *input_text = app.display_container_state.input_value().to_string();
+                    handled = true;
+                }
+            }
+            
+            // Handle navigation keys - only if no modifier is pressed
+            if !self.ctrl_pressed && !self.shift_pressed && !self.alt_pressed {
                 if i.key_pressed(egui::Key::ArrowUp) {
                     if let Some(index) = app.display_container_state.focused_index {
                         if index > 0 {
@@ -263,8 +534,31 @@ impl KeyHandler {
                 }
             }
             
+            // Ctrl+Shift+Down/Up expand or collapse the focused subtree,
+            // or every task when on the input line
+            if self.ctrl_pressed && self.shift_pressed
+                && (i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::ArrowDown))
+            {
+                let expanded = i.key_pressed(egui::Key::ArrowDown);
+                let command: fn(Vec<Target>) -> Command = if expanded { Command::Expand } else { Command::Collapse };
+                if !run_on_focused_tasks(app, input_text, command) {
+                    execute_command(app, Some(if expanded { Command::ExpandAll } else { Command::CollapseAll }));
+                    *input_text = app.display_container_state.input_value().to_string();
+                }
+                handled = true;
+            }
+
+            // Ctrl+1 to Ctrl+9 show that many levels of tasks
+            if self.ctrl_pressed && !self.shift_pressed && !self.alt_pressed {
+                if let Some(depth) = DEPTH_KEYS.iter().position(|key| i.key_pressed(*key)) {
+                    execute_command(app, Some(Command::ExpandToDepth(depth + 1)));
+                    *input_text = app.display_container_state.input_value().to_string();
+                    handled = true;
+                }
+            }
+
             // Handle task expansion/collapse with Ctrl+Up/Down
-            if self.ctrl_pressed {
+            if self.ctrl_pressed && !self.shift_pressed {
                 // Prevent regular arrow key handling when Ctrl is pressed
                 if i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::ArrowDown) {
                     if let Some(index) = app.display_container_state.focused_index {
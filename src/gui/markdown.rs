@@ -0,0 +1,317 @@
+//! Minimal markdown rendering for Genius cards and task notes.
+//!
+//! Card bodies use a small subset of markdown: headings, paragraphs, bullet and
+//! numbered lists, block quotes, fenced code, and inline bold, italic, code and
+//! links, including `[[...]]` links to tasks.
+//!
+//! The text is parsed into blocks first, which keeps the parser testable
+//! without egui, and then laid out with wrapping labels.
+
+use eframe::egui;
+
+/// Inline text with its styling
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    /// Target of a link, if the span is one
+    pub url: Option<String>,
+    /// Text of a `[[...]]` link to a task, if the span is one
+    pub task_link: Option<String>,
+}
+
+/// A block of markdown content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// Heading with its level (1-6)
+    Heading(usize, Vec<Span>),
+    /// Paragraph of wrapped text
+    Paragraph(Vec<Span>),
+    /// List item with its marker ("•" or "1.")
+    ListItem(String, Vec<Span>),
+    /// Quoted text
+    Quote(Vec<Span>),
+    /// Fenced code block, rendered verbatim
+    Code(String),
+}
+
+/// Parse markdown text into blocks
+pub fn parse_blocks(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    // Finish the paragraph collected so far
+    fn flush(paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>) {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(parse_spans(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    }
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            match code.take() {
+                Some(lines) => blocks.push(Block::Code(lines.join("\n"))),
+                None => {
+                    flush(&mut paragraph, &mut blocks);
+                    code = Some(Vec::new());
+                }
+            }
+            continue;
+        }
+        if let Some(lines) = code.as_mut() {
+            lines.push(line);
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some((level, heading)) = parse_heading(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(level, parse_spans(heading)));
+        } else if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|m| trimmed.strip_prefix(m)) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::ListItem("•".to_string(), parse_spans(item)));
+        } else if let Some((number, item)) = parse_numbered_item(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::ListItem(format!("{number}."), parse_spans(item)));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Quote(parse_spans(quote.trim())));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+
+    // An unterminated code fence still shows its content
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines.join("\n")));
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/// Parse `# Heading` into its level and text
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, text.trim()))
+}
+
+/// Parse `1. Item` into its number and text
+fn parse_numbered_item(line: &str) -> Option<(&str, &str)> {
+    let (number, text) = line.split_once(". ")?;
+    (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit())).then_some((number, text))
+}
+
+/// Parse inline styling: `**bold**`, `*italic*`/`_italic_`, `` `code` ``, `[text](url)`
+/// and `[[task]]`
+pub fn parse_spans(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut current = Span::default();
+    let mut rest = text;
+
+    // Finish the span collected so far and start one with the new style
+    fn push(spans: &mut Vec<Span>, current: &mut Span) {
+        if !current.text.is_empty() {
+            spans.push(current.clone());
+        }
+        current.text.clear();
+    }
+
+    while let Some(c) = rest.chars().next() {
+        // `_` only starts emphasis at a word boundary, so snake_case stays intact
+        let at_word_start = text[..text.len() - rest.len()]
+            .chars()
+            .next_back()
+            .is_none_or(|prev| !prev.is_alphanumeric());
+
+        if current.code && c != '`' {
+            current.text.push(c);
+            rest = &rest[c.len_utf8()..];
+        } else if let Some(after) = rest.strip_prefix("**") {
+            push(&mut spans, &mut current);
+            current.bold = !current.bold;
+            rest = after;
+        } else if c == '*' || (c == '_' && (current.italic || at_word_start)) {
+            push(&mut spans, &mut current);
+            current.italic = !current.italic;
+            rest = &rest[1..];
+        } else if c == '`' {
+            push(&mut spans, &mut current);
+            current.code = !current.code;
+            rest = &rest[1..];
+        } else if let Some((target, after)) = rest.strip_prefix("[[").and_then(|r| r.split_once("]]")) {
+            push(&mut spans, &mut current);
+            spans.push(Span {
+                text: target.to_string(),
+                task_link: Some(target.to_string()),
+                ..current.clone()
+            });
+            rest = after;
+        } else if let Some((label, url, after)) = parse_link(rest) {
+            push(&mut spans, &mut current);
+            spans.push(Span {
+                text: label.to_string(),
+                url: Some(url.to_string()),
+                ..current.clone()
+            });
+            rest = after;
+        } else {
+            current.text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    push(&mut spans, &mut current);
+    spans
+}
+
+/// Parse `[label](url)` at the start of the text
+fn parse_link(text: &str) -> Option<(&str, &str, &str)> {
+    let inner = text.strip_prefix('[')?;
+    let (label, after_label) = inner.split_once("](")?;
+    let (url, after) = after_label.split_once(')')?;
+    (!label.contains('[') && !url.contains(' ')).then_some((label, url, after))
+}
+
+/// Render markdown text into the UI
+///
+/// Returns the text of the `[[...]]` task link that was clicked, if any.
+pub fn render_markdown(ui: &mut egui::Ui, text: &str, text_color: egui::Color32) -> Option<String> {
+    let mut clicked = None;
+    for block in parse_blocks(text) {
+        let clicked_in_block = match block {
+            Block::Heading(level, spans) => {
+                let size = if level <= 2 { 16.0 } else { 14.0 };
+                render_spans(ui, &spans, text_color, |text| text.size(size).strong())
+            }
+            Block::Paragraph(spans) => {
+                render_spans(ui, &spans, text_color, |text| text)
+            }
+            Block::ListItem(marker, spans) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(egui::RichText::new(format!("{marker} ")).color(text_color));
+                    render_inline(ui, &spans, text_color, |text| text)
+                }).inner
+            }
+            Block::Quote(spans) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(egui::RichText::new("│ ").color(text_color).weak());
+                    render_inline(ui, &spans, text_color, |text| text.italics())
+                }).inner
+            }
+            Block::Code(code) => {
+                ui.add(egui::Label::new(egui::RichText::new(code).code().color(text_color)).wrap(true));
+                None
+            }
+        };
+        clicked = clicked.or(clicked_in_block);
+    }
+    clicked
+}
+
+/// Render a block of spans on wrapping lines
+fn render_spans(
+    ui: &mut egui::Ui,
+    spans: &[Span],
+    text_color: egui::Color32,
+    style: impl Fn(egui::RichText) -> egui::RichText,
+) -> Option<String> {
+    ui.horizontal_wrapped(|ui| render_inline(ui, spans, text_color, style)).inner
+}
+
+/// Render spans into the current (wrapping) layout, returning the task link
+/// that was clicked
+fn render_inline(
+    ui: &mut egui::Ui,
+    spans: &[Span],
+    text_color: egui::Color32,
+    style: impl Fn(egui::RichText) -> egui::RichText,
+) -> Option<String> {
+    let mut clicked = None;
+    ui.spacing_mut().item_spacing.x = 0.0;
+    for span in spans {
+        let mut text = style(egui::RichText::new(&span.text).color(text_color));
+        if span.bold {
+            text = text.strong();
+        }
+        if span.italic {
+            text = text.italics();
+        }
+        if span.code {
+            text = text.code();
+        }
+        if let Some(task_link) = &span.task_link {
+            if ui.link(text).clicked() {
+                clicked = Some(task_link.clone());
+            }
+            continue;
+        }
+        match &span.url {
+            Some(url) => {
+                ui.hyperlink_to(text.underline(), url);
+            }
+            None => {
+                ui.label(text);
+            }
+        }
+    }
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str) -> Span {
+        Span {
+            text: text.to_string(),
+            ..Span::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let blocks = parse_blocks("# Title\nFirst line\ncontinues\n\n- one\n2. two\n> quoted");
+
+        assert_eq!(blocks[0], Block::Heading(1, vec![plain("Title")]));
+        assert_eq!(blocks[1], Block::Paragraph(vec![plain("First line continues")]));
+        assert_eq!(blocks[2], Block::ListItem("•".to_string(), vec![plain("one")]));
+        assert_eq!(blocks[3], Block::ListItem("2.".to_string(), vec![plain("two")]));
+        assert_eq!(blocks[4], Block::Quote(vec![plain("quoted")]));
+    }
+
+    #[test]
+    fn test_parse_code_block_verbatim() {
+        let blocks = parse_blocks("```\nlet x = *y;\n```");
+        assert_eq!(blocks, vec![Block::Code("let x = *y;".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_inline_styles() {
+        let spans = parse_spans("a **bold** and *it* `co*de` [link](https://example.com) snake_case");
+
+        assert_eq!(spans[0], plain("a "));
+        assert!(spans[1].bold && spans[1].text == "bold");
+        assert!(spans[3].italic && spans[3].text == "it");
+        assert!(spans[5].code && spans[5].text == "co*de");
+        assert_eq!(spans[7].text, "link");
+        assert_eq!(spans[7].url.as_deref(), Some("https://example.com"));
+        assert_eq!(spans[8], plain(" snake_case"));
+    }
+
+    #[test]
+    fn test_parse_task_links() {
+        let spans = parse_spans("see [[Plan trip]] and [x](y)");
+
+        assert_eq!(spans[1].text, "Plan trip");
+        assert_eq!(spans[1].task_link.as_deref(), Some("Plan trip"));
+        assert_eq!(spans[2], plain(" and "));
+        assert!(spans[3].task_link.is_none() && spans[3].url.is_some());
+    }
+}
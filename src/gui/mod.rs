@@ -5,6 +5,7 @@
 //! minimalist interface design.
 
 mod keyhandler;
+mod markdown;
 mod rendering;
 pub mod genius_feed;
 
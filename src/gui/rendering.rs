@@ -3,18 +3,70 @@
 //! This module handles the rendering of the task manager interface using egui.
 //! It provides a minimalist interface similar to the previous TUI implementation.
 
+use std::collections::HashMap;
+use std::ops::Range;
+
 use eframe::egui;
 use crate::App;
+use crate::command_parser::Target;
 use crate::display_container::TaskIndex;
+use crate::links::{self, LinkTarget};
 use crate::taskstore::{Task, TaskStatus};
+use crate::taskstore::operations::{self, Placement, Progress};
 use crate::gui::keyhandler::KeyHandler;
 use crate::genius_platform::GeniusApiBridge;
 use crate::genius_platform::genius_keyhandler::GeniusKeyHandler;
-use crate::commands::AppMode;
+use crate::commands::{AppMode, Command, execute_command, parse_command};
+use crate::completion;
 
 /// The primary accent color used throughout the UI
 const ACCENT_COLOR: egui::Color32 = egui::Color32::from_rgb(57, 255, 20);
 
+/// Background of selected tasks, a dark shade of the accent color
+const SELECTION_COLOR: egui::Color32 = egui::Color32::from_rgb(20, 90, 7);
+
+/// Width of one nesting level in the task list
+const INDENT_WIDTH: f32 = 20.0;
+
+/// Drag-and-drop payload of a task dragged in the task list
+struct DraggedTask(u32);
+
+/// ID of the text editor in the notes pane
+const NOTES_EDITOR_ID: &str = "notes_editor";
+
+/// Notes of a task being edited in the notes pane
+struct NotesPane {
+    task_id: u32,
+    /// The notes as edited so far, saved when the pane closes
+    text: String,
+    /// Whether the notes are shown as rendered markdown instead of edited
+    preview: bool,
+    /// Whether the editor still has to take the keyboard focus
+    request_focus: bool,
+}
+
+/// Part of a task's text: plain text, or a link to another task with its
+/// text and the ID of the linked task
+enum TextSegment {
+    Text(String),
+    Link(String, u32),
+}
+
+/// A rendered task, kept to handle mouse interactions once all tasks are drawn
+struct TaskRow {
+    task_id: u32,
+    task_index: TaskIndex,
+    depth: usize,
+    /// The whole row, which tasks can be dropped on
+    row: egui::Response,
+    /// The task text, which can be clicked and dragged, or its inline editor
+    label: egui::Response,
+    /// Whether the checkbox was ticked this frame
+    checked: bool,
+    /// Task of the link in the text that was clicked this frame
+    link_clicked: Option<u32>,
+}
+
 /// Run the application with egui
 pub fn run_app(app: App) -> Result<(), eframe::Error> {
     let options = eframe::NativeOptions {
@@ -59,6 +111,12 @@ struct GuiApp {
     key_handler: KeyHandler,
     /// Genius Feed key handler for feed mode input processing
     genius_key_handler: GeniusKeyHandler,
+    /// Task being edited in place in the task list, and its new content
+    inline_edit: Option<(u32, String)>,
+    /// Notes pane of the task in `DisplayContainerState::notes_task`
+    notes_pane: Option<NotesPane>,
+    /// Progress of the tasks with subtasks, computed once per frame
+    progress: HashMap<u32, Progress>,
 }
 
 impl GuiApp {
@@ -68,18 +126,18 @@ impl GuiApp {
             input_text: String::new(),
             key_handler: KeyHandler::new(),
             genius_key_handler: GeniusKeyHandler::new(),
+            inline_edit: None,
+            notes_pane: None,
+            progress: HashMap::new(),
         }
     }
     
-    /// Format a task for display, including indentation, index, status, etc.
-    fn format_task_text(&self, task: &Task, task_index: &TaskIndex, depth: usize) -> String {
+    /// Format a task for display, including index, status, etc.
+    ///
+    /// Also returns where the task's content is in the text.
+    fn format_task_text(&self, task: &Task, task_index: &TaskIndex, depth: usize) -> (String, Range<usize>) {
         let mut task_text = String::new();
         
-        // Add indentation
-        for _ in 0..depth {
-            task_text.push_str("  "); // Two spaces per level
-        }
-        
         // Add task index
         task_text.push_str(&format!("{}", task_index));
         
@@ -87,7 +145,7 @@ impl GuiApp {
         if depth == 0 {
             task_text.push_str(". ");
         } else {
-            task_text.push_str(" ");
+            task_text.push(' ');
         }
         
         // Note: We no longer add completion status indicator here since we'll use the Checkbox widget
@@ -105,11 +163,67 @@ impl GuiApp {
         }
         
         // Add task content
+        let content_start = task_text.len();
         task_text.push_str(&task.content);
-        task_text
+        let content = content_start..task_text.len();
+        
+        // Done subtasks at any depth, for tasks with subtasks
+        if let Some(progress) = self.progress.get(&task.id) {
+            task_text.push_str(&format!(" [{}/{}, {}%]", progress.done, progress.total, progress.percent()));
+        }
+        
+        for tag in &task.tags {
+            task_text.push_str(&format!(" #{tag}"));
+        }
+        
+        if let Some(due) = task.due {
+            task_text.push_str(&format!(" (due {due})"));
+        }
+        
+        if let Some(recurrence) = &task.recurrence {
+            task_text.push_str(&format!(" ({recurrence})"));
+        }
+        
+        if task.notes.is_some() {
+            task_text.push_str(" (notes)");
+        }
+        
+        // Private tasks are never sent to external providers
+        if task.private {
+            task_text.push_str(" (private)");
+        }
+        (task_text, content)
+    }
+    
+    /// Split a task's text into plain text and the links in its content
+    /// that point to existing tasks
+    fn split_links(&self, task_text: &str, content: Range<usize>) -> Vec<TextSegment> {
+        let content_start = content.start;
+        let mut segments = Vec::new();
+        let mut end = 0;
+        for link in links::parse_links(&task_text[content]) {
+            let Some(task_id) = link.target.resolve(&self.app.tasks) else {
+                continue;
+            };
+            let text = match &link.target {
+                LinkTarget::Title(title) => title.clone(),
+                LinkTarget::Id(_) => self.app.tasks.iter()
+                    .find(|t| t.id == task_id)
+                    .map(|t| t.content.clone())
+                    .unwrap_or_default(),
+            };
+            segments.push(TextSegment::Text(task_text[end..content_start + link.range.start].to_string()));
+            segments.push(TextSegment::Link(text, task_id));
+            end = content_start + link.range.end;
+        }
+        segments.push(TextSegment::Text(task_text[end..].to_string()));
+        segments
     }
     
     /// Render a single task (without handling interactions)
+    ///
+    /// With `edit_text`, the task's content is replaced by an editor for it.
+    #[allow(clippy::too_many_arguments)]
     fn render_single_task(
         &self,
         ui: &mut egui::Ui,
@@ -117,55 +231,273 @@ impl GuiApp {
         task_index: &TaskIndex,
         depth: usize,
         is_focused: bool,
-    ) -> (egui::Response, Option<u32>) {
-        let task_text = self.format_task_text(task, task_index, depth);
-        let task_id = task.id;
-        let mut task_to_complete = None;
+        is_selected: bool,
+        edit_text: Option<&mut String>,
+    ) -> TaskRow {
+        let (task_text, content) = self.format_task_text(task, task_index, depth);
+        let mut checked = false;
+        let mut link_clicked = None;
         
         // Only apply highlighting if we're in PKM mode
         let should_highlight = is_focused && matches!(self.app.app_mode, crate::commands::AppMode::Pkm);
         
         // Create a frame that will have the background color if focused
+        // Selected rows get a dimmed accent, and an outline when also focused
         let frame = if should_highlight {
-            egui::Frame::none()
+            let frame = egui::Frame::none()
                 .fill(ACCENT_COLOR)
+                .inner_margin(egui::style::Margin::symmetric(4.0, 0.0));
+            if is_selected {
+                frame.stroke(egui::Stroke::new(2.0, egui::Color32::WHITE))
+            } else {
+                frame
+            }
+        } else if is_selected {
+            egui::Frame::none()
+                .fill(SELECTION_COLOR)
                 .inner_margin(egui::style::Margin::symmetric(4.0, 0.0))
         } else {
             egui::Frame::none()
         };
         
         // Use the frame to create a container with the right background
-        let response = frame.show(ui, |ui| {
+        let inner = frame.show(ui, |ui| {
             ui.horizontal(|ui| {
+                // Subtasks are indented by their depth
+                ui.add_space(depth as f32 * INDENT_WIDTH);
+                
                 // Create a mutable copy of the task's status for the checkbox
                 let mut is_checked = task.status == TaskStatus::Done;
                 let checkbox_response = ui.checkbox(&mut is_checked, "");
                 
                 // If the checkbox was clicked and the status changed, mark for completion
-                if checkbox_response.changed() && is_checked {
-                    task_to_complete = Some(task_id);
+                checked = checkbox_response.changed() && is_checked;
+                
+                if let Some(edit_text) = edit_text {
+                    return ui.add(
+                        egui::TextEdit::singleline(edit_text)
+                            .desired_width(ui.available_width())
+                            .id(egui::Id::new("inline_task_editor"))
+                    );
                 }
                 
                 // Render the task text with the appropriate style and ensure it wraps
-                let text = if should_highlight {
-                    egui::RichText::new(task_text).color(egui::Color32::BLACK)
-                } else {
-                    egui::RichText::new(task_text)
+                let styled = |text: String| {
+                    if should_highlight {
+                        egui::RichText::new(text).color(egui::Color32::BLACK)
+                    } else {
+                        egui::RichText::new(text)
+                    }
                 };
                 
                 // Use a label with wrapping to ensure text stays within bounds
-                ui.add(
-                    egui::Label::new(text)
-                        .wrap(true) // Enable text wrapping
-                );
-            }).response
-        }).response;
+                // It senses clicks and drags, so it can't be used to select text
+                let label = |ui: &mut egui::Ui, text: String| {
+                    ui.add(
+                        egui::Label::new(styled(text))
+                            .wrap(true) // Enable text wrapping
+                            .selectable(false)
+                            .sense(egui::Sense::click_and_drag())
+                    )
+                };
+                
+                let mut segments = self.split_links(&task_text, content).into_iter();
+                if segments.len() == 1 {
+                    return label(ui, task_text);
+                }
+                
+                // Links to other tasks sit between the labels of the rest of the text
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    let Some(TextSegment::Text(first)) = segments.next() else {
+                        unreachable!("the text starts with the task index");
+                    };
+                    let mut response = label(ui, first);
+                    for segment in segments {
+                        match segment {
+                            TextSegment::Text(text) => response = response.union(label(ui, text)),
+                            TextSegment::Link(text, task_id) => {
+                                if ui.link(styled(text).underline()).clicked() {
+                                    link_clicked = Some(task_id);
+                                }
+                            }
+                        }
+                    }
+                    response
+                }).inner
+            }).inner
+        });
+        
+        TaskRow {
+            task_id: task.id,
+            task_index: task_index.clone(),
+            depth,
+            row: inner.response,
+            label: inner.inner,
+            checked,
+            link_clicked,
+        }
+    }
+    
+    /// Where a task dropped on a row goes, with the depth and height to draw
+    /// the drop indicator at
+    ///
+    /// The upper half of a row drops before its task and the lower half after
+    /// it, or under it when the pointer is right of the task's text. Returns
+    /// None for drops on the dragged task or its subtasks.
+    fn drop_placement(&self, row: &TaskRow, dragged_id: u32, pointer: egui::Pos2) -> Option<(Placement, usize, f32)> {
+        let task = self.app.tasks.iter().find(|t| t.id == row.task_id)?;
+        let rect = row.row.rect;
+        // The first subtask of an expanded task is shown right below it
+        let first_child = task.child_ids.first().filter(|_| self.app.display_container_state.is_task_expanded(task.id));
+        let nested = pointer.x > rect.left() + (row.depth + 2) as f32 * INDENT_WIDTH;
+        
+        let (placement, depth, y) = if pointer.y < rect.center().y {
+            (Placement::Before(task.id), row.depth, rect.top())
+        } else if let Some(&child_id) = first_child {
+            (Placement::Before(child_id), row.depth + 1, rect.bottom())
+        } else if nested {
+            (Placement::LastChildOf(task.id), row.depth + 1, rect.bottom())
+        } else {
+            (Placement::After(task.id), row.depth, rect.bottom())
+        };
         
-        (response, task_to_complete)
+        let (Placement::LastChildOf(anchor_id) | Placement::Before(anchor_id) | Placement::After(anchor_id)) = placement;
+        (!operations::is_ancestor(&self.app.tasks, dragged_id, anchor_id)).then_some((placement, depth, y))
+    }
+    
+    /// Show the context menu of a task, returning the command that was chosen
+    fn task_context_menu(ui: &mut egui::Ui, targets: &[Target]) -> Option<Command> {
+        let mut command = None;
+        if ui.button("Complete").clicked() {
+            command = Some(Command::Complete(targets.to_vec()));
+        }
+        ui.menu_button("Move to", |ui| {
+            if ui.button("Taskpad").clicked() {
+                command = Some(Command::MoveToTaskpad(targets.to_vec()));
+            }
+            if ui.button("Backburner").clicked() {
+                command = Some(Command::MoveToBackburner(targets.to_vec()));
+            }
+            if ui.button("Shelved").clicked() {
+                command = Some(Command::MoveToShelved(targets.to_vec()));
+            }
+        });
+        if ui.button("Indent").clicked() {
+            command = Some(Command::Indent(targets.to_vec()));
+        }
+        if ui.button("Outdent").clicked() {
+            command = Some(Command::Outdent(targets.to_vec()));
+        }
+        ui.separator();
+        if ui.button("Delete").clicked() {
+            command = Some(Command::Delete(targets.to_vec()));
+        }
+        if command.is_some() {
+            ui.close_menu();
+        }
+        command
+    }
+    
+    /// Handle clicks, drags, drops and context menus on the rendered tasks
+    ///
+    /// Changes to tasks run as commands, which save them like typed commands do.
+    fn handle_task_interactions(&mut self, ui: &egui::Ui, rows: &[TaskRow]) {
+        // A task that disappeared can't be edited anymore
+        if self.inline_edit.as_ref().is_some_and(|(id, _)| !rows.iter().any(|row| row.task_id == *id)) {
+            self.inline_edit = None;
+        }
+        let mut command = None;
+        let mut focus = None;
+        let mut linked = None;
+        // Commands act on the selection when the task is part of it
+        let targets_of = |task_id: u32| -> Vec<Target> {
+            if self.app.display_container_state.is_selected(task_id) {
+                vec![Target::Selection]
+            } else {
+                rows.iter()
+                    .find(|row| row.task_id == task_id)
+                    .map(|row| vec![Target::Index(row.task_index.clone())])
+                    .unwrap_or_default()
+            }
+        };
+        
+        for row in rows {
+            let task_id = row.task_id;
+            
+            // The inline editor saves when it loses focus, unless Escape was pressed
+            if self.inline_edit.as_ref().is_some_and(|(id, _)| *id == task_id) {
+                if row.label.lost_focus() {
+                    if let Some((_, content)) = self.inline_edit.take() {
+                        if !ui.input(|i| i.key_pressed(egui::Key::Escape)) && !content.trim().is_empty() {
+                            command = Some(Command::Edit(task_id, content.trim().to_string()));
+                        }
+                    }
+                    focus = Some(task_id);
+                } else {
+                    row.label.request_focus();
+                }
+                continue;
+            }
+            
+            if row.checked {
+                command = Some(Command::CompleteById(task_id));
+            }
+            if row.link_clicked.is_some() {
+                linked = row.link_clicked;
+            }
+            if row.label.clicked() || row.label.secondary_clicked() {
+                focus = Some(task_id);
+            }
+            if row.label.double_clicked() {
+                let content = self.app.tasks.iter().find(|t| t.id == task_id).map(|t| t.content.clone());
+                self.inline_edit = content.map(|content| (task_id, content));
+            }
+            
+            row.label.dnd_set_drag_payload(DraggedTask(task_id));
+            let targets = targets_of(task_id);
+            row.label.context_menu(|ui| {
+                if let Some(chosen) = Self::task_context_menu(ui, &targets) {
+                    command = Some(chosen);
+                }
+            });
+            
+            // Show where a dragged task would go, and move it there when dropped
+            let Some(dragged) = row.row.dnd_hover_payload::<DraggedTask>() else {
+                continue;
+            };
+            let Some(pointer) = ui.input(|i| i.pointer.hover_pos()) else {
+                continue;
+            };
+            if let Some((placement, depth, y)) = self.drop_placement(row, dragged.0, pointer) {
+                let left = row.row.rect.left() + depth as f32 * INDENT_WIDTH;
+                ui.painter().hline(left..=row.row.rect.right(), y, egui::Stroke::new(2.0, ACCENT_COLOR));
+                if row.row.dnd_release_payload::<DraggedTask>().is_some() {
+                    command = Some(Command::Place(targets_of(dragged.0), placement));
+                    focus = Some(dragged.0);
+                }
+            }
+        }
+        
+        if command.is_some() {
+            execute_command(&mut self.app, command);
+        }
+        if let Some(task_id) = linked {
+            self.focus_linked_task(task_id);
+        } else if let Some(task_id) = focus {
+            self.app.display_container_state.focus_task_and_update_input(Some(task_id), &self.app.tasks);
+            self.input_text = self.app.display_container_state.input_value().to_string();
+            // The inline editor keeps the keyboard until it's done
+            if self.inline_edit.is_some() {
+                self.app.display_container_state.request_focus_next_frame = false;
+            }
+        }
     }
     
     /// Render the task list
     fn render_tasks(&mut self, ui: &mut egui::Ui) {
+        self.progress = operations::progress_by_task(&self.app.tasks);
+        
         // Use accent color for the scrollable area
         egui::ScrollArea::vertical()
             .show(ui, |ui| {
@@ -175,7 +507,32 @@ impl GuiApp {
                     .show(ui, |ui| {
                         let container_name = self.app.display_container_state.active_container.display_name();
                         let capitalized_name = container_name.chars().next().unwrap_or_default().to_uppercase().collect::<String>() + &container_name[1..];
-                        ui.heading(capitalized_name);
+                        let sort_mode = self.app.display_container_state.sort_mode();
+                        let zoom_path = self.app.display_container_state.zoom_path(&self.app.tasks);
+                        if let Some((zoomed, ancestors)) = zoom_path.split_last() {
+                            // Breadcrumbs lead back to the ancestors and the whole container
+                            let mut zoom_to = None;
+                            ui.horizontal_wrapped(|ui| {
+                                if ui.link(&capitalized_name).clicked() {
+                                    zoom_to = Some(None);
+                                }
+                                for ancestor in ancestors {
+                                    ui.label("›");
+                                    if ui.link(&ancestor.content).clicked() {
+                                        zoom_to = Some(Some(ancestor.id));
+                                    }
+                                }
+                            });
+                            ui.heading(&zoomed.content);
+                            if let Some(task_id) = zoom_to {
+                                crate::commands::execute_zoom_by_id_command(&mut self.app, task_id);
+                                self.input_text = self.app.display_container_state.input_value().to_string();
+                            }
+                        } else if sort_mode == crate::sorting::SortMode::Manual {
+                            ui.heading(capitalized_name);
+                        } else {
+                            ui.heading(format!("{capitalized_name} (by {sort_mode})"));
+                        }
                     });
                 
                 ui.separator();
@@ -211,12 +568,11 @@ impl GuiApp {
                         // Collect all tasks and their metadata before rendering
                         // This avoids borrowing issues during the recursive rendering
                         let mut task_data = Vec::new();
-                        let active_container = self.app.display_container_state.active_container;
                         
-                        // First collect top-level tasks
-                        for (idx, task) in self.app.tasks
-                            .iter()
-                            .filter(|t| t.container == active_container && t.parent_id.is_none())
+                        // First collect top-level tasks, in the order of the container's sort mode
+                        for (idx, task) in self.app.display_container_state
+                            .top_level_tasks(&self.app.tasks)
+                            .into_iter()
                             .enumerate()
                         {
                             let mut current_index = vec![idx + 1]; // 1-based index
@@ -227,54 +583,34 @@ impl GuiApp {
                             );
                         }
                         
-                        // Now render all tasks and collect responses
-                        let mut display_index = 1; // Start at 1 because 0 is "Create new task"
-                        let mut all_responses = Vec::new();
+                        // Now render all tasks, handling interactions once all are drawn
+                        let mut rows = Vec::new();
+                        let mut inline_edit = self.inline_edit.take();
                         
                         // We need to handle each task one at a time to avoid multiple mutable borrows
-                        for (task_id, task_index, depth) in &task_data {
+                        // Display indices start at 1 because 0 is "Create new task"
+                        for (display_index, (task_id, task_index, depth)) in (1..).zip(task_data.iter()) {
                             let task = self.app.tasks.iter().find(|t| t.id == *task_id).unwrap();
                             let is_focused = focused_index == Some(display_index);
+                            let is_selected = self.app.display_container_state.is_selected(*task_id);
+                            let edit_text = inline_edit
+                                .as_mut()
+                                .filter(|(id, _)| id == task_id)
+                                .map(|(_, text)| text);
                             
-                            // Render the task and get the response
-                            let (response, task_to_complete) = self.render_single_task(
+                            rows.push(self.render_single_task(
                                 ui,
                                 task,
                                 task_index,
                                 *depth,
                                 is_focused,
-                            );
-                            
-                            all_responses.push((response, task_to_complete, display_index));
-                            display_index += 1;
+                                is_selected,
+                                edit_text,
+                            ));
                         }
                         
-                        // Handle UI interactions after rendering is complete
-                        for (response, task_to_complete, display_idx) in all_responses {
-                            // Handle click to select task
-                            if response.clicked() {
-                                self.app.display_container_state.focused_index = Some(display_idx);
-                                
-                                // Find the task to update input text
-                                if let Some(task) = self.app.tasks.iter().find(|t| t.id == task_to_complete.unwrap_or(0)) {
-                                    self.input_text = task.content.clone();
-                                }
-                            }
-                            
-                            // Handle double-click to toggle expansion
-                            if response.double_clicked() {
-                                if let Some(task) = self.app.tasks.iter().find(|t| t.id == task_to_complete.unwrap_or(0)) {
-                                    if !task.child_ids.is_empty() {
-                                        self.app.display_container_state.toggle_task_expansion(task.id, &self.app.tasks);
-                                    }
-                                }
-                            }
-                            
-                            // Handle task completion
-                            if let Some(task_id) = task_to_complete {
-                                crate::commands::execute_complete_by_id_command(&mut self.app, task_id);
-                            }
-                        }
+                        self.inline_edit = inline_edit;
+                        self.handle_task_interactions(ui, &rows);
                     });
             });
     }
@@ -307,6 +643,147 @@ impl GuiApp {
         }
     }
     
+    /// Focus the task a link points to, wherever it is
+    fn focus_linked_task(&mut self, task_id: u32) {
+        crate::commands::execute_focus_by_id_command(&mut self.app, task_id);
+        self.input_text = self.app.display_container_state.input_value().to_string();
+    }
+    
+    /// Render the tasks that link to the focused task
+    fn render_backlinks(&mut self, ui: &mut egui::Ui) {
+        if self.app.app_mode != AppMode::Pkm {
+            return;
+        }
+        let Some(task_id) = self.app.display_container_state.focused_task_id() else {
+            return;
+        };
+        let backlinks = links::backlinks(&self.app.tasks, task_id);
+        if backlinks.is_empty() {
+            return;
+        }
+        
+        let mut clicked = None;
+        egui::Frame::none()
+            .inner_margin(egui::style::Margin::symmetric(8.0, 4.0))
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new("Linked from:").color(ACCENT_COLOR));
+                for backlink_id in backlinks {
+                    let Some(task) = self.app.tasks.iter().find(|t| t.id == backlink_id) else {
+                        continue;
+                    };
+                    if ui.link(&task.content).clicked() {
+                        clicked = Some(backlink_id);
+                    }
+                }
+            });
+        if let Some(task_id) = clicked {
+            self.focus_linked_task(task_id);
+        }
+    }
+    
+    /// Save the notes in the notes pane if they were changed
+    fn save_notes(&mut self) {
+        let Some(pane) = self.notes_pane.as_ref() else {
+            return;
+        };
+        let task = self.app.tasks.iter().find(|t| t.id == pane.task_id);
+        if task.is_some_and(|t| t.notes.as_deref().unwrap_or_default() != pane.text.trim_end()) {
+            let command = Command::EditNotes(pane.task_id, pane.text.clone());
+            execute_command(&mut self.app, Some(command));
+        }
+    }
+    
+    /// Open, switch or close the notes pane to follow `notes_task`
+    ///
+    /// Notes that were changed are saved when their pane closes.
+    fn sync_notes_pane(&mut self) {
+        let wanted = self.app.display_container_state.notes_task;
+        if self.notes_pane.as_ref().map(|pane| pane.task_id) == wanted {
+            return;
+        }
+        
+        self.save_notes();
+        let task = wanted.and_then(|id| self.app.tasks.iter().find(|t| t.id == id));
+        self.notes_pane = task.map(|task| NotesPane {
+            task_id: task.id,
+            text: task.notes.clone().unwrap_or_default(),
+            preview: false,
+            request_focus: true,
+        });
+        if self.notes_pane.is_none() {
+            self.app.display_container_state.notes_task = None;
+        }
+    }
+    
+    /// Render the notes pane of a task, a markdown editor with a preview
+    fn render_notes_pane(&mut self, ui: &mut egui::Ui, editing: bool) {
+        let Some(pane) = self.notes_pane.as_mut() else {
+            return;
+        };
+        let content = self.app.tasks.iter()
+            .find(|t| t.id == pane.task_id)
+            .map(|t| t.content.clone())
+            .unwrap_or_default();
+        let mut close = false;
+        let mut save = false;
+        let mut linked = None;
+        
+        ui.horizontal(|ui| {
+            ui.heading("Notes");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                close = ui.button("Close").clicked();
+                let label = if pane.preview { "Edit" } else { "Preview" };
+                if ui.button(label).clicked() {
+                    pane.preview = !pane.preview;
+                    pane.request_focus = !pane.preview;
+                }
+            });
+        });
+        ui.label(egui::RichText::new(content).color(ACCENT_COLOR));
+        ui.separator();
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if pane.preview {
+                let clicked = crate::gui::markdown::render_markdown(ui, &pane.text, ui.visuals().text_color());
+                linked = clicked.and_then(|link| LinkTarget::parse(&link)?.resolve(&self.app.tasks));
+                return;
+            }
+            let response = ui.add(
+                egui::TextEdit::multiline(&mut pane.text)
+                    .id(egui::Id::new(NOTES_EDITOR_ID))
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY)
+                    .desired_rows(20)
+                    .hint_text("Notes in markdown...")
+            );
+            if pane.request_focus {
+                response.request_focus();
+                pane.request_focus = false;
+            }
+            // Escape closes the editor, clicking elsewhere just saves the notes
+            if response.lost_focus() {
+                close = ui.input(|i| i.key_pressed(egui::Key::Escape));
+                save = true;
+            }
+        });
+        // Ctrl+N closes the editor like it opened it
+        if editing && ui.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::N)) {
+            close = true;
+        }
+        
+        if save {
+            self.save_notes();
+        }
+        if let Some(task_id) = linked {
+            self.focus_linked_task(task_id);
+        }
+        if close {
+            self.app.display_container_state.notes_task = None;
+            self.app.display_container_state.request_focus_next_frame = true;
+            self.sync_notes_pane();
+        }
+    }
+    
     /// Render the activity log
     fn render_activity_log(&self, ui: &mut egui::Ui) {
         if let Some(message) = self.app.activity_log.latest_message() {
@@ -333,7 +810,7 @@ impl GuiApp {
                     // Use a label with explicit wrapping to ensure text stays within bounds
                     ui.add(
                         egui::Label::new(
-                            egui::RichText::new("Help: Enter = execute | Shift+Enter = subtask | Ctrl+Enter = PKM: toggle done, Feed: toggle pinned | Ctrl+Up/Down = expand/collapse | Ctrl+Space = switch mode")
+                            egui::RichText::new("Help: Enter = execute | Shift+Enter = subtask | Ctrl+Enter = PKM: toggle done, Feed: toggle pinned | Feed: Ctrl+P = pinboard, Ctrl+I = query context, Enter = capture as task (or jump to linked task), Shift+Enter = capture as subtask, Alt+Enter = capture as note | Ctrl+Up/Down = expand/collapse, Ctrl+Shift+Down/Up = expand/collapse subtree (all on input line), Ctrl+1..9 = show levels | Shift+Up/Down = extend selection, Ctrl+S = select task, Esc = clear selection | Ctrl+N = notes | Tab/Shift+Tab = indent/outdent task | Alt+Up/Down = move task, on input line: history | Alt+Right/Left = zoom in/out | Mouse: click = focus, double-click = edit, drag = move, right-click = menu | Ctrl+R = search history | Tab = complete command | Ctrl+Space = switch mode")
                                 .color(ACCENT_COLOR)
                         )
                         .wrap(true) // Enable text wrapping
@@ -351,8 +828,8 @@ impl GuiApp {
                 ui.vertical(|ui| {
                     // Customize the visuals to make the border always visible
                     // Store the original visuals
-                    let original_inactive = ui.visuals().widgets.inactive.clone();
-                    let original_active = ui.visuals().widgets.active.clone();
+                    let original_inactive = ui.visuals().widgets.inactive;
+                    let original_active = ui.visuals().widgets.active;
                     
                     // Modify the visuals for this scope
                     ui.visuals_mut().widgets.inactive.bg_stroke = egui::Stroke::new(1.0, ACCENT_COLOR);
@@ -362,13 +839,20 @@ impl GuiApp {
                     let cursor_pos = self.input_text.len();
                     
                     // Use a custom text edit with a visible background
+                    let prompt = self.app.credential_prompt.as_ref();
+                    let hint_text = if let Some(prompt) = prompt {
+                        prompt.message()
+                    } else if self.app.input_history.search().is_some() {
+                        "Search history (Ctrl+R = older, Enter = use, Escape = cancel)..."
+                    } else if let AppMode::Feed = self.app.app_mode {
+                        "Enter search query for Genius Feed..."
+                    } else {
+                        "Enter task or command..."
+                    };
                     let text_edit = egui::TextEdit::singleline(&mut self.input_text)
                         .desired_width(ui.available_width()) // Make it take full available width
-                        .hint_text(if let AppMode::Feed = self.app.app_mode {
-                            "Enter search query for Genius Feed..."
-                        } else {
-                            "Enter task or command..."
-                        }) // Add hint text
+                        .hint_text(hint_text)
+                        .password(prompt.is_some_and(|p| p.is_secret())) // Mask API keys and passphrases
                         .id(egui::Id::new("main_input_field")); // Use a consistent ID
                     
                     // Request focus on the text edit
@@ -415,6 +899,17 @@ impl GuiApp {
                     // NOTE: Enter key handling is done in keyhandler.rs
                     // Do not handle Enter key here to avoid conflicts
                     
+                    // Show the match of a running history search
+                    if self.app.input_history.search().is_some() {
+                        let text = match self.app.input_history.search_match() {
+                            Some(entry) => format!("reverse-i-search: {entry}"),
+                            None => "reverse-i-search: no match".to_string(),
+                        };
+                        ui.label(egui::RichText::new(text).color(ACCENT_COLOR));
+                    } else if prompt.is_none() && self.app.app_mode == AppMode::Pkm {
+                        self.render_completions(ui);
+                    }
+                    
                     // Display current mode below the input field
                     let mode_text = match self.app.app_mode {
                         AppMode::Pkm => "PKM Mode",
@@ -425,10 +920,43 @@ impl GuiApp {
             });
     }
     
+    /// Render suggestions for the command being typed and near-miss warnings
+    fn render_completions(&self, ui: &mut egui::Ui) {
+        if !matches!(self.app.display_container_state.focused_index, Some(0) | None) {
+            return;
+        }
+        
+        let completions = completion::complete(&self.app, &self.input_text);
+        let selected = self.app.completion_state.selected(completions.len());
+        for (index, suggestion) in completions.iter().enumerate() {
+            let label = egui::RichText::new(format!("{}  ({})", suggestion.label, suggestion.detail)).small();
+            let label = if index == selected {
+                label.color(ACCENT_COLOR).strong()
+            } else {
+                label.weak()
+            };
+            ui.label(label);
+        }
+        if !completions.is_empty() {
+            ui.label(egui::RichText::new("Tab = complete, Ctrl+Up/Down = choose").small().weak());
+        }
+        
+        // Warn before a mistyped command is turned into a task
+        let warning = match parse_command(self.input_text.clone()) {
+            Command::Create(input) => completion::near_miss(&input),
+            // While arguments are still being chosen from the suggestions, the command is unfinished
+            Command::Invalid(error) if completions.is_empty() => Some(error.to_string()),
+            _ => None,
+        };
+        if let Some(warning) = warning {
+            ui.label(egui::RichText::new(format!("⚠ {warning}")).color(egui::Color32::YELLOW));
+        }
+    }
+    
     /// Render the genius feed
     fn render_genius_feed(&self, ui: &mut egui::Ui) {
         // Always show the genius feed, but interaction is only enabled in Feed mode
-        crate::gui::genius_feed::render_genius_feed(ui, &GeniusApiBridge::global(), self.app.app_mode);
+        crate::gui::genius_feed::render_genius_feed(ui, &GeniusApiBridge::global(), &self.app);
     }
 }
 
@@ -439,14 +967,42 @@ impl eframe::App for GuiApp {
         self.genius_key_handler.update_modifiers(ctx);
         
         // Process keyboard input based on current mode
-        let input_handled = match self.app.app_mode {
-            AppMode::Pkm => self.key_handler.handle_input(&mut self.app, ctx, &mut self.input_text),
-            AppMode::Feed => self.genius_key_handler.handle_input(&mut self.app, ctx, &mut self.input_text),
+        // While the login flow waits for an answer, the input line belongs to it
+        // Keys typed into the notes editor are left to it
+        let editing_notes = ctx.memory(|m| m.has_focus(egui::Id::new(NOTES_EDITOR_ID)));
+        let input_handled = if self.inline_edit.is_some() || editing_notes {
+            // Keys go to the task being edited in the task list or its notes
+            false
+        } else if self.app.credential_prompt.is_some() {
+            self.key_handler.handle_credential_prompt(&mut self.app, ctx, &mut self.input_text)
+        } else if self.app.input_history.search().is_some() {
+            // So does a running history search
+            self.key_handler.handle_history_search(&mut self.app, ctx, &mut self.input_text)
+        } else if self.key_handler.handle_history_keys(&mut self.app, ctx, &mut self.input_text)
+            || (self.app.app_mode == AppMode::Pkm
+                && self.key_handler.handle_completion_keys(&mut self.app, ctx, &mut self.input_text))
+        {
+            true
+        } else {
+            match self.app.app_mode {
+                AppMode::Pkm => self.key_handler.handle_input(&mut self.app, ctx, &mut self.input_text),
+                AppMode::Feed => self.genius_key_handler.handle_input(&mut self.app, ctx, &mut self.input_text),
+            }
         };
         
+        self.sync_notes_pane();
+        if self.notes_pane.is_some() {
+            egui::SidePanel::right("notes_pane")
+                .resizable(true)
+                .default_width(320.0)
+                .show(ctx, |ui| self.render_notes_pane(ui, editing_notes));
+        }
+        
         // Set up the central panel with accent-colored visuals
-        let mut frame = egui::Frame::default();
-        frame.stroke = egui::Stroke::new(1.0, ACCENT_COLOR.linear_multiply(0.5));
+        let frame = egui::Frame {
+            stroke: egui::Stroke::new(1.0, ACCENT_COLOR.linear_multiply(0.5)),
+            ..Default::default()
+        };
         
         egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
             // Set a max width for all content to ensure it stays within bounds
@@ -465,6 +1021,9 @@ impl eframe::App for GuiApp {
                     // Tasks area (takes most of the space)
                     self.render_tasks(ui);
                     
+                    // Tasks linking to the focused one
+                    self.render_backlinks(ui);
+                    
                     ui.separator();
                     
                     // Activity log
@@ -499,4 +1058,16 @@ impl eframe::App for GuiApp {
         // Using the variable prevents unused variable warnings
         let _ = input_handled;
     }
+    
+    /// Save edits that are still open in the notes pane or the inline task
+    /// editor when the window closes
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_notes();
+        if let Some((task_id, content)) = self.inline_edit.take() {
+            let changed = self.app.tasks.iter().any(|t| t.id == task_id && t.content != content.trim());
+            if changed && !content.trim().is_empty() {
+                execute_command(&mut self.app, Some(Command::Edit(task_id, content.trim().to_string())));
+            }
+        }
+    }
 }
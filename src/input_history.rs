@@ -0,0 +1,306 @@
+//! History of the input line.
+//!
+//! Commands entered in PKM mode and queries typed in Feed mode are remembered
+//! separately and saved next to the tasks file of the workspace. Entries can be
+//! recalled one by one (Alt+Up/Down) or found with a reverse incremental
+//! search (Ctrl+R), like in a shell.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::AppMode;
+use crate::taskstore::{load_json, save_json};
+
+/// File name of the input history inside the workspace
+pub const HISTORY_FILE_NAME: &str = "history.json";
+
+/// Position while stepping through the history with Alt+Up/Down
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Recall {
+    mode: AppMode,
+    /// Index of the recalled entry
+    index: usize,
+    /// What was in the input line before recalling started
+    draft: String,
+}
+
+/// State of a reverse incremental search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistorySearch {
+    /// Mode whose history is searched
+    pub mode: AppMode,
+    /// What was in the input line before the search started
+    pub draft: String,
+    /// Text searched for
+    pub query: String,
+    /// Number of newer matches skipped with Ctrl+R
+    skip: usize,
+}
+
+/// Entered commands and queries, oldest first, per mode
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct InputHistory {
+    #[serde(default)]
+    pkm: Vec<String>,
+    #[serde(default)]
+    feed: Vec<String>,
+    #[serde(skip)]
+    recall: Option<Recall>,
+    #[serde(skip)]
+    search: Option<HistorySearch>,
+}
+
+impl InputHistory {
+    /// Create an empty history
+    pub const fn new() -> Self {
+        Self {
+            pkm: Vec::new(),
+            feed: Vec::new(),
+            recall: None,
+            search: None,
+        }
+    }
+
+    /// Entries of a mode, oldest first
+    pub fn entries(&self, mode: AppMode) -> &[String] {
+        match mode {
+            AppMode::Pkm => &self.pkm,
+            AppMode::Feed => &self.feed,
+        }
+    }
+
+    fn entries_mut(&mut self, mode: AppMode) -> &mut Vec<String> {
+        match mode {
+            AppMode::Pkm => &mut self.pkm,
+            AppMode::Feed => &mut self.feed,
+        }
+    }
+
+    /// Remember an entered line, keeping at most `limit` entries per mode.
+    ///
+    /// A line that is already in the history moves to the end.
+    pub fn push(&mut self, mode: AppMode, entry: &str, limit: usize) {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            return;
+        }
+        let entries = self.entries_mut(mode);
+        entries.retain(|existing| existing != entry);
+        entries.push(entry.to_string());
+        truncate_front(entries, limit);
+        self.recall = None;
+    }
+
+    /// Step back to an older entry.
+    ///
+    /// `input` is the current input line; it is restored when stepping past
+    /// the newest entry again. Returns the entry to show, if there is one.
+    pub fn recall_previous(&mut self, mode: AppMode, input: &str) -> Option<String> {
+        let len = self.entries(mode).len();
+        let recall = match self.recall.take() {
+            // Keep going if the recalled entry wasn't edited in the meantime
+            Some(recall)
+                if recall.mode == mode && self.entries(mode).get(recall.index).is_some_and(|e| e == input) =>
+            {
+                Recall {
+                    index: recall.index.saturating_sub(1),
+                    ..recall
+                }
+            }
+            _ => Recall {
+                mode,
+                index: len.checked_sub(1)?,
+                draft: input.to_string(),
+            },
+        };
+        let entry = self.entries(mode)[recall.index].clone();
+        self.recall = Some(recall);
+        Some(entry)
+    }
+
+    /// Step forward to a newer entry, or back to the draft after the newest one
+    pub fn recall_next(&mut self, mode: AppMode, input: &str) -> Option<String> {
+        let recall = self.recall.take()?;
+        if recall.mode != mode || self.entries(mode).get(recall.index).is_none_or(|e| e != input) {
+            return None;
+        }
+        if recall.index + 1 < self.entries(mode).len() {
+            let index = recall.index + 1;
+            let entry = self.entries(mode)[index].clone();
+            self.recall = Some(Recall { index, ..recall });
+            Some(entry)
+        } else {
+            Some(recall.draft)
+        }
+    }
+
+    /// The running reverse search, if any
+    pub fn search(&self) -> Option<&HistorySearch> {
+        self.search.as_ref()
+    }
+
+    /// Start a reverse search, remembering the current input line
+    pub fn start_search(&mut self, mode: AppMode, draft: &str) {
+        self.recall = None;
+        self.search = Some(HistorySearch {
+            mode,
+            draft: draft.to_string(),
+            query: String::new(),
+            skip: 0,
+        });
+    }
+
+    /// Update the text searched for; a changed query starts over at the newest match
+    pub fn set_search_query(&mut self, query: &str) {
+        if let Some(search) = self.search.as_mut() {
+            if search.query != query {
+                search.query = query.to_string();
+                search.skip = 0;
+            }
+        }
+    }
+
+    /// Matches of the running search, newest first
+    fn matches<'a>(&'a self, search: &'a HistorySearch) -> impl Iterator<Item = &'a String> {
+        let query = search.query.to_lowercase();
+        self.entries(search.mode)
+            .iter()
+            .rev()
+            .filter(move |entry| entry.to_lowercase().contains(&query))
+    }
+
+    /// The entry the running search currently points at
+    pub fn search_match(&self) -> Option<&str> {
+        let search = self.search.as_ref()?;
+        self.matches(search).nth(search.skip).map(String::as_str)
+    }
+
+    /// Move the running search to the next older match, if there is one
+    pub fn search_older(&mut self) {
+        let has_older = self
+            .search
+            .as_ref()
+            .is_some_and(|search| self.matches(search).nth(search.skip + 1).is_some());
+        if has_older {
+            if let Some(search) = self.search.as_mut() {
+                search.skip += 1;
+            }
+        }
+    }
+
+    /// End the search, returning the match (or the draft if nothing matched)
+    pub fn accept_search(&mut self) -> Option<String> {
+        let accepted = self.search_match().map(str::to_string);
+        let search = self.search.take()?;
+        Some(accepted.unwrap_or(search.draft))
+    }
+
+    /// End the search, returning the draft it started from
+    pub fn cancel_search(&mut self) -> Option<String> {
+        self.search.take().map(|search| search.draft)
+    }
+}
+
+/// Drop the oldest entries so at most `limit` remain
+fn truncate_front(entries: &mut Vec<String>, limit: usize) {
+    if entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+}
+
+/// Saves the input history to a JSON file.
+///
+/// ### Arguments
+/// * `history` - The history to save
+/// * `path` - Path to the history storage file
+pub fn save_history(history: &InputHistory, path: &str) -> std::io::Result<()> {
+    save_json(history, path)
+}
+
+/// Loads the input history from a JSON file.
+///
+/// ### Arguments
+/// * `path` - Path to the history storage file
+pub fn load_history(path: &str) -> std::io::Result<InputHistory> {
+    load_json(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn history() -> InputHistory {
+        let mut history = InputHistory::new();
+        for entry in ["show archived", "delete 3", "complete 1", "show taskpad"] {
+            history.push(AppMode::Pkm, entry, 100);
+        }
+        history.push(AppMode::Feed, "focus tips", 100);
+        history
+    }
+
+    #[test]
+    fn test_push_limits_and_deduplicates() {
+        let mut history = history();
+        history.push(AppMode::Pkm, "delete 3", 3);
+
+        assert_eq!(history.entries(AppMode::Pkm), ["complete 1", "show taskpad", "delete 3"]);
+        assert_eq!(history.entries(AppMode::Feed), ["focus tips"]);
+    }
+
+    #[test]
+    fn test_recall_steps_through_history_per_mode() {
+        let mut history = history();
+
+        let entry = history.recall_previous(AppMode::Pkm, "draft").unwrap();
+        assert_eq!(entry, "show taskpad");
+        let entry = history.recall_previous(AppMode::Pkm, &entry).unwrap();
+        assert_eq!(entry, "complete 1");
+        let entry = history.recall_next(AppMode::Pkm, &entry).unwrap();
+        assert_eq!(entry, "show taskpad");
+        assert_eq!(history.recall_next(AppMode::Pkm, &entry).unwrap(), "draft");
+
+        // Editing a recalled entry starts over from the newest one
+        history.recall_previous(AppMode::Pkm, "");
+        assert_eq!(history.recall_previous(AppMode::Pkm, "show task").unwrap(), "show taskpad");
+
+        assert_eq!(history.recall_previous(AppMode::Feed, "").unwrap(), "focus tips");
+    }
+
+    #[test]
+    fn test_reverse_search() {
+        let mut history = history();
+        history.start_search(AppMode::Pkm, "draft");
+
+        history.set_search_query("SHOW");
+        assert_eq!(history.search_match(), Some("show taskpad"));
+        history.search_older();
+        assert_eq!(history.search_match(), Some("show archived"));
+        history.search_older();
+        assert_eq!(history.search_match(), Some("show archived"));
+
+        // Typing more starts over at the newest match
+        history.set_search_query("show t");
+        assert_eq!(history.search_match(), Some("show taskpad"));
+
+        assert_eq!(history.accept_search().unwrap(), "show taskpad");
+        assert!(history.search().is_none());
+
+        history.start_search(AppMode::Pkm, "draft");
+        history.set_search_query("nothing");
+        assert_eq!(history.search_match(), None);
+        assert_eq!(history.cancel_search().unwrap(), "draft");
+    }
+
+    #[test]
+    fn test_history_persists() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(HISTORY_FILE_NAME);
+        let path = path.to_str().unwrap();
+
+        save_history(&history(), path).unwrap();
+        let loaded = load_history(path).unwrap();
+
+        assert_eq!(loaded.entries(AppMode::Pkm).len(), 4);
+        assert_eq!(loaded.entries(AppMode::Feed), ["focus tips"]);
+    }
+}
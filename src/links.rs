@@ -0,0 +1,167 @@
+//! Links between tasks.
+//!
+//! Task content and notes can reference other tasks as `[[task title]]`, or as
+//! `[[#id]]` to name a task by its ID. A title link points to the task with
+//! that content, ignoring case. Title links are rewritten when their task is
+//! renamed, so they keep pointing at it.
+
+use std::ops::Range;
+
+use crate::taskstore::Task;
+
+/// What a link names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// The task with this content
+    Title(String),
+    /// The task with this ID
+    Id(u32),
+}
+
+impl LinkTarget {
+    /// Parses the text between `[[` and `]]`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some(id) = text.strip_prefix('#').and_then(|id| id.parse().ok()) {
+            return Some(Self::Id(id));
+        }
+        (!text.is_empty()).then(|| Self::Title(text.to_string()))
+    }
+
+    /// ID of the task the link points to, if it exists
+    pub fn resolve(&self, tasks: &[Task]) -> Option<u32> {
+        match self {
+            Self::Id(id) => tasks.iter().find(|t| t.id == *id).map(|t| t.id),
+            Self::Title(title) => {
+                let title = title.to_lowercase();
+                tasks.iter().find(|t| t.content.trim().to_lowercase() == title).map(|t| t.id)
+            }
+        }
+    }
+}
+
+/// A link found in a text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Byte range of the link in the text, brackets included
+    pub range: Range<usize>,
+    pub target: LinkTarget,
+}
+
+/// Finds the `[[...]]` links in a text
+pub fn parse_links(text: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut start = 0;
+    while let Some(open) = text[start..].find("[[").map(|open| start + open) {
+        let Some(close) = text[open + 2..].find("]]").map(|close| open + 2 + close) else {
+            break;
+        };
+        let inner = &text[open + 2..close];
+        // In `[[a [[b]]` only the innermost brackets make a link
+        if let Some(nested) = inner.rfind("[[") {
+            start = open + 2 + nested;
+            continue;
+        }
+        if let Some(target) = LinkTarget::parse(inner) {
+            links.push(Link { range: open..close + 2, target });
+        }
+        start = close + 2;
+    }
+    links
+}
+
+/// Whether a text links to a task
+fn links_to(text: &str, tasks: &[Task], task_id: u32) -> bool {
+    parse_links(text).iter().any(|link| link.target.resolve(tasks) == Some(task_id))
+}
+
+/// IDs of the tasks whose content or notes link to a task, besides the task itself
+pub fn backlinks(tasks: &[Task], task_id: u32) -> Vec<u32> {
+    tasks
+        .iter()
+        .filter(|task| task.id != task_id)
+        .filter(|task| {
+            links_to(&task.content, tasks, task_id)
+                || task.notes.as_deref().is_some_and(|notes| links_to(notes, tasks, task_id))
+        })
+        .map(|task| task.id)
+        .collect()
+}
+
+/// Rewrites `[[old title]]` links in a text to `[[new title]]`.
+///
+/// Returns None if the text has no such link.
+fn rename_in_text(text: &str, old_title: &str, new_title: &str) -> Option<String> {
+    let old_title = old_title.trim().to_lowercase();
+    let mut renamed = String::new();
+    let mut end = 0;
+    for link in parse_links(text) {
+        if matches!(&link.target, LinkTarget::Title(title) if title.to_lowercase() == old_title) {
+            renamed.push_str(&text[end..link.range.start]);
+            renamed.push_str(&format!("[[{}]]", new_title.trim()));
+            end = link.range.end;
+        }
+    }
+    (end > 0).then(|| renamed + &text[end..])
+}
+
+/// Rewrites title links to a renamed task in the content and notes of all
+/// tasks. Returns how many tasks changed.
+pub fn rename_links(tasks: &mut [Task], old_title: &str, new_title: &str) -> usize {
+    let mut changed = 0;
+    for task in tasks.iter_mut() {
+        let content = rename_in_text(&task.content, old_title, new_title);
+        let notes = task.notes.as_deref().and_then(|notes| rename_in_text(notes, old_title, new_title));
+        changed += usize::from(content.is_some() || notes.is_some());
+        if let Some(content) = content {
+            task.content = content;
+        }
+        if notes.is_some() {
+            task.notes = notes;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taskstore::TaskBuilder;
+
+    #[test]
+    fn test_parse_links() {
+        let text = "See [[Plan trip]] and [[#3]], not [[ ]] or [[open";
+        let links = parse_links(text);
+        assert_eq!(links.len(), 2);
+        assert_eq!(&text[links[0].range.clone()], "[[Plan trip]]");
+        assert_eq!(links[0].target, LinkTarget::Title("Plan trip".to_string()));
+        assert_eq!(links[1].target, LinkTarget::Id(3));
+
+        let nested = parse_links("[[a [[b]]");
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].target, LinkTarget::Title("b".to_string()));
+        assert_eq!(LinkTarget::parse("#x"), Some(LinkTarget::Title("#x".to_string())));
+    }
+
+    #[test]
+    fn test_backlinks_and_renames() {
+        let mut tasks = vec![
+            TaskBuilder::new(1).content("Plan trip").build(),
+            TaskBuilder::new(2).content("Book hotel for [[plan trip]]").build(),
+            TaskBuilder::new(3).content("Pack").build(),
+            TaskBuilder::new(4).content("Budget [[#1]]").build(),
+        ];
+        tasks[2].notes = Some("Checklist for [[Plan trip]] and [[Pack]]".to_string());
+        assert_eq!(LinkTarget::Title("PLAN TRIP".to_string()).resolve(&tasks), Some(1));
+        assert_eq!(LinkTarget::Id(9).resolve(&tasks), None);
+        assert_eq!(backlinks(&tasks, 1), [2, 3, 4]);
+        assert!(backlinks(&tasks, 3).is_empty());
+
+        tasks[0].content = "Plan holiday".to_string();
+        assert_eq!(rename_links(&mut tasks, "Plan trip", "Plan holiday"), 2);
+        assert_eq!(tasks[1].content, "Book hotel for [[Plan holiday]]");
+        assert_eq!(tasks[2].notes.as_deref(), Some("Checklist for [[Plan holiday]] and [[Pack]]"));
+        assert_eq!(tasks[3].content, "Budget [[#1]]");
+        assert_eq!(backlinks(&tasks, 1), [2, 3, 4]);
+    }
+}
@@ -0,0 +1,373 @@
+//! Leveled logging for the application.
+//!
+//! Messages are written with the `log` macros (`debug!`, `info!`, ...) and end
+//! up in a rotating log file in the data directory. Levels are set per module
+//! in the `[logging]` section of the configuration, or with the
+//! `CYBERORGANISM_LOG` environment variable, e.g.
+//! `CYBERORGANISM_LOG="info,genius_platform::genius_api=debug"`.
+//!
+//! Logging never aborts the application: if the log file can't be written,
+//! messages go to stderr instead. Secrets are redacted from every message.
+
+use crate::config::LoggingConfig;
+use crate::credentials::redact;
+use chrono::Local;
+use directories::ProjectDirs;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock, RwLock};
+
+/// Environment variable with the log filter; it overrides the configuration
+pub const LOG_ENV_VAR: &str = "CYBERORGANISM_LOG";
+
+/// Name of the current log file; rotated files get a `.1`, `.2`, ... suffix
+pub const LOG_FILE_NAME: &str = "cyberorganism.log";
+
+/// Prefix of the log targets of this crate
+const CRATE_PREFIX: &str = "cyberorganism";
+
+/// Level for other crates (egui, reqwest, ...) unless a filter names them
+const DEPENDENCY_LEVEL: LevelFilter = LevelFilter::Warn;
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Log levels per module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    /// A filter that logs this crate at `default`
+    pub const fn new(default: LevelFilter) -> Self {
+        Self {
+            default,
+            modules: Vec::new(),
+        }
+    }
+
+    /// Log `module` and its submodules at `level`
+    pub fn with_module(mut self, module: &str, level: LevelFilter) -> Self {
+        self.modules.retain(|(name, _)| name != module);
+        self.modules.push((module.to_string(), level));
+        self
+    }
+
+    /// Parse a filter like `info,genius_platform::genius_api=debug`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Self::new(LevelFilter::Info);
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    filter = filter.with_module(module.trim(), parse_level(level)?);
+                }
+                None => filter.default = parse_level(directive)?,
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Build the filter from the `[logging]` configuration
+    pub fn from_config(config: &LoggingConfig) -> Result<Self, String> {
+        let mut filter = Self::new(parse_level(&config.level)?);
+        for (module, level) in &config.modules {
+            filter = filter.with_module(module, parse_level(level)?);
+        }
+        Ok(filter)
+    }
+
+    /// The level a log target is written at
+    ///
+    /// Module names may leave out the crate name; the longest matching module wins.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        let local = strip_module_prefix(target, CRATE_PREFIX);
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                strip_module_prefix(target, module).is_some()
+                    || local.is_some_and(|local| strip_module_prefix(local, module).is_some())
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or_else(
+                || if local.is_some() { self.default } else { DEPENDENCY_LEVEL },
+                |(_, level)| *level,
+            )
+    }
+
+    /// The most verbose level any target is written at
+    pub fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .chain([self.default, DEPENDENCY_LEVEL])
+            .max()
+            .unwrap_or(LevelFilter::Off)
+    }
+}
+
+/// Returns the rest of `path` if it is `module` or one of its submodules
+fn strip_module_prefix<'a>(path: &'a str, module: &str) -> Option<&'a str> {
+    match path.strip_prefix(module)? {
+        "" => Some(""),
+        rest => rest.strip_prefix("::"),
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level.trim()).map_err(|_| format!("Unknown log level '{}'", level.trim()))
+}
+
+/// Log file that is rotated when it grows past a size limit
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    /// Open the log file for appending, creating its directory if needed
+    fn open(path: &Path, max_size: u64, max_files: usize) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size,
+            max_files,
+        })
+    }
+
+    /// Path of the `index`-th rotated file
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{index}"));
+        PathBuf::from(name)
+    }
+
+    /// Shift the rotated files up by one and start a new log file
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 + 1 > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{line}")?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+}
+
+/// The installed logger
+struct Logger {
+    filter: RwLock<LogFilter>,
+    file: Mutex<Option<RotatingFile>>,
+}
+
+impl Logger {
+    /// Write a line to the log file, or to stderr if there is none
+    fn write(&self, line: &str) {
+        let Ok(mut guard) = self.file.lock() else {
+            eprintln!("{line}");
+            return;
+        };
+        let failed = match guard.as_mut() {
+            Some(file) => file.write_line(line).err(),
+            None => {
+                eprintln!("{line}");
+                return;
+            }
+        };
+        if let Some(e) = failed {
+            // Stop using a broken log file but keep the application running
+            *guard = None;
+            eprintln!("Failed to write log file, logging to stderr: {e}");
+            eprintln!("{line}");
+        }
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.filter
+            .read()
+            .is_ok_and(|filter| metadata.level() <= filter.level_for(metadata.target()))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let target = strip_module_prefix(record.target(), CRATE_PREFIX).unwrap_or(record.target());
+        let message = redact(&record.args().to_string());
+        let line = format!(
+            "[{}] {:<5} {}: {}",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            target,
+            message
+        );
+
+        // Problems are also shown on the terminal
+        if record.level() <= Level::Warn {
+            eprintln!("{} {}: {}", record.level(), target, message);
+        }
+        self.write(&line);
+    }
+
+    fn flush(&self) {
+        if let Ok(mut guard) = self.file.lock() {
+            if let Some(file) = guard.as_mut() {
+                let _ = file.file.flush();
+            }
+        }
+    }
+}
+
+/// Directory the log files are written to
+pub fn log_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "cyberorganism", "cyberorganism")
+        .map(|proj_dirs| proj_dirs.data_dir().join("logs"))
+}
+
+/// Path of the current log file
+pub fn log_file_path() -> Option<PathBuf> {
+    log_dir().map(|dir| dir.join(LOG_FILE_NAME))
+}
+
+/// Filter from the environment variable, if it is set and valid
+fn env_filter() -> Option<LogFilter> {
+    let spec = std::env::var(LOG_ENV_VAR).ok()?;
+    match LogFilter::parse(&spec) {
+        Ok(filter) => Some(filter),
+        Err(e) => {
+            eprintln!("Ignoring {LOG_ENV_VAR}: {e}");
+            None
+        }
+    }
+}
+
+/// Install the logger with the default configuration.
+///
+/// Call this before anything else logs; `configure` applies the
+/// `[logging]` settings once the configuration has been loaded.
+pub fn init() {
+    let defaults = LoggingConfig::default();
+    let filter = env_filter().unwrap_or_else(|| LogFilter::new(LevelFilter::Info));
+    let file = log_file_path().and_then(|path| {
+        RotatingFile::open(&path, defaults.max_file_size_kb * 1024, defaults.max_files)
+            .map_err(|e| eprintln!("Failed to open log file {}, logging to stderr: {e}", path.display()))
+            .ok()
+    });
+
+    let max_level = filter.max_level();
+    let logger = LOGGER.get_or_init(|| Logger {
+        filter: RwLock::new(filter),
+        file: Mutex::new(file),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Apply the `[logging]` configuration to the installed logger
+///
+/// The environment variable still takes precedence over the configured levels.
+pub fn configure(config: &LoggingConfig) {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+
+    let filter = env_filter().or_else(|| match LogFilter::from_config(config) {
+        Ok(filter) => Some(filter),
+        Err(e) => {
+            log::warn!("Ignoring logging configuration: {e}");
+            None
+        }
+    });
+    if let Some(filter) = filter {
+        log::set_max_level(filter.max_level());
+        if let Ok(mut current) = logger.filter.write() {
+            *current = filter;
+        }
+    }
+
+    if let Ok(mut guard) = logger.file.lock() {
+        if let Some(file) = guard.as_mut() {
+            file.max_size = config.max_file_size_kb * 1024;
+            file.max_files = config.max_files;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_filter() {
+        let filter = LogFilter::parse("warn, genius_platform=debug,genius_platform::genius_api=trace").unwrap();
+
+        assert_eq!(filter.level_for("cyberorganism::commands"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("cyberorganism::genius_platform::genius_api_bridge"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("cyberorganism::genius_platform::genius_api"), LevelFilter::Trace);
+        assert_eq!(filter.level_for("reqwest::connect"), LevelFilter::Warn);
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+
+        assert!(LogFilter::parse("loud").is_err());
+        assert!(LogFilter::parse("gui=verbose").is_err());
+    }
+
+    #[test]
+    fn test_dependencies_can_be_named() {
+        let filter = LogFilter::new(LevelFilter::Debug).with_module("reqwest", LevelFilter::Info);
+
+        assert_eq!(filter.level_for("reqwest::connect"), LevelFilter::Info);
+        assert_eq!(filter.level_for("reqwest_extra"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("eframe"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("cyberorganism"), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn test_log_file_rotation() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("logs").join(LOG_FILE_NAME);
+        let mut file = RotatingFile::open(&path, 20, 2).unwrap();
+
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            file.write_line(line).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth line\n");
+        assert_eq!(fs::read_to_string(file.rotated_path(1)).unwrap(), "third line\n");
+        assert_eq!(fs::read_to_string(file.rotated_path(2)).unwrap(), "second line\n");
+        assert!(!file.rotated_path(3).exists());
+    }
+}
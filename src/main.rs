@@ -2,18 +2,32 @@
 //! the application's state and event loop, delegating UI rendering, task operations, and
 //! command handling to their respective specialized modules.
 
+#![allow(clippy::collapsible_if)]
+
+mod command_parser;
 mod commands;
+mod completion;
 mod config;
-mod debug;
+mod credentials;
+mod logging;
 mod display_container;
 mod genius_platform;
 mod gui;
+mod input_history;
+mod links;
+mod recurrence;
+mod sorting;
 mod taskstore;
 
-use crate::commands::AppMode;
+use crate::commands::{AppMode, CredentialPrompt};
+use crate::completion::CompletionState;
 use crate::display_container::{ActivityLog, DisplayContainerState};
+use crate::genius_platform::pinboard::{PINS_FILE_NAME, Pinboard, load_pins};
+use crate::input_history::{HISTORY_FILE_NAME, InputHistory, load_history};
+use crate::genius_platform::sessions::{SESSIONS_FILE_NAME, SessionStore, load_sessions, save_sessions};
+use crate::sorting::{SORTING_FILE_NAME, load_sort_modes};
 use std::fmt;
-use taskstore::{Task, load_tasks};
+use taskstore::{Task, load_tasks, workspace_file};
 
 /// Represents the current state of the application
 pub struct App {
@@ -31,6 +45,22 @@ pub struct App {
     pub show_help: bool,
     /// Current application mode (PKM or Feed)
     pub app_mode: AppMode,
+    /// Pinned Genius items, saved with the workspace
+    pub pinboard: Pinboard,
+    /// Genius feed sessions, saved with the workspace
+    pub sessions: SessionStore,
+    /// Commands and queries entered in the input line, saved with the workspace
+    pub input_history: InputHistory,
+    /// Path to the encrypted Genius credentials file
+    pub credentials_file: String,
+    /// Step of the `login` flow the input line is answering, if any
+    pub credential_prompt: Option<CredentialPrompt>,
+    /// Highlighted suggestion of the completion popup
+    pub completion_state: CompletionState,
+    /// Input that looked like a mistyped command and is created as a task on a second Enter
+    pub pending_create: Option<String>,
+    /// Whether completing the last open subtask of a task completes the task too
+    pub auto_complete_parents: bool,
 }
 
 impl Default for App {
@@ -51,9 +81,37 @@ impl App {
             activity_log: ActivityLog::new(),
             show_help: true,
             app_mode: AppMode::default(),
+            pinboard: Pinboard::new(),
+            sessions: SessionStore::new(),
+            input_history: InputHistory::new(),
+            credentials_file: credentials::default_credentials_file(),
+            credential_prompt: None,
+            completion_state: CompletionState::default(),
+            pending_create: None,
+            auto_complete_parents: config::get_config().tasks.auto_complete_parents,
         }
     }
 
+    /// Path to the pinboard file in the current workspace
+    pub fn pins_file(&self) -> String {
+        workspace_file(&self.tasks_file, PINS_FILE_NAME)
+    }
+
+    /// Path to the Genius sessions file in the current workspace
+    pub fn sessions_file(&self) -> String {
+        workspace_file(&self.tasks_file, SESSIONS_FILE_NAME)
+    }
+
+    /// Path to the input history file in the current workspace
+    pub fn history_file(&self) -> String {
+        workspace_file(&self.tasks_file, HISTORY_FILE_NAME)
+    }
+
+    /// Path to the sort modes file in the current workspace
+    pub fn sorting_file(&self) -> String {
+        workspace_file(&self.tasks_file, SORTING_FILE_NAME)
+    }
+
     /// Logs an activity message
     pub fn log_activity(&mut self, message: String) {
         self.activity_log.add_message(message);
@@ -106,9 +164,20 @@ impl std::error::Error for AppError {}
 /// Runs the application, loading the initial state from disk if available,
 /// and starting the GUI.
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Start logging before anything else, then apply the configured levels
+    logging::init();
+    config::init();
+    logging::configure(&config::get_config().logging);
+
     // Create app state
     let mut app = App::new();
 
+    // Load the sort modes of the workspace before the tasks are first displayed
+    match load_sort_modes(&app.sorting_file()) {
+        Ok(modes) => app.display_container_state.sort_modes = modes,
+        Err(e) => log::warn!("Failed to load sort modes: {e}"),
+    }
+
     // Load tasks from disk if available
     if let Ok(tasks) = load_tasks(&app.tasks_file) {
         app.tasks = tasks;
@@ -116,17 +185,47 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         app.display_container_state.update_display_order(&app.tasks);
     }
 
-    // Initialize the Genius API from environment variables
+    // Load pinned Genius items saved with the workspace
+    match load_pins(&app.pins_file()) {
+        Ok(pinboard) => app.pinboard = pinboard,
+        Err(e) => log::warn!("Failed to load pinned items: {e}"),
+    }
+
+    // Load the input line history of the workspace
+    match load_history(&app.history_file()) {
+        Ok(history) => app.input_history = history,
+        Err(e) => log::warn!("Failed to load input history: {e}"),
+    }
+
+    // Resume the last Genius feed session of the workspace, or start one
+    match load_sessions(&app.sessions_file()) {
+        Ok(sessions) => app.sessions = sessions,
+        Err(e) => log::warn!("Failed to load Genius sessions: {e}"),
+    }
+    let started = app.sessions.active().is_none();
+    let session_id = app.sessions.active_or_start().id.clone();
+    genius_platform::get_api_bridge().set_session(&session_id);
+    if started {
+        if let Err(e) = save_sessions(&app.sessions, &app.sessions_file()) {
+            log::warn!("Failed to save Genius sessions: {e}");
+        }
+    }
+
+    // Initialize the Genius API from the environment, the API key command,
+    // or the encrypted credentials file (which needs the passphrase first)
     if genius_platform::initialize_from_env() {
-        println!("Genius API initialized from environment variables");
+        log::info!("Genius API initialized from environment variables");
+    } else if genius_platform::initialize_from_command() {
+        log::info!("Genius API initialized from genius.api_key_command");
+    } else if std::path::Path::new(&app.credentials_file).exists() {
+        commands::execute_login_command(&mut app);
     } else {
-        println!("Genius API not configured. Set GENIUS_API_KEY and GENIUS_ORGANIZATION_ID environment variables to enable API integration.");
-        // You could also load from a config file here as a fallback
+        log::info!("Genius API not configured. Run `login`, set genius.api_key_command, or set GENIUS_API_KEY and GENIUS_ORGANIZATION_ID to enable API integration.");
     }
 
     // Run the GUI application
     if let Err(e) = gui::run_app(app) {
-        eprintln!("Error running application: {}", e);
+        log::error!("Error running application: {}", e);
         return Err(Box::new(AppError(e.to_string())));
     }
 
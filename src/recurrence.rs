@@ -0,0 +1,192 @@
+//! Recurring tasks.
+//!
+//! A task can repeat every day, on some days of the week, on a day of the
+//! month, or a number of days after it was last completed. Completing a
+//! recurring task archives it and creates its next occurrence, due on the next
+//! date of the rule.
+//!
+//! Rules are written as in `every:mon,thu` inline in a task's content, or as
+//! the argument of the `repeat` command:
+//!
+//! | Rule | Repeats |
+//! | --- | --- |
+//! | `day` | Every day |
+//! | `mon`, `mon,thu` | Weekly on these days |
+//! | `15`, `15th` | Monthly on this day, or the last day of shorter months |
+//! | `3d` | Three days after each completion |
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Prefix of a rule written inline in a task's content
+const INLINE_PREFIX: &str = "every:";
+
+/// When a task comes back after it is completed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recurrence {
+    /// Every day
+    Daily,
+    /// Every week on these days, in week order
+    Weekly(Vec<Weekday>),
+    /// Every month on this day (1 to 31)
+    Monthly(u32),
+    /// This many days after each completion
+    AfterCompletion(u32),
+}
+
+impl Recurrence {
+    /// Parses a rule like `day`, `mon,thu`, `15th` or `3d`, ignoring case
+    pub fn parse(rule: &str) -> Option<Self> {
+        let rule = rule.trim().to_lowercase();
+        if rule == "day" || rule == "daily" {
+            return Some(Self::Daily);
+        }
+        if let Some(days) = rule.strip_suffix('d').and_then(|days| days.parse().ok()) {
+            return (days > 0).then_some(Self::AfterCompletion(days));
+        }
+        let day = ["st", "nd", "rd", "th"].iter().fold(rule.as_str(), |rule, suffix| rule.strip_suffix(suffix).unwrap_or(rule));
+        if let Ok(day) = day.parse() {
+            return (1..=31).contains(&day).then_some(Self::Monthly(day));
+        }
+        let mut weekdays = rule
+            .split(',')
+            .map(|day| day.trim().parse::<Weekday>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        weekdays.sort_by_key(Weekday::num_days_from_monday);
+        weekdays.dedup();
+        Some(Self::Weekly(weekdays))
+    }
+
+    /// Due date of the first occurrence of a new recurring task, if the rule
+    /// follows the calendar
+    pub fn first_due(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::AfterCompletion(_) => None,
+            _ => today.pred_opt().map(|yesterday| self.next_after(yesterday)),
+        }
+    }
+
+    /// Due date of the occurrence after one that was due on `due` and
+    /// completed on `completed_on`.
+    ///
+    /// Calendar rules skip dates that are already past, so a late completion
+    /// doesn't create overdue tasks.
+    pub fn next_due(&self, due: Option<NaiveDate>, completed_on: NaiveDate) -> NaiveDate {
+        match self {
+            Self::AfterCompletion(days) => completed_on + Days::new(u64::from(*days)),
+            _ => self.next_after(due.map_or(completed_on, |due| due.max(completed_on))),
+        }
+    }
+
+    /// First date of a calendar rule after `date`
+    fn next_after(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily | Self::AfterCompletion(_) => date + Days::new(1),
+            // A week without days repeats daily
+            Self::Weekly(days) => (1..=7)
+                .map(|offset| date + Days::new(offset))
+                .find(|next| days.is_empty() || days.contains(&next.weekday()))
+                .unwrap_or(date + Days::new(1)),
+            Self::Monthly(day) => {
+                let on_day = |month_start: NaiveDate| {
+                    let last_day = (month_start + Months::new(1)).pred_opt().map_or(28, |last| last.day());
+                    month_start.with_day((*day).min(last_day)).unwrap_or(month_start)
+                };
+                let month_start = date.with_day(1).unwrap_or(date);
+                let this_month = on_day(month_start);
+                if this_month > date { this_month } else { on_day(month_start + Months::new(1)) }
+            }
+        }
+    }
+
+    /// Finds an `every:` rule in a task's content.
+    ///
+    /// Returns the content without the rule and the rule, or the content
+    /// unchanged and None if it has no valid rule.
+    pub fn extract(content: &str) -> (String, Option<Self>) {
+        let words: Vec<&str> = content.split(' ').collect();
+        let found = words.iter().enumerate().find_map(|(index, word)| {
+            let rule = word.get(..INLINE_PREFIX.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(INLINE_PREFIX))
+                .map(|_| &word[INLINE_PREFIX.len()..])?;
+            Self::parse(rule).map(|recurrence| (index, recurrence))
+        });
+        match found {
+            Some((index, recurrence)) => {
+                let rest: Vec<&str> = words.iter().enumerate().filter(|(i, _)| *i != index).map(|(_, word)| *word).collect();
+                (rest.join(" ").trim().to_string(), Some(recurrence))
+            }
+            None => (content.to_string(), None),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily => write!(f, "every day"),
+            Self::Weekly(days) => {
+                let days: Vec<String> = days.iter().map(ToString::to_string).collect();
+                write!(f, "every {}", days.join(", "))
+            }
+            Self::Monthly(day) => write!(f, "monthly on day {day}"),
+            Self::AfterCompletion(1) => write!(f, "1 day after completion"),
+            Self::AfterCompletion(days) => write!(f, "{days} days after completion"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Recurrence::parse("Day"), Some(Recurrence::Daily));
+        assert_eq!(Recurrence::parse("thu,MON,monday"), Some(Recurrence::Weekly(vec![Weekday::Mon, Weekday::Thu])));
+        assert_eq!(Recurrence::parse("15th"), Some(Recurrence::Monthly(15)));
+        assert_eq!(Recurrence::parse("31"), Some(Recurrence::Monthly(31)));
+        assert_eq!(Recurrence::parse("3d"), Some(Recurrence::AfterCompletion(3)));
+        for invalid in ["", "32", "0", "0d", "mon,", "fortnight", "d"] {
+            assert_eq!(Recurrence::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_next_due() {
+        // 2026-10-18 is a Sunday
+        let weekly = Recurrence::Weekly(vec![Weekday::Mon, Weekday::Thu]);
+        assert_eq!(weekly.first_due(date(10, 18)), Some(date(10, 19)));
+        assert_eq!(weekly.first_due(date(10, 19)), Some(date(10, 19)));
+        assert_eq!(weekly.next_due(Some(date(10, 19)), date(10, 19)), date(10, 22));
+        // Completed early, the next one follows the due date
+        assert_eq!(weekly.next_due(Some(date(10, 22)), date(10, 20)), date(10, 26));
+        // Completed late, past dates are skipped
+        assert_eq!(weekly.next_due(Some(date(10, 12)), date(10, 21)), date(10, 22));
+
+        assert_eq!(Recurrence::Daily.next_due(None, date(10, 18)), date(10, 19));
+        assert_eq!(Recurrence::Monthly(31).next_due(Some(date(10, 31)), date(10, 31)), date(11, 30));
+        assert_eq!(Recurrence::Monthly(15).next_due(Some(date(10, 15)), date(10, 9)), date(11, 15));
+        assert_eq!(Recurrence::Monthly(15).first_due(date(10, 18)), Some(date(11, 15)));
+        assert_eq!(Recurrence::AfterCompletion(3).next_due(Some(date(10, 1)), date(10, 18)), date(10, 21));
+        assert_eq!(Recurrence::AfterCompletion(3).first_due(date(10, 18)), None);
+    }
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(
+            Recurrence::extract("Weekly review EVERY:fri"),
+            ("Weekly review".to_string(), Some(Recurrence::Weekly(vec![Weekday::Fri])))
+        );
+        assert_eq!(
+            Recurrence::extract("Pay rent every:1st now"),
+            ("Pay rent now".to_string(), Some(Recurrence::Monthly(1)))
+        );
+        assert_eq!(Recurrence::extract("Go every:sometimes"), ("Go every:sometimes".to_string(), None));
+    }
+}
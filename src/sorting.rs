@@ -0,0 +1,185 @@
+//! Sort order of the task list.
+//!
+//! Top-level tasks are shown in manual order (see `order_key_between`) or
+//! sorted by when they were created, when they are due, their status or their
+//! content. Each container has its own sort mode, saved next to the tasks file
+//! of the workspace. Subtasks always keep the order of their parent's
+//! `child_ids`.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::taskstore::{Task, TaskContainer, TaskStatus, load_json, save_json};
+
+/// File name of the sort modes inside the workspace
+pub const SORTING_FILE_NAME: &str = "sorting.json";
+
+/// How top-level tasks are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SortMode {
+    /// The order tasks were arranged in
+    #[default]
+    Manual,
+    /// Oldest first
+    Created,
+    /// Soonest due first, tasks without a due date last
+    Due,
+    /// Tasks being worked on first, then open ones, then done ones
+    Status,
+    /// By content, ignoring case
+    Alphabetical,
+}
+
+impl SortMode {
+    /// Names accepted by the `sort` command
+    pub const NAMES: &'static [&'static str] = &["manual", "created", "due", "status", "name"];
+
+    /// Parse a sort mode from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "manual" => Some(Self::Manual),
+            "created" => Some(Self::Created),
+            "due" => Some(Self::Due),
+            "status" => Some(Self::Status),
+            "name" | "alphabetical" => Some(Self::Alphabetical),
+            _ => None,
+        }
+    }
+
+    /// Compares two tasks, falling back to manual order for ties
+    pub fn compare(self, a: &Task, b: &Task) -> Ordering {
+        let ordering = match self {
+            Self::Manual => Ordering::Equal,
+            Self::Created => a.created_at.cmp(&b.created_at),
+            Self::Due => match (a.due, b.due) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            Self::Status => status_rank(&a.status).cmp(&status_rank(&b.status)),
+            Self::Alphabetical => a.content.to_lowercase().cmp(&b.content.to_lowercase()),
+        };
+        ordering.then_with(|| a.order.cmp(&b.order))
+    }
+}
+
+impl fmt::Display for SortMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Manual => "manual",
+            Self::Created => "created",
+            Self::Due => "due",
+            Self::Status => "status",
+            Self::Alphabetical => "name",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Position of a status when sorting by status
+const fn status_rank(status: &TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Doing => 0,
+        TaskStatus::Todo => 1,
+        TaskStatus::Done => 2,
+    }
+}
+
+/// Sort mode of each container; containers without one are in manual order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortModes {
+    #[serde(default)]
+    modes: HashMap<TaskContainer, SortMode>,
+}
+
+impl SortModes {
+    /// Creates sort modes with every container in manual order
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sort mode of a container
+    pub fn get(&self, container: TaskContainer) -> SortMode {
+        self.modes.get(&container).copied().unwrap_or_default()
+    }
+
+    /// Sets the sort mode of a container
+    pub fn set(&mut self, container: TaskContainer, mode: SortMode) {
+        if mode == SortMode::Manual {
+            self.modes.remove(&container);
+        } else {
+            self.modes.insert(container, mode);
+        }
+    }
+}
+
+/// Saves the sort modes to a JSON file.
+///
+/// ### Arguments
+/// * `modes` - The sort modes to save
+/// * `path` - Path to the sort modes file
+pub fn save_sort_modes(modes: &SortModes, path: &str) -> std::io::Result<()> {
+    save_json(modes, path)
+}
+
+/// Loads the sort modes from a JSON file.
+///
+/// ### Arguments
+/// * `path` - Path to the sort modes file
+pub fn load_sort_modes(path: &str) -> std::io::Result<SortModes> {
+    load_json(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taskstore::TaskBuilder;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use tempfile::tempdir;
+
+    fn sorted(tasks: &[Task], mode: SortMode) -> Vec<u32> {
+        let mut tasks: Vec<&Task> = tasks.iter().collect();
+        tasks.sort_by(|a, b| mode.compare(a, b));
+        tasks.into_iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn test_sort_modes() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let mut tasks = vec![
+            TaskBuilder::new(1).content("write report").due(day(20)).build(),
+            TaskBuilder::new(2).content("Buy groceries").created_at(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()).build(),
+            TaskBuilder::new(3).content("call dentist").due(day(19)).build(),
+        ];
+        for (task, order) in tasks.iter_mut().zip(["a", "b", "c"]) {
+            task.order = order.to_string();
+        }
+        tasks[0].status = TaskStatus::Done;
+        tasks[2].status = TaskStatus::Doing;
+
+        assert_eq!(sorted(&tasks, SortMode::Manual), [1, 2, 3]);
+        assert_eq!(sorted(&tasks, SortMode::Created), [2, 1, 3]);
+        assert_eq!(sorted(&tasks, SortMode::Due), [3, 1, 2]);
+        assert_eq!(sorted(&tasks, SortMode::Status), [3, 2, 1]);
+        assert_eq!(sorted(&tasks, SortMode::Alphabetical), [2, 3, 1]);
+    }
+
+    #[test]
+    fn test_save_and_load_sort_modes() -> std::io::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(SORTING_FILE_NAME);
+        let path = path.to_str().unwrap();
+        assert_eq!(load_sort_modes(path)?, SortModes::new());
+
+        let mut modes = SortModes::new();
+        modes.set(TaskContainer::Shelved, SortMode::Due);
+        save_sort_modes(&modes, path)?;
+        let loaded = load_sort_modes(path)?;
+        assert_eq!(loaded.get(TaskContainer::Shelved), SortMode::Due);
+        assert_eq!(loaded.get(TaskContainer::Taskpad), SortMode::Manual);
+        Ok(())
+    }
+}
@@ -4,13 +4,17 @@
 //! Core data structures and persistence layer for cyberorganism. Handles task
 //! representation, serialization, and file-based storage operations.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use fuzzy_matcher::FuzzyMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+use crate::recurrence::Recurrence;
+
 /// A single task in the cyberorganism system.
 ///
 /// Each task has a unique identifier, content, creation timestamp,
@@ -31,6 +35,29 @@ pub struct Task {
     pub parent_id: Option<u32>,
     /// IDs of any subtasks this task has
     pub child_ids: Vec<u32>,
+    /// Longer free-form notes attached to the task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Where the task was captured from, if it came from a feed suggestion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<TaskSource>,
+    /// Private tasks (and their subtasks) are never sent to external providers
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub private: bool,
+    /// Lowercase tags without the leading `#`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Sort key of top-level tasks in manual order, see `order_key_between`
+    ///
+    /// Empty until the task is added to a task list.
+    #[serde(default)]
+    pub order: String,
+    /// Day the task is due
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// When the task comes back after it is completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
 }
 
 impl Task {
@@ -44,6 +71,13 @@ impl Task {
             status: TaskStatus::Todo,
             parent_id: None,
             child_ids: Vec::new(),
+            notes: None,
+            source: None,
+            private: false,
+            tags: Vec::new(),
+            order: String::new(),
+            due: None,
+            recurrence: None,
         }
     }
 
@@ -58,6 +92,32 @@ impl Task {
         self.container = TaskContainer::Archived;
     }
 
+    /// The next occurrence of a recurring task, to add when this one is
+    /// completed on `completed_on`.
+    ///
+    /// The occurrence takes the place of this task in its container and
+    /// under its parent, and keeps its content, notes and tags, but not its
+    /// subtasks.
+    pub fn next_occurrence(&self, id: u32, completed_on: NaiveDate) -> Option<Self> {
+        let recurrence = self.recurrence.clone()?;
+        Some(Self {
+            id,
+            content: self.content.clone(),
+            created_at: Utc::now(),
+            container: self.container,
+            status: TaskStatus::Todo,
+            parent_id: self.parent_id,
+            child_ids: Vec::new(),
+            notes: self.notes.clone(),
+            source: None,
+            private: self.private,
+            tags: self.tags.clone(),
+            order: self.order.clone(),
+            due: Some(recurrence.next_due(self.due, completed_on)),
+            recurrence: Some(recurrence),
+        })
+    }
+
     /// Returns true if the task is in the taskpad container
     pub const fn is_in_taskpad(&self) -> bool {
         matches!(self.container, TaskContainer::Taskpad)
@@ -72,10 +132,59 @@ impl Task {
     pub fn add_subtask(&mut self, subtask_id: u32) {
         self.child_ids.push(subtask_id);
     }
+
+    /// Returns true if the task has the tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Adds a tag, returning false if the task already had it
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        if self.has_tag(tag) {
+            return false;
+        }
+        self.tags.push(tag.to_string());
+        true
+    }
+
+    /// Removes a tag, returning false if the task didn't have it
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let len = self.tags.len();
+        self.tags.retain(|t| t != tag);
+        self.tags.len() != len
+    }
+
+    /// Appends a paragraph to the task's notes
+    pub fn append_note(&mut self, text: &str) {
+        match &mut self.notes {
+            Some(notes) if !notes.is_empty() => {
+                notes.push_str("\n\n");
+                notes.push_str(text);
+            }
+            _ => self.notes = Some(text.to_string()),
+        }
+    }
+}
+
+/// Records where a task was captured from, so it links back to the
+/// suggestion that produced it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaskSource {
+    /// Name of the suggestion provider (e.g. "genius")
+    pub provider: String,
+    /// ID of the feed item the task was created from
+    pub item_id: String,
+    /// Query that produced the item
+    pub query: String,
+    /// When the item was captured
+    pub captured_at: DateTime<Utc>,
+    /// Raw metadata of the item, e.g. the original Genius card
+    #[serde(default)]
+    pub metadata: serde_json::Value,
 }
 
 /// Represents where the task is located in our system
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TaskContainer {
     /// Task is ready to be processed
     Taskpad,
@@ -88,6 +197,17 @@ pub enum TaskContainer {
 }
 
 impl TaskContainer {
+    /// Parse a container from its display name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "taskpad" => Some(Self::Taskpad),
+            "backburner" => Some(Self::Backburner),
+            "shelved" => Some(Self::Shelved),
+            "archived" => Some(Self::Archived),
+            _ => None,
+        }
+    }
+
     /// Returns a human-readable name for the container
     pub const fn display_name(self) -> &'static str {
         match self {
@@ -110,6 +230,80 @@ pub enum TaskStatus {
     Done,
 }
 
+/// Digits of order keys, in ascending order
+const ORDER_DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Returns an order key that sorts between `before` and `after`.
+///
+/// Order keys are compared as strings, so a task can be moved between two
+/// others by changing its own key only. `None` stands for the start and the
+/// end of the list. Keys never end with `0`, which leaves room below every key.
+///
+/// Keys at the end of the list count up instead of halving the room left, so
+/// tasks that are always added last keep short keys.
+pub fn order_key_between(before: Option<&str>, after: Option<&str>) -> String {
+    let digits = |key: &str| -> Vec<usize> {
+        key.bytes()
+            .filter_map(|d| ORDER_DIGITS.iter().position(|&o| o == d))
+            .collect()
+    };
+    let before = digits(before.unwrap_or(""));
+    // A key that isn't above `before` can't bound it
+    let after = after.map(digits).filter(|after| *after > before);
+    let key = match after {
+        None if !before.is_empty() => increment(&before),
+        after => midpoint(&before, after.as_deref()),
+    };
+    key.into_iter()
+        .map(|d| char::from(ORDER_DIGITS[d]))
+        .collect()
+}
+
+/// The next key above `key` with as few digits as possible
+///
+/// Bumps the last digit below `z` and restarts the digits after it at `1`. A
+/// key of only `z`s doubles in length, which leaves more keys before the next
+/// one has to grow.
+fn increment(key: &[usize]) -> Vec<usize> {
+    let mut next = key.to_vec();
+    match key.iter().rposition(|&d| d + 1 < ORDER_DIGITS.len()) {
+        Some(position) => {
+            next[position] += 1;
+            next[position + 1..].fill(1);
+        }
+        None => next.resize(key.len() * 2, 1),
+    }
+    next
+}
+
+/// Digits strictly between `low` and `high`, with `None` for no upper bound
+fn midpoint(low: &[usize], high: Option<&[usize]>) -> Vec<usize> {
+    let low_digit = |i: usize| low.get(i).copied().unwrap_or(0);
+    if let Some(high) = high {
+        // Keep the common prefix and look for room after it
+        let common = high.iter().enumerate().take_while(|&(i, &d)| low_digit(i) == d).count();
+        if common > 0 {
+            let mut key = high[..common].to_vec();
+            key.extend(midpoint(low.get(common..).unwrap_or(&[]), Some(&high[common..])));
+            return key;
+        }
+    }
+
+    let (low_first, high_first) = (low_digit(0), high.map_or(ORDER_DIGITS.len(), |high| high[0]));
+    if high_first > low_first + 1 {
+        return vec![(low_first + high_first) / 2];
+    }
+    match high {
+        // `high` continues after its first digit, so that digit alone sorts below it
+        Some(high) if high.len() > 1 => vec![high_first],
+        _ => {
+            let mut key = vec![low_first];
+            key.extend(midpoint(low.get(1..).unwrap_or(&[]), None));
+            key
+        }
+    }
+}
+
 /// Low-level task operations that don't update display state
 pub mod operations {
     use super::*;
@@ -120,10 +314,34 @@ pub mod operations {
     }
 
     /// Add a task to the task list (does not update display)
-    pub fn add_task(tasks: &mut Vec<Task>, task: Task) {
+    ///
+    /// A task without an order key is placed after all others.
+    pub fn add_task(tasks: &mut Vec<Task>, mut task: Task) {
+        if task.order.is_empty() {
+            task.order = order_key_between(last_order_key(tasks, &task.container), None);
+        }
         tasks.push(task);
     }
 
+    /// The highest order key among the tasks of a container
+    fn last_order_key<'a>(tasks: &'a [Task], container: &TaskContainer) -> Option<&'a str> {
+        tasks.iter()
+            .filter(|t| t.container == *container)
+            .map(|t| t.order.as_str())
+            .max()
+    }
+
+    /// Give tasks without an order key one after all others, in list order
+    ///
+    /// Task lists saved before tasks had order keys keep their order.
+    pub fn assign_missing_order_keys(tasks: &mut [Task]) {
+        for index in 0..tasks.len() {
+            if tasks[index].order.is_empty() {
+                tasks[index].order = order_key_between(last_order_key(tasks, &tasks[index].container), None);
+            }
+        }
+    }
+
     /// Update a task in the task list (does not update display)
     pub fn update_task(tasks: &mut [Task], index: usize, update_fn: impl FnOnce(&mut Task)) {
         update_fn(&mut tasks[index]);
@@ -140,6 +358,159 @@ pub mod operations {
         }
     }
 
+    /// Where `move_task` puts a task
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Placement {
+        /// As the last child of a task
+        LastChildOf(u32),
+        /// Right before a task, under the same parent
+        Before(u32),
+        /// Right after a task, under the same parent
+        After(u32),
+    }
+
+    /// How many subtasks of a task are done, counting subtasks at any depth
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Progress {
+        pub done: usize,
+        pub total: usize,
+    }
+
+    impl Progress {
+        /// Share of done subtasks in whole percent, rounded down
+        pub fn percent(self) -> usize {
+            (self.done * 100).checked_div(self.total).unwrap_or(0)
+        }
+    }
+
+    /// Progress of every task that has subtasks, by task ID
+    pub fn progress_by_task(tasks: &[Task]) -> HashMap<u32, Progress> {
+        let by_id: HashMap<u32, &Task> = tasks.iter().map(|t| (t.id, t)).collect();
+        tasks
+            .iter()
+            .filter_map(|task| subtree_progress(&by_id, task).map(|progress| (task.id, progress)))
+            .collect()
+    }
+
+    /// Progress of a task's subtasks, following `child_ids`, or None if it has none
+    fn subtree_progress(by_id: &HashMap<u32, &Task>, task: &Task) -> Option<Progress> {
+        let mut progress = Progress { done: 0, total: 0 };
+        let mut seen = HashSet::from([task.id]);
+        let mut stack = task.child_ids.clone();
+        while let Some(id) = stack.pop() {
+            // Each task counts once in case the hierarchy is broken
+            let Some(child) = by_id.get(&id).filter(|_| seen.insert(id)) else {
+                continue;
+            };
+            progress.total += 1;
+            progress.done += usize::from(child.status == TaskStatus::Done);
+            stack.extend(&child.child_ids);
+        }
+        (progress.total > 0).then_some(progress)
+    }
+
+    /// IDs of a task and its siblings in display order
+    ///
+    /// Subtasks are ordered by their parent's `child_ids`, top-level tasks by
+    /// their order key, among the tasks of the same container. This is the
+    /// manual order; views may sort top-level tasks differently.
+    pub fn sibling_ids(tasks: &[Task], task_id: u32) -> Vec<u32> {
+        let Some(task) = tasks.iter().find(|t| t.id == task_id) else {
+            return Vec::new();
+        };
+        match task.parent_id.and_then(|id| tasks.iter().find(|t| t.id == id)) {
+            Some(parent) => parent.child_ids.clone(),
+            None => {
+                let mut siblings: Vec<&Task> = tasks
+                    .iter()
+                    .filter(|t| t.parent_id.is_none() && t.container == task.container)
+                    .collect();
+                siblings.sort_by(|a, b| a.order.cmp(&b.order));
+                siblings.into_iter().map(|t| t.id).collect()
+            }
+        }
+    }
+
+    /// A task followed by its parent, its parent's parent and so on up to
+    /// its top-level task; empty if the task doesn't exist
+    pub fn ancestry(tasks: &[Task], task_id: u32) -> Vec<&Task> {
+        let mut ancestry = Vec::new();
+        let mut current = Some(task_id);
+        while let Some(task) = current.and_then(|id| tasks.iter().find(|t| t.id == id)) {
+            // Bounded by the number of tasks in case the hierarchy is broken
+            if ancestry.len() > tasks.len() {
+                break;
+            }
+            ancestry.push(task);
+            current = task.parent_id;
+        }
+        ancestry
+    }
+
+    /// Returns true if `ancestor_id` is `task_id` itself or one of its ancestors
+    pub fn is_ancestor(tasks: &[Task], ancestor_id: u32, task_id: u32) -> bool {
+        task_id == ancestor_id || ancestry(tasks, task_id).iter().any(|t| t.id == ancestor_id)
+    }
+
+    /// Move a task, with its subtasks, to another place in the hierarchy (does not update display)
+    ///
+    /// Keeps `parent_id` and `child_ids` consistent. A task placed among
+    /// top-level tasks gets an order key next to its new neighbour and joins
+    /// its container.
+    /// Fails without changing anything if either task doesn't exist or if
+    /// the task would end up below itself.
+    pub fn move_task(tasks: &mut [Task], task_id: u32, placement: Placement) -> Result<(), String> {
+        let (Placement::LastChildOf(anchor_id) | Placement::Before(anchor_id) | Placement::After(anchor_id)) = placement;
+        let (Some(index), Some(anchor_index)) = (find_task_by_id(tasks, task_id), find_task_by_id(tasks, anchor_id)) else {
+            return Err("No matching task found".to_string());
+        };
+        if is_ancestor(tasks, task_id, anchor_id) {
+            return Err(format!("Can't move '{}' below itself", tasks[index].content));
+        }
+
+        let anchor_container = tasks[anchor_index].container;
+        let new_parent = match placement {
+            Placement::LastChildOf(parent_id) => Some(parent_id),
+            Placement::Before(_) | Placement::After(_) => tasks[anchor_index].parent_id,
+        };
+        if let Some(old_parent_index) = tasks[index].parent_id.and_then(|id| find_task_by_id(tasks, id)) {
+            remove_child_from_parent(tasks, old_parent_index, task_id);
+        }
+        tasks[index].parent_id = new_parent;
+
+        let after = usize::from(matches!(placement, Placement::After(_)));
+        match new_parent.and_then(|id| find_task_by_id(tasks, id)) {
+            Some(parent_index) => {
+                let child_ids = &mut tasks[parent_index].child_ids;
+                let position = match placement {
+                    Placement::LastChildOf(_) => child_ids.len(),
+                    Placement::Before(_) | Placement::After(_) => child_ids
+                        .iter()
+                        .position(|&id| id == anchor_id)
+                        .map_or(child_ids.len(), |position| position + after),
+                };
+                child_ids.insert(position, task_id);
+            }
+            None => {
+                assign_missing_order_keys(tasks);
+                tasks[index].container = anchor_container;
+                let siblings: Vec<u32> = sibling_ids(tasks, anchor_id)
+                    .into_iter()
+                    .filter(|&id| id != task_id)
+                    .collect();
+                let anchor_position = siblings.iter().position(|&id| id == anchor_id).unwrap_or(0);
+                let key = |position: Option<usize>| {
+                    let id = siblings.get(position?)?;
+                    find_task_by_id(tasks, *id).map(|index| tasks[index].order.as_str())
+                };
+                let first = if after == 1 { Some(anchor_position) } else { anchor_position.checked_sub(1) };
+                let order = order_key_between(key(first), key(Some(anchor_position + after)));
+                tasks[index].order = order;
+            }
+        }
+        Ok(())
+    }
+
     /// Find the nearest sibling of a task
     /// 
     /// Returns the ID of the nearest sibling task, preferring siblings above the current task
@@ -184,11 +555,23 @@ pub fn find_task_by_id(tasks: &[Task], id: u32) -> Option<usize> {
 /// - Returns None for empty queries
 /// - Only matches full content with tolerance for typos
 /// - Case insensitive
-#[allow(clippy::cast_possible_wrap)]
 pub fn find_task_by_content(
     tasks: &[Task],
     query: &str,
     active_container: TaskContainer,
+) -> Option<usize> {
+    // First try tasks in active container
+    find_task_by_content_where(tasks, query, |task| task.container == active_container)
+        // If no match in active container, try all tasks
+        .or_else(|| find_task_by_content_where(tasks, query, |_| true))
+}
+
+/// Like `find_task_by_content`, among the tasks `keep` accepts
+#[allow(clippy::cast_possible_wrap)]
+pub fn find_task_by_content_where(
+    tasks: &[Task],
+    query: &str,
+    keep: impl Fn(&Task) -> bool,
 ) -> Option<usize> {
     // Return None for empty queries
     if query.is_empty() {
@@ -200,35 +583,10 @@ pub fn find_task_by_content(
     // Calculate minimum score based on query length - allow roughly 1-2 typos
     let min_score = query.len() as i64 * 2 - 3;
 
-    // First try tasks in active container
-    let active_match = tasks
-        .iter()
-        .enumerate()
-        .filter(|(_, task)| task.container == active_container)
-        .filter_map(|(i, task)| {
-            // We want the query length to be close to the task content length
-            let len_diff = (task.content.len() as i64 - query.len() as i64).abs();
-            if len_diff > 3 {
-                // Allow for small differences in length
-                return None;
-            }
-
-            matcher
-                .fuzzy_match(&task.content, query)
-                .filter(|&score| score >= min_score)
-                .map(|score| (i, score))
-        })
-        .max_by_key(|(_, score)| *score)
-        .map(|(i, _)| i);
-
-    if active_match.is_some() {
-        return active_match;
-    }
-
-    // If no match in active container, try all tasks
     tasks
         .iter()
         .enumerate()
+        .filter(|(_, task)| keep(task))
         .filter_map(|(i, task)| {
             // We want the query length to be close to the task content length
             let len_diff = (task.content.len() as i64 - query.len() as i64).abs();
@@ -246,34 +604,66 @@ pub fn find_task_by_content(
         .map(|(i, _)| i)
 }
 
-/// Saves the current tasks to a JSON file.
+/// Returns the path of another file stored in the same workspace
+/// (directory) as the tasks file.
 ///
 /// ### Arguments
-/// * `tasks` - A slice of tasks to save
-/// * `path` - Path to the tasks storage file
-pub fn save_tasks(tasks: &[Task], path: &str) -> std::io::Result<()> {
+/// * `tasks_file` - Path to the tasks storage file
+/// * `file_name` - Name of the workspace file, e.g. "pins.json"
+pub fn workspace_file(tasks_file: &str, file_name: &str) -> String {
+    Path::new(tasks_file)
+        .with_file_name(file_name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Saves a value to a JSON file, creating its directory if needed.
+///
+/// ### Arguments
+/// * `value` - The value to save
+/// * `path` - Path to the storage file
+pub fn save_json<T: Serialize + ?Sized>(value: &T, path: &str) -> std::io::Result<()> {
     // Create parent directory if it doesn't exist
     if let Some(parent) = Path::new(path).parent() {
         fs::create_dir_all(parent)?;
     }
 
-    let json = serde_json::to_string_pretty(tasks)?;
+    let json = serde_json::to_string_pretty(value)?;
     fs::write(path, json)
 }
 
-/// Loads tasks from the JSON file.
+/// Loads a value from a JSON file, or its default if the file doesn't exist.
 ///
 /// ### Arguments
-/// * `path` - Path to the tasks storage file
-pub fn load_tasks(path: &str) -> std::io::Result<Vec<Task>> {
+/// * `path` - Path to the storage file
+pub fn load_json<T: DeserializeOwned + Default>(path: &str) -> std::io::Result<T> {
     if Path::new(path).exists() {
         let json = fs::read_to_string(path)?;
         Ok(serde_json::from_str(&json)?)
     } else {
-        Ok(Vec::new())
+        Ok(T::default())
     }
 }
 
+/// Saves the current tasks to a JSON file.
+///
+/// ### Arguments
+/// * `tasks` - A slice of tasks to save
+/// * `path` - Path to the tasks storage file
+pub fn save_tasks(tasks: &[Task], path: &str) -> std::io::Result<()> {
+    save_json(tasks, path)
+}
+
+/// Loads tasks from the JSON file.
+///
+/// ### Arguments
+/// * `path` - Path to the tasks storage file
+pub fn load_tasks(path: &str) -> std::io::Result<Vec<Task>> {
+    let mut tasks: Vec<Task> = load_json(path)?;
+    operations::assign_missing_order_keys(&mut tasks);
+    Ok(tasks)
+}
+
 #[cfg(test)]
 pub struct TaskBuilder {
     task: Task,
@@ -291,6 +681,13 @@ impl TaskBuilder {
                 status: TaskStatus::Todo,
                 parent_id: None,
                 child_ids: Vec::new(),
+                notes: None,
+                source: None,
+                private: false,
+                tags: Vec::new(),
+                order: String::new(),
+                due: None,
+                recurrence: None,
             },
         }
     }
@@ -305,6 +702,18 @@ impl TaskBuilder {
         self
     }
 
+    #[allow(dead_code)]
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.task.created_at = created_at;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn due(mut self, due: NaiveDate) -> Self {
+        self.task.due = Some(due);
+        self
+    }
+
     #[allow(dead_code)]
     pub fn parent(mut self, parent_id: u32) -> Self {
         self.task.parent_id = Some(parent_id);
@@ -317,6 +726,18 @@ impl TaskBuilder {
         self
     }
 
+    #[allow(dead_code)]
+    pub fn private(mut self) -> Self {
+        self.task.private = true;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.task.tags = tags.iter().map(|t| (*t).to_string()).collect();
+        self
+    }
+
     pub fn build(self) -> Task {
         self.task
     }
@@ -326,6 +747,7 @@ impl TaskBuilder {
 mod tests {
     #[allow(clippy::wildcard_imports)]
     use super::*;
+    use proptest::prelude::*;
     use tempfile::tempdir;
 
     fn setup_test_tasks() -> Vec<Task> {
@@ -355,6 +777,32 @@ mod tests {
         assert!(matches!(task.container, TaskContainer::Archived));
     }
 
+    #[test]
+    fn test_task_append_note() {
+        let mut task = Task::new(1, "Test task".to_string());
+        task.append_note("First");
+        task.append_note("Second");
+        assert_eq!(task.notes.as_deref(), Some("First\n\nSecond"));
+    }
+
+    #[test]
+    fn test_load_tasks_without_new_fields() -> std::io::Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("tasks.json");
+        fs::write(
+            &file_path,
+            r#"[{"id":1,"content":"Old task","created_at":"2024-01-01T00:00:00Z",
+                "container":"Taskpad","status":"Todo","parent_id":null,"child_ids":[]}]"#,
+        )?;
+
+        let tasks = load_tasks(file_path.to_str().unwrap())?;
+        assert!(tasks[0].notes.is_none());
+        assert!(tasks[0].source.is_none());
+        assert!(!tasks[0].order.is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn test_task_container_methods() {
         let task = Task::new(1, "Test task".to_string());
@@ -370,6 +818,12 @@ mod tests {
         assert_eq!(TaskContainer::Archived.display_name(), "archived");
     }
 
+    #[test]
+    fn test_task_container_from_name() {
+        assert_eq!(TaskContainer::from_name("Shelved"), Some(TaskContainer::Shelved));
+        assert_eq!(TaskContainer::from_name("inbox"), None);
+    }
+
     #[test]
     fn test_find_task_by_id() {
         let tasks = setup_test_tasks();
@@ -444,6 +898,130 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_order_key_between() {
+        assert_eq!(order_key_between(None, None), "i");
+        assert_eq!(order_key_between(Some("i"), None), "j");
+        assert_eq!(order_key_between(Some("iz"), None), "j1");
+        assert_eq!(order_key_between(Some("zz"), None), "zz11");
+        assert_eq!(order_key_between(None, Some("i")), "9");
+        assert_eq!(order_key_between(Some("i"), Some("j")), "ii");
+        assert_eq!(order_key_between(Some("i"), Some("j1")), "j");
+        assert_eq!(order_key_between(None, Some("01")), "00i");
+        // A bound that isn't above `before` is ignored
+        assert_eq!(order_key_between(Some("r"), Some("i")), "s");
+    }
+
+    #[test]
+    fn test_added_tasks_keep_short_order_keys() {
+        let mut tasks = Vec::new();
+        for id in 1..=1000 {
+            operations::add_task(&mut tasks, TaskBuilder::new(id).content("Task").build());
+        }
+        assert!(tasks.windows(2).all(|pair| pair[0].order < pair[1].order));
+        assert!(tasks.iter().all(|task| task.order.len() <= 4));
+    }
+
+    proptest! {
+        #[test]
+        fn order_keys_stay_sorted(positions in prop::collection::vec(any::<prop::sample::Index>(), 1..60)) {
+            let mut keys: Vec<String> = Vec::new();
+            for position in positions {
+                let at = position.index(keys.len() + 1);
+                let before = at.checked_sub(1).map(|i| keys[i].as_str());
+                let key = order_key_between(before, keys.get(at).map(String::as_str));
+                prop_assert!(before.is_none_or(|before| before < key.as_str()));
+                prop_assert!(keys.get(at).is_none_or(|after| key < *after));
+                prop_assert!(!key.ends_with('0'));
+                keys.insert(at, key);
+            }
+        }
+    }
+
+    /// Checks that every parent lists its children and every child points back
+    fn assert_consistent(tasks: &[Task]) {
+        for task in tasks {
+            for &child_id in &task.child_ids {
+                let child = &tasks[find_task_by_id(tasks, child_id).unwrap()];
+                assert_eq!(child.parent_id, Some(task.id), "child {child_id} of {}", task.id);
+            }
+            if let Some(parent_id) = task.parent_id {
+                let parent = &tasks[find_task_by_id(tasks, parent_id).unwrap()];
+                assert_eq!(parent.child_ids.iter().filter(|&&id| id == task.id).count(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ancestry() {
+        let tasks = vec![
+            TaskBuilder::new(1).children(vec![2]).build(),
+            TaskBuilder::new(2).parent(1).build(),
+            // A parent cycle, as in a hand-edited tasks file
+            TaskBuilder::new(3).parent(4).build(),
+            TaskBuilder::new(4).parent(3).build(),
+        ];
+        let ids = |task_id| operations::ancestry(&tasks, task_id).iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(2), [2, 1]);
+        assert!(ids(9).is_empty());
+        assert!(ids(3).len() <= tasks.len() + 1);
+        assert!(operations::is_ancestor(&tasks, 1, 2));
+        assert!(!operations::is_ancestor(&tasks, 1, 3));
+    }
+
+    #[test]
+    fn test_move_task() {
+        use operations::{Placement, move_task, sibling_ids};
+        let mut tasks = setup_test_tasks();
+        tasks.push(TaskBuilder::new(4).content("Plan trip").children(vec![5]).build());
+        tasks.push(TaskBuilder::new(5).content("Book flights").parent(4).build());
+
+        // Indent 2 under 1, then put 3 before it
+        move_task(&mut tasks, 2, Placement::LastChildOf(1)).unwrap();
+        move_task(&mut tasks, 3, Placement::Before(2)).unwrap();
+        assert_eq!(sibling_ids(&tasks, 2), [3, 2]);
+        assert_eq!(tasks[find_task_by_id(&tasks, 3).unwrap()].parent_id, Some(1));
+
+        // Outdent 3 to right after 4, which gives it an order key after 4
+        move_task(&mut tasks, 3, Placement::After(4)).unwrap();
+        assert_eq!(sibling_ids(&tasks, 3), [1, 4, 3]);
+        assert_eq!(tasks[find_task_by_id(&tasks, 3).unwrap()].parent_id, None);
+
+        // A task never ends up below itself
+        assert!(move_task(&mut tasks, 4, Placement::LastChildOf(5)).is_err());
+        assert!(move_task(&mut tasks, 4, Placement::After(4)).is_err());
+        assert!(move_task(&mut tasks, 4, Placement::LastChildOf(99)).is_err());
+        move_task(&mut tasks, 4, Placement::LastChildOf(2)).unwrap();
+        assert!(operations::is_ancestor(&tasks, 1, 5));
+        assert_consistent(&tasks);
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut tasks = vec![
+            TaskBuilder::new(1).children(vec![2, 3]).build(),
+            TaskBuilder::new(2).parent(1).children(vec![4]).build(),
+            TaskBuilder::new(3).parent(1).build(),
+            TaskBuilder::new(4).parent(2).build(),
+        ];
+        tasks[2].complete();
+        tasks[3].complete();
+        let progress = operations::progress_by_task(&tasks);
+        assert_eq!(progress[&1], operations::Progress { done: 2, total: 3 });
+        assert_eq!(progress[&1].percent(), 66);
+        assert_eq!(progress[&2].percent(), 100);
+        assert!(!progress.contains_key(&3));
+    }
+
+    #[test]
+    fn test_workspace_file() {
+        assert_eq!(workspace_file("tasks.json", "pins.json"), "pins.json");
+        assert_eq!(
+            workspace_file("/home/me/notes/tasks.json", "pins.json"),
+            "/home/me/notes/pins.json"
+        );
+    }
+
     #[test]
     fn test_load_tasks_nonexistent_file() -> std::io::Result<()> {
         let dir = tempdir()?;